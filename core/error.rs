@@ -0,0 +1,18 @@
+//! Shared constructors for errors raised while parsing constraints and the lists of the
+//! `__next_step` relay protocol.
+//!
+//! Every message is prefixed with `coinduction:` so failures from any stage of the
+//! multi-stage expansion are reported consistently.
+
+use proc_macro2::Span;
+use syn::Error;
+
+/// A bracketed, comma-separated list was expected
+pub fn expected_list(span: Span, what: &str) -> Error {
+    Error::new(span, format!("coinduction: expected a list of {}", what))
+}
+
+/// A `Type: Trait` constraint was expected
+pub fn expected_constraint(span: Span) -> Error {
+    Error::new(span, "coinduction: expected a constraint `Type: Trait`")
+}
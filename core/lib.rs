@@ -0,0 +1,171 @@
+//! The constraint matching and solving layer of the `coinduction` macros, for tools which work
+//! with the same `Type: Trait` constraints outside of a macro expansion.
+//!
+//! [`solver::Constraint`] parses and prints a `Type: Trait` bound, and [`matching::Matching`]
+//! matches a pattern over generic params against a term, yielding the
+//! [`matching::Substitute`] which turns one into the other:
+//!
+//! ```
+//! use coinduction_core::matching::Matching;
+//! use coinduction_core::solver::Constraint;
+//! use std::collections::HashSet;
+//! use syn::{parse_quote, GenericArgument, GenericParam};
+//!
+//! let rule: Constraint = parse_quote!(Vec<T>: Clone);
+//! let params: HashSet<GenericParam> = [parse_quote!(T)].into_iter().collect();
+//! let substitute = rule
+//!     .matches(&parse_quote!(Vec<String>: Clone), &params)
+//!     .unwrap();
+//! assert_eq!(
+//!     substitute.get(&parse_quote!(T)),
+//!     Some(&GenericArgument::Type(parse_quote!(String)))
+//! );
+//! assert!(rule.matches(&parse_quote!(Box<u8>: Clone), &params).is_err());
+//! ```
+//!
+//! A [`solver::Solver`] holds the graph of constraints and the constraints they depend on.
+//! The bounds of an impl which lie on a cycle of it are replaced by the dependencies leaving
+//! the cycle, which is how `#[coinduction]` breaks mutually recursive bounds:
+//!
+//! ```
+//! use coinduction_core::gotgraph::prelude::*;
+//! use coinduction_core::solver::{add_dependency, Constraint, ConstraintIndex, Solver};
+//! use std::collections::HashSet;
+//! use syn::{parse_quote, Generics, WhereClause};
+//!
+//! let mut graph = VecGraph::default();
+//! let mut index = ConstraintIndex::new(&graph);
+//! let expr = index.add(&mut graph, parse_quote!(Expr: Eval));
+//! let term = index.add(&mut graph, parse_quote!(Term: Eval));
+//! let number = index.add(&mut graph, parse_quote!(i32: Eval));
+//! add_dependency(&mut graph, expr, term);
+//! add_dependency(&mut graph, term, expr);
+//! add_dependency(&mut graph, term, number);
+//! let solver = Solver {
+//!     graph,
+//!     generic_params: HashSet::new(),
+//! };
+//!
+//! let mut generics = Generics::default();
+//! generics.where_clause = Some(parse_quote!(where Term: Eval));
+//! solver.break_cycles(&mut generics, &parse_quote!(Expr));
+//! let expected: WhereClause = parse_quote!(where i32: Eval);
+//! assert_eq!(generics.where_clause, Some(expected));
+//! ```
+//!
+//! [`solve`] does all of this for a set of impls, given as rules of their generics, head and
+//! bounds, and returns the generics of the impl whose head matches the seed with its bounds
+//! reduced:
+//!
+//! ```
+//! use syn::{parse_quote, Generics, WhereClause};
+//!
+//! let rules = vec![
+//!     (
+//!         parse_quote!(<T>),
+//!         parse_quote!(List<T>: Show),
+//!         vec![parse_quote!(Node<T>: Show)],
+//!     ),
+//!     (
+//!         parse_quote!(<T>),
+//!         parse_quote!(Node<T>: Show),
+//!         vec![parse_quote!(List<T>: Show), parse_quote!(T: Show)],
+//!     ),
+//! ];
+//! let generics = coinduction_core::solve(rules, parse_quote!(List<T>: Show));
+//! let expected: Generics = parse_quote!(<T>);
+//! assert_eq!(generics.params, expected.params);
+//! let expected: WhereClause = parse_quote!(where T: Show);
+//! assert_eq!(generics.where_clause, Some(expected));
+//! ```
+//!
+//! Parsing and matching never abort. A generic argument carrying attributes other than `doc`
+//! and lint attributes fails the match with a [`matching::MatchError`] for which
+//! [`matching::MatchError::is_unsupported`] holds.
+
+use syn::*;
+
+pub mod error;
+pub mod matching;
+pub mod solver;
+
+/// The graph library the [`solver::Solver`] is built on
+pub use gotgraph;
+
+pub use solver::solve;
+
+/// Unwrap TypeGroup/TypeParen which may be introduced during macro expansion
+pub fn unwrap_type_group(typ: Type) -> Type {
+    match typ {
+        Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. }) => {
+            unwrap_type_group(*elem)
+        }
+        _ => typ,
+    }
+}
+
+/// Whether `path` names a marker trait of the language such as `Sized` or `Copy`; these are
+/// obligations of the compiler itself and are never resolved coinductively. Only the bare name
+/// as in the prelude and the paths through `core::marker` or `std::marker` count, so a trait
+/// such as `shapes::Copy` of the user's is resolved as any other.
+pub fn is_marker_trait(path: &Path) -> bool {
+    const MARKER_TRAITS: &[&str] = &["Sized", "Copy", "Send", "Sync", "Unpin"];
+    let segments: Vec<_> = path.segments.iter().collect();
+    let (last, module) = match segments.as_slice() {
+        [last] if path.leading_colon.is_none() => (last, None),
+        [krate, marker, last] => (last, Some((krate, marker))),
+        _ => return false,
+    };
+    let in_marker_module = module.is_none_or(|(krate, marker)| {
+        (krate.ident == "core" || krate.ident == "std")
+            && marker.ident == "marker"
+            && krate.arguments.is_none()
+            && marker.arguments.is_none()
+    });
+    in_marker_module
+        && last.arguments.is_none()
+        && MARKER_TRAITS.iter().any(|name| last.ident == name)
+}
+
+/// Parse a bracketed, comma-separated list, reporting `what` if the brackets are missing
+pub fn parse_list<T>(
+    input: parse::ParseStream,
+    what: &str,
+    parser: fn(parse::ParseStream) -> syn::Result<T>,
+) -> syn::Result<punctuated::Punctuated<T, Token![,]>> {
+    if !input.peek(token::Bracket) {
+        return Err(error::expected_list(input.span(), what));
+    }
+    let content;
+    bracketed!(content in input);
+    content.parse_terminated(parser, Token![,])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_marker_trait() {
+        for path in [
+            parse_quote!(Sized),
+            parse_quote!(Copy),
+            parse_quote!(core::marker::Send),
+            parse_quote!(::std::marker::Sync),
+            parse_quote!(std::marker::Unpin),
+        ] {
+            assert!(is_marker_trait(&path), "{}", template_quote::quote!(#path));
+        }
+        // Traits of the user's which share a name with a marker trait
+        for path in [
+            parse_quote!(mymod::Send),
+            parse_quote!(crate::Copy),
+            parse_quote!(::Sized),
+            parse_quote!(std::Sync),
+            parse_quote!(core::marker::Clone),
+            parse_quote!(Copy<T>),
+        ] {
+            assert!(!is_marker_trait(&path), "{}", template_quote::quote!(#path));
+        }
+    }
+}
@@ -0,0 +1,3306 @@
+use core::ops::Deref;
+use proc_macro2::Span;
+use std::collections::{HashMap, HashSet};
+use syn::{punctuated::Punctuated, spanned::Spanned, visit::Visit, *};
+
+use crate::solver::{Constraint, GroupStripper};
+
+/// Why two terms do not match: the offending part of each side and a short reason
+#[derive(Clone, Debug)]
+pub struct MatchError {
+    pub lhs: Span,
+    pub rhs: Span,
+    pub reason: &'static str,
+    /// The param which the match would have bound to two different arguments, if that is why
+    pub conflict: Option<Box<Conflict>>,
+}
+
+/// A param which a match binds to two different arguments, each keeping its span
+#[derive(Clone, Debug)]
+pub struct Conflict {
+    pub param: GenericParam,
+    pub first: GenericArgument,
+    pub second: GenericArgument,
+}
+
+impl MatchError {
+    pub fn new(lhs: &impl Spanned, rhs: &impl Spanned, reason: &'static str) -> Self {
+        MatchError {
+            lhs: lhs.span(),
+            rhs: rhs.span(),
+            reason,
+            conflict: None,
+        }
+    }
+
+    /// Records that `param` was bound to both `first` and `second`, unless a conflict of a
+    /// param nested in them was recorded already
+    fn with_conflict(
+        mut self,
+        param: &GenericParam,
+        first: &GenericArgument,
+        second: &GenericArgument,
+    ) -> Self {
+        self.conflict.get_or_insert_with(|| {
+            Box::new(Conflict {
+                param: param.clone(),
+                first: first.clone(),
+                second: second.clone(),
+            })
+        });
+        self
+    }
+
+    /// Whether the match failed as an argument carries attributes which a substitution cannot
+    /// drop, a mistake in the input rather than a mismatch
+    pub fn is_unsupported(&self) -> bool {
+        self.reason == UNSUPPORTED_ATTRIBUTES
+    }
+}
+
+/// The reason of a [`MatchError`] binding a param to an argument with attributes other than
+/// `doc` and lint attributes, which could change the meaning of the substituted code
+pub const UNSUPPORTED_ATTRIBUTES: &str =
+    "the generic argument contains attributes which are not supported in substitutions";
+
+impl core::fmt::Display for MatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(conflict) = &self.conflict {
+            let Conflict {
+                param,
+                first,
+                second,
+            } = conflict.as_ref();
+            let param = match param {
+                GenericParam::Type(param) => param.ident.to_string(),
+                GenericParam::Const(param) => param.ident.to_string(),
+                GenericParam::Lifetime(param) => param.lifetime.to_string(),
+            };
+            // A param renamed apart is named as it is written in the rule
+            write!(
+                f,
+                "`{}` would need to be both `{}` and `{}`: ",
+                param.strip_prefix(RENAMED_PREFIX).unwrap_or(&param),
+                template_quote::quote!(#first),
+                template_quote::quote!(#second)
+            )?;
+        }
+        f.write_str(self.reason)?;
+        // The source text is only known for spans of the macro input
+        if let (Some(lhs), Some(rhs)) = (self.lhs.source_text(), self.rhs.source_text()) {
+            write!(f, " (`{}` against `{}`)", lhs, rhs)?;
+        }
+        Ok(())
+    }
+}
+
+pub type MatchResult<T = Substitute> = core::result::Result<T, MatchError>;
+
+/// Fails with `reason` at `lhs` and `rhs` unless `condition` holds
+fn ensure(
+    condition: bool,
+    lhs: &impl Spanned,
+    rhs: &impl Spanned,
+    reason: &'static str,
+) -> MatchResult<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(MatchError::new(lhs, rhs, reason))
+    }
+}
+
+fn has_attributes_recursive(arg: &GenericArgument) -> bool {
+    struct AttributeChecker(bool);
+    impl<'ast> Visit<'ast> for AttributeChecker {
+        fn visit_attribute(&mut self, _: &'ast syn::Attribute) {
+            self.0 = true;
+        }
+    }
+    let mut checker = AttributeChecker(false);
+    checker.visit_generic_argument(arg);
+    checker.0
+}
+
+/// Attributes which only affect lints or docs, dropped from arguments; `cfg` is not among them,
+/// as dropping it would make the gated code unconditional
+const INERT_ATTRIBUTES: &[&str] = &["doc", "allow", "warn", "deny", "forbid", "expect"];
+
+/// Removes the inert attributes from `arg`, leaving any others in place
+///
+/// This works on the tokens so that attributes anywhere within `arg` are reached, and leaves the
+/// tokens of macro invocations alone, since `#[..]` there need not be an attribute.
+fn strip_inert_attributes(arg: &GenericArgument) -> GenericArgument {
+    use proc_macro2::{Delimiter, TokenStream, TokenTree};
+    fn is_inert(attr: &TokenStream) -> bool {
+        matches!(
+            attr.clone().into_iter().next(),
+            Some(TokenTree::Ident(ident)) if INERT_ATTRIBUTES.iter().any(|name| ident == name)
+        )
+    }
+    fn strip(stream: TokenStream) -> TokenStream {
+        let tokens: Vec<TokenTree> = stream.into_iter().collect();
+        let mut stripped = Vec::with_capacity(tokens.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            match &tokens[i] {
+                TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                    let bang = matches!(&tokens.get(i + 1), Some(TokenTree::Punct(p)) if p.as_char() == '!');
+                    let body = i + 1 + bang as usize;
+                    if let Some(TokenTree::Group(group)) = tokens.get(body) {
+                        if group.delimiter() == Delimiter::Bracket && is_inert(&group.stream()) {
+                            i = body + 1;
+                            continue;
+                        }
+                    }
+                    stripped.push(tokens[i].clone());
+                }
+                TokenTree::Group(group) if !matches!(stripped.last(), Some(TokenTree::Punct(p)) if p.as_char() == '!') =>
+                {
+                    let mut inner =
+                        proc_macro2::Group::new(group.delimiter(), strip(group.stream()));
+                    inner.set_span(group.span());
+                    stripped.push(TokenTree::Group(inner));
+                }
+                token => stripped.push(token.clone()),
+            }
+            i += 1;
+        }
+        stripped.into_iter().collect()
+    }
+    if !has_attributes_recursive(arg) {
+        return arg.clone();
+    }
+    syn::parse2(strip(template_quote::quote!(#arg))).unwrap_or_else(|_| arg.clone())
+}
+
+/// Whether `param` appears within `arg`, other than as the whole of it
+pub fn occurs_in(param: &GenericParam, arg: &GenericArgument) -> bool {
+    struct Finder<'a> {
+        ident: &'a Ident,
+        lifetime: bool,
+        found: bool,
+    }
+    impl<'ast> Visit<'ast> for Finder<'_> {
+        fn visit_path(&mut self, path: &'ast Path) {
+            self.found |= !self.lifetime && path.is_ident(self.ident);
+            syn::visit::visit_path(self, path);
+        }
+        fn visit_lifetime(&mut self, lifetime: &'ast Lifetime) {
+            self.found |= self.lifetime && &lifetime.ident == self.ident;
+        }
+    }
+    let (ident, lifetime) = match param {
+        GenericParam::Type(type_param) => (&type_param.ident, false),
+        GenericParam::Lifetime(lifetime_param) => (&lifetime_param.lifetime.ident, true),
+        GenericParam::Const(const_param) => (&const_param.ident, false),
+    };
+    let mut finder = Finder {
+        ident,
+        lifetime,
+        found: false,
+    };
+    finder.visit_generic_argument(arg);
+    // Binding a param to itself is harmless
+    finder.found && !is_param_itself(param, arg)
+}
+
+/// Whether `arg` is `param` itself
+fn is_param_itself(param: &GenericParam, arg: &GenericArgument) -> bool {
+    match (param, arg) {
+        (GenericParam::Lifetime(lifetime_param), GenericArgument::Lifetime(lt)) => {
+            lifetime_param.lifetime.ident == lt.ident
+        }
+        (
+            GenericParam::Type(TypeParam { ident, .. })
+            | GenericParam::Const(ConstParam { ident, .. }),
+            GenericArgument::Type(Type::Path(TypePath { qself: None, path }))
+            | GenericArgument::Const(Expr::Path(ExprPath {
+                qself: None, path, ..
+            })),
+        ) => path.is_ident(ident),
+        _ => false,
+    }
+}
+
+/// Whether two arguments are the same once invisible groups and parentheses are ignored, so
+/// that a param bound both through a `$t:ty` fragment and directly does not conflict
+fn same_argument(lhs: &GenericArgument, rhs: &GenericArgument) -> bool {
+    let strip = |arg: &GenericArgument| {
+        let mut arg = arg.clone();
+        visit_mut::VisitMut::visit_generic_argument_mut(&mut GroupStripper, &mut arg);
+        arg
+    };
+    strip(lhs) == strip(rhs)
+}
+
+/// Whether `lifetime` is the anonymous lifetime `'_`, which an elided lifetime is bound as
+fn is_anonymous(lifetime: &Lifetime) -> bool {
+    lifetime.ident == "_"
+}
+
+fn is_anonymous_argument(arg: &GenericArgument) -> bool {
+    matches!(arg, GenericArgument::Lifetime(lifetime) if is_anonymous(lifetime))
+}
+
+/// Cleans `param` by removing attributes, bounds, colon_token for consistent comparison
+pub(crate) fn clean_param(mut param: GenericParam) -> GenericParam {
+    match &mut param {
+        GenericParam::Type(type_param) => {
+            type_param.attrs = vec![];
+            type_param.bounds = Default::default();
+            type_param.colon_token = None;
+            type_param.eq_token = None;
+            type_param.default = None;
+        }
+        GenericParam::Lifetime(lifetime_param) => {
+            lifetime_param.attrs = vec![];
+            lifetime_param.bounds = Default::default();
+            lifetime_param.colon_token = None;
+        }
+        GenericParam::Const(const_param) => {
+            const_param.attrs = vec![];
+            const_param.eq_token = None;
+            const_param.default = None;
+        }
+    }
+    param
+}
+
+/// The arguments which generic params are bound to by a match
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct Substitute(pub HashMap<GenericParam, GenericArgument>);
+
+impl Deref for Substitute {
+    type Target = HashMap<GenericParam, GenericArgument>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Substitute {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn from_param_arg(param: GenericParam, arg: GenericArgument) -> MatchResult<Self> {
+        Self::new().insert(param, arg)
+    }
+
+    pub fn insert(mut self, param: GenericParam, arg: GenericArgument) -> MatchResult<Self> {
+        let param = clean_param(param);
+
+        // Lint and doc attributes are dropped; anything else could change the meaning
+        let arg = strip_inert_attributes(&arg);
+        if has_attributes_recursive(&arg) {
+            return Err(MatchError::new(&param, &arg, UNSUPPORTED_ATTRIBUTES));
+        }
+
+        // A binding such as `T -> Vec<T>` grows its target on every application
+        if occurs_in(&param, &arg) {
+            return Err(MatchError::new(
+                &param,
+                &arg,
+                "the param occurs in its own binding",
+            ));
+        }
+
+        // Use HashMap::entry() for more efficient insertion
+        use std::collections::hash_map::Entry;
+        match self.0.entry(param) {
+            // An anonymous lifetime stands for an elided one, and gives way to any other lifetime
+            Entry::Occupied(mut existing_entry) if is_anonymous_argument(existing_entry.get()) => {
+                existing_entry.insert(arg);
+                Ok(self)
+            }
+            Entry::Occupied(_) if is_anonymous_argument(&arg) => Ok(self),
+            Entry::Occupied(existing_entry) => {
+                let existing = existing_entry.get();
+                ensure(
+                    same_argument(existing, &arg),
+                    existing,
+                    &arg,
+                    "the param is bound to different arguments",
+                )
+                .map_err(|error| error.with_conflict(existing_entry.key(), existing, &arg))?;
+                Ok(self)
+            }
+            Entry::Vacant(vacant_entry) => {
+                vacant_entry.insert(arg.clone());
+                Ok(self)
+            }
+        }
+    }
+
+    /// Looks up the expression bound to the const parameter named `ident`, whatever its type
+    pub fn get_const(&self, ident: &Ident) -> Option<&Expr> {
+        self.0.iter().find_map(|(param, arg)| match (param, arg) {
+            (GenericParam::Const(const_param), GenericArgument::Const(expr))
+                if &const_param.ident == ident =>
+            {
+                Some(expr)
+            }
+            _ => None,
+        })
+    }
+
+    /// Drops the bindings of `params`, e.g. lifetimes shadowed by a `for<..>` binder
+    pub fn without(&self, params: &[GenericParam]) -> Self {
+        Substitute(
+            self.0
+                .iter()
+                .filter(|(param, _)| !params.contains(param))
+                .map(|(param, arg)| (param.clone(), arg.clone()))
+                .collect(),
+        )
+    }
+
+    /// Whether applying the substitution to `original` yields exactly `replaced`
+    #[cfg(test)]
+    pub fn verify_replacement(&self, original: &Type, replaced: &Type) -> bool {
+        let mut original = original.clone();
+        original.replace(self);
+        &original == replaced
+    }
+
+    /// Binds `param` to `arg`, applying the bindings so far to `arg` and the new one to them, so
+    /// that no bound param is left in any argument, as unification needs
+    fn insert_resolved(
+        mut self,
+        param: GenericParam,
+        mut arg: GenericArgument,
+    ) -> MatchResult<Self> {
+        arg.replace(&self);
+        let substitute = Substitute::from_param_arg(param.clone(), arg.clone())?;
+        for (bound, value) in self.0.iter_mut() {
+            value.replace(&substitute);
+            if occurs_in(bound, value) {
+                return Err(MatchError::new(
+                    bound,
+                    value,
+                    "the param occurs in its own binding",
+                ));
+            }
+        }
+        self.insert(param, arg)
+    }
+
+    /// Merges `other` into the substitution
+    ///
+    /// The arguments of a plain match are terms of its right-hand side, so they are bound as
+    /// they are, and a param bound to two different arguments is a conflict. When unifying, the
+    /// arguments may still contain params: they are resolved, and the two arguments of a param
+    /// bound on both sides are unified with the params of either bindable.
+    pub fn combine<'a>(
+        mut self,
+        other: Self,
+        cx: impl Into<MatchContext<'a>>,
+    ) -> MatchResult<Self> {
+        let cx = cx.into();
+        for (param, mut arg) in other.0 {
+            if cx.target_params.is_none() {
+                self = self.insert(param, arg)?;
+                continue;
+            }
+            arg.replace(&self);
+            self = match self.get(&param) {
+                Some(existing)
+                    if !same_argument(existing, &arg)
+                        && !is_anonymous_argument(existing)
+                        && !is_anonymous_argument(&arg) =>
+                {
+                    let either = MatchContext {
+                        target_params: Some(cx.params),
+                        ..cx
+                    };
+                    // The bindings are unified, and a failure reports this param unless one
+                    // nested in the arguments conflicted first
+                    existing
+                        .matches_in(&arg, either)
+                        .and_then(|unifier| self.clone().combine(unifier, cx))
+                        .map_err(|error| error.with_conflict(&param, existing, &arg))?
+                }
+                _ => self.insert_resolved(param, arg)?,
+            };
+        }
+        Ok(self)
+    }
+
+    /// The substitution which applies `self` and then `other`: each param bound by `self` is
+    /// bound to its argument with `other` applied, and the params only `other` binds are bound
+    /// as there. Composing is associative, `a.compose(b)?.compose(c)` being
+    /// `a.compose(b.compose(c)?)`, and drops the bindings of a param to itself.
+    ///
+    /// Unlike [`Substitute::combine`], which merges the bindings of two matches of the same
+    /// term and, when unifying, unifies the arguments a param is bound to on both sides,
+    /// composing never unifies: a param bound on both sides must end up with the same argument
+    /// either way, and no param may occur in its resulting argument.
+    pub fn compose(self, other: Self) -> MatchResult<Self> {
+        let mut composed = HashMap::new();
+        for (param, mut arg) in self.0 {
+            arg.replace(&other);
+            if let Some(theirs) = other.get(&param) {
+                if !same_argument(&arg, theirs) {
+                    return Err(MatchError::new(
+                        &arg,
+                        theirs,
+                        "the param is bound to different arguments",
+                    )
+                    .with_conflict(&param, &arg, theirs));
+                }
+            }
+            composed.insert(param, arg);
+        }
+        for (param, arg) in other.0 {
+            composed.entry(param).or_insert(arg);
+        }
+        composed.retain(|param, arg| !is_param_itself(param, arg));
+        if let Some((param, arg)) = composed.iter().find(|(param, arg)| occurs_in(param, arg)) {
+            return Err(MatchError::new(
+                param,
+                arg,
+                "the param occurs in its own binding",
+            ));
+        }
+        Ok(Substitute(composed))
+    }
+}
+
+/// Prepended to the names of params renamed apart by [`rename_apart`]
+const RENAMED_PREFIX: &str = "__rule_";
+
+/// Renames `params` to fresh names, returning the renaming and each fresh param with its original
+///
+/// The params of a rule head are all bound when the rule applies, so renaming them keeps them
+/// from being confused with the params of whatever the rule is applied to.
+pub fn rename_apart(
+    params: &HashSet<GenericParam>,
+) -> (Substitute, HashMap<GenericParam, GenericParam>) {
+    use syn::ext::IdentExt;
+    let fresh = |ident: &Ident| {
+        Ident::new(
+            &format!("{}{}", RENAMED_PREFIX, ident.unraw()),
+            ident.span(),
+        )
+    };
+    let mut renaming = Substitute::new();
+    let mut renamed = HashMap::new();
+    for param in params {
+        let (new_param, arg) = match param {
+            GenericParam::Type(type_param) => {
+                let ident = fresh(&type_param.ident);
+                (
+                    GenericParam::Type(TypeParam::from(ident.clone())),
+                    GenericArgument::Type(Type::Path(TypePath {
+                        qself: None,
+                        path: ident.into(),
+                    })),
+                )
+            }
+            GenericParam::Lifetime(lifetime_param) => {
+                let lifetime = &lifetime_param.lifetime;
+                let lifetime = Lifetime {
+                    apostrophe: lifetime.apostrophe,
+                    ident: fresh(&lifetime.ident),
+                };
+                (
+                    GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())),
+                    GenericArgument::Lifetime(lifetime),
+                )
+            }
+            GenericParam::Const(const_param) => {
+                let ident = fresh(&const_param.ident);
+                (
+                    GenericParam::Const(ConstParam {
+                        attrs: vec![],
+                        ident: ident.clone(),
+                        eq_token: None,
+                        default: None,
+                        ..const_param.clone()
+                    }),
+                    GenericArgument::Const(Expr::Path(ExprPath {
+                        attrs: vec![],
+                        qself: None,
+                        path: ident.into(),
+                    })),
+                )
+            }
+        };
+        renaming = renaming
+            .insert(param.clone(), arg)
+            .expect("params are renamed to fresh names");
+        renamed.insert(new_param, param.clone());
+    }
+    (renaming, renamed)
+}
+
+/// Unifies `pattern` with `target`, where params of either side may be bound, returning the
+/// bindings of `pattern_params` and of `target_params` separately
+///
+/// The pattern's params are renamed apart first, so a param of the same name on both sides is
+/// two different params. A target param bound to a term of the pattern means the pattern only
+/// applies to an instance of the target; such a term may mention the renamed pattern params.
+pub fn unify<T: Matching + Clone>(
+    pattern: &T,
+    target: &T,
+    pattern_params: &HashSet<GenericParam>,
+    target_params: &HashSet<GenericParam>,
+    loose_paths: bool,
+) -> MatchResult<(Substitute, Substitute)> {
+    let (renaming, renamed) = rename_apart(pattern_params);
+    let mut pattern = pattern.clone();
+    pattern.replace(&renaming);
+    let target_params: HashSet<_> = target_params.iter().cloned().map(clean_param).collect();
+    let params = renamed
+        .keys()
+        .cloned()
+        .chain(target_params.clone())
+        .collect();
+    let unifier = pattern.matches_in(
+        target,
+        MatchContext {
+            params: &params,
+            target_params: Some(&target_params),
+            loose_paths,
+        },
+    )?;
+    // The arguments are in terms of the target, so they are not inserted one by one, which would
+    // substitute the pattern's bindings into them
+    let pattern_bindings = renamed
+        .iter()
+        .filter_map(|(fresh, original)| {
+            let arg = unifier.get(fresh)?;
+            Some((clean_param(original.clone()), arg.clone()))
+        })
+        .collect();
+    let target_bindings = unifier
+        .0
+        .into_iter()
+        .filter(|(param, arg)| target_params.contains(param) && !is_param_itself(param, arg))
+        .collect();
+    Ok((Substitute(pattern_bindings), Substitute(target_bindings)))
+}
+
+/// The binding of a param to a term containing that same param, which made `pattern` fail to
+/// match `target`, e.g. `T -> Vec<T>` for `Wrap<T>` against `Wrap<Vec<T>>`
+pub fn cyclic_binding<T: Matching + Clone>(
+    pattern: &T,
+    target: &T,
+    params: &HashSet<GenericParam>,
+    loose_paths: bool,
+) -> Option<(GenericParam, GenericArgument)> {
+    let (renaming, renamed) = rename_apart(params);
+    let mut pattern = pattern.clone();
+    pattern.replace(&renaming);
+    let renamed_params = renamed.keys().cloned().collect();
+    let substitute = pattern
+        .matches_in(
+            target,
+            MatchContext {
+                loose_paths,
+                ..MatchContext::from(&renamed_params)
+            },
+        )
+        .ok()?;
+    substitute.0.into_iter().find_map(|(param, arg)| {
+        let original = renamed.get(&param)?;
+        occurs_in(original, &arg).then(|| (original.clone(), arg))
+    })
+}
+
+/// The params a match may bind
+///
+/// A plain match only binds the params of its left-hand side, so that a param of the right-hand
+/// side which happens to share a name stays the term it is. [`unify`] binds the params of either
+/// side, after renaming the pattern's apart.
+#[derive(Clone, Copy, Debug)]
+pub struct MatchContext<'a> {
+    /// The params bound to the terms they are matched against
+    pub params: &'a HashSet<GenericParam>,
+    /// The params of the right-hand side bound to the terms they face, when unifying
+    pub target_params: Option<&'a HashSet<GenericParam>>,
+    /// Whether a single-segment path matches any path ending in the same segment, so that
+    /// `Vec<T>` matches `std::vec::Vec<u8>`
+    pub loose_paths: bool,
+}
+
+impl<'a> From<&'a HashSet<GenericParam>> for MatchContext<'a> {
+    fn from(params: &'a HashSet<GenericParam>) -> Self {
+        MatchContext {
+            params,
+            target_params: None,
+            loose_paths: false,
+        }
+    }
+}
+
+impl<'a> MatchContext<'a> {
+    /// The params left bindable under a `for<..>` binder of `bound`, to be passed to
+    /// [`MatchContext::with_params`]
+    fn shadowed(
+        &self,
+        bound: &[GenericParam],
+    ) -> (HashSet<GenericParam>, Option<HashSet<GenericParam>>) {
+        (
+            without_params(self.params, bound),
+            self.target_params
+                .map(|params| without_params(params, bound)),
+        )
+    }
+
+    fn with_params<'b>(
+        &self,
+        params: &'b HashSet<GenericParam>,
+        target_params: Option<&'b HashSet<GenericParam>>,
+    ) -> MatchContext<'b> {
+        MatchContext {
+            params,
+            target_params,
+            loose_paths: self.loose_paths,
+        }
+    }
+
+    /// The param of the right-hand side which `ty` consists of, if it may be bound
+    fn target_type_param(&self, ty: &Type) -> Option<GenericParam> {
+        type_param(ty, self.target_params?)
+    }
+
+    /// The const param of the right-hand side which `expr` consists of, if it may be bound
+    fn target_const_param(&self, expr: &Expr) -> Option<GenericParam> {
+        const_param_of_expr(expr, self.target_params?)
+    }
+}
+
+/// Trait for matching AST elements and performing generic parameter substitution
+#[allow(unused)]
+pub trait Matching {
+    /// Check if this element matches another, returning substitutions if successful
+    fn matches(&self, other: &Self, params: &HashSet<GenericParam>) -> MatchResult {
+        self.matches_in(other, params.into())
+    }
+
+    /// Check if this element matches another, binding the params `cx` allows
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult;
+
+    /// Replace generic parameters in this element using the provided substitutions
+    fn replace(&mut self, dict: &Substitute);
+}
+
+impl Matching for Lifetime {
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult {
+        if self == other {
+            // Concrete lifetimes must match exactly
+            Ok(Substitute::new())
+        } else {
+            // Check if this lifetime is a generic parameter
+            let predicate = GenericParam::Lifetime(LifetimeParam {
+                attrs: vec![],
+                lifetime: self.clone(),
+                colon_token: None,
+                bounds: Default::default(),
+            });
+
+            let other_predicate = GenericParam::Lifetime(LifetimeParam::new(other.clone()));
+            if cx.params.contains(&predicate) {
+                // This is a generic lifetime parameter, create substitution
+                Substitute::from_param_arg(predicate, GenericArgument::Lifetime(other.clone()))
+            } else if cx
+                .target_params
+                .is_some_and(|params| params.contains(&other_predicate))
+            {
+                Substitute::from_param_arg(other_predicate, GenericArgument::Lifetime(self.clone()))
+            } else {
+                Err(MatchError::new(self, other, "different lifetimes"))
+            }
+        }
+    }
+
+    fn replace(&mut self, dict: &Substitute) {
+        let predicate = GenericParam::Lifetime(LifetimeParam {
+            attrs: vec![],
+            lifetime: self.clone(),
+            colon_token: None,
+            bounds: Default::default(),
+        });
+
+        if let Some(GenericArgument::Lifetime(new_lifetime)) = dict.get(&predicate) {
+            *self = new_lifetime.clone();
+        }
+    }
+}
+
+/// Reasons why [`eval_const`] could not produce a value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ConstEvalError {
+    /// The expression is not built only from integer literals and arithmetic
+    NotConst,
+    /// The arithmetic overflows the (suffix-declared) integer type
+    Overflow,
+}
+
+/// A value of any of the integer types, as a sign and a magnitude, so that both the `u128` and
+/// the `i128` values fit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ConstInt {
+    negative: bool,
+    magnitude: u128,
+}
+
+impl ConstInt {
+    fn new(negative: bool, magnitude: u128) -> Self {
+        // Zero has a single representation, so that `-0 == 0`
+        Self {
+            negative: negative && magnitude != 0,
+            magnitude,
+        }
+    }
+
+    fn neg(self) -> Self {
+        Self::new(!self.negative, self.magnitude)
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(if self.negative == other.negative {
+            Self::new(self.negative, self.magnitude.checked_add(other.magnitude)?)
+        } else if self.magnitude >= other.magnitude {
+            Self::new(self.negative, self.magnitude - other.magnitude)
+        } else {
+            Self::new(other.negative, other.magnitude - self.magnitude)
+        })
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        self.checked_add(other.neg())
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        let magnitude = self.magnitude.checked_mul(other.magnitude)?;
+        Some(Self::new(self.negative != other.negative, magnitude))
+    }
+
+    /// Division truncates toward zero, as it does in Rust
+    fn checked_div(self, other: Self) -> Option<Self> {
+        let magnitude = self.magnitude.checked_div(other.magnitude)?;
+        Some(Self::new(self.negative != other.negative, magnitude))
+    }
+
+    /// The remainder takes the sign of the dividend, as it does in Rust
+    fn checked_rem(self, other: Self) -> Option<Self> {
+        Some(Self::new(
+            self.negative,
+            self.magnitude.checked_rem(other.magnitude)?,
+        ))
+    }
+}
+
+/// The largest magnitudes of the negative and the positive values of the integer type named by a
+/// literal suffix, assuming a 64-bit target; an unsuffixed value may be of any integer type
+fn int_range(suffix: Option<&str>) -> Option<(u128, u128)> {
+    Some(match suffix {
+        Some("u8") => (0, u8::MAX.into()),
+        Some("u16") => (0, u16::MAX.into()),
+        Some("u32") => (0, u32::MAX.into()),
+        Some("u64" | "usize") => (0, u64::MAX.into()),
+        Some("u128") => (0, u128::MAX),
+        Some("i8") => (i8::MIN.unsigned_abs().into(), i8::MAX.unsigned_abs().into()),
+        Some("i16") => (
+            i16::MIN.unsigned_abs().into(),
+            i16::MAX.unsigned_abs().into(),
+        ),
+        Some("i32") => (
+            i32::MIN.unsigned_abs().into(),
+            i32::MAX.unsigned_abs().into(),
+        ),
+        Some("i64" | "isize") => (
+            i64::MIN.unsigned_abs().into(),
+            i64::MAX.unsigned_abs().into(),
+        ),
+        Some("i128") => (i128::MIN.unsigned_abs(), i128::MAX.unsigned_abs()),
+        None => (i128::MIN.unsigned_abs(), u128::MAX),
+        Some(_) => return None,
+    })
+}
+
+/// The value of an integer literal with its suffix, if any, before its range is checked
+fn int_literal(lit: &LitInt) -> core::result::Result<(ConstInt, Option<String>), ConstEvalError> {
+    let suffix = (!lit.suffix().is_empty()).then(|| lit.suffix().to_string());
+    let magnitude = lit
+        .base10_parse::<u128>()
+        .map_err(|_| ConstEvalError::Overflow)?;
+    Ok((ConstInt::new(false, magnitude), suffix))
+}
+
+/// Conservatively evaluate an integer const expression made of literals and arithmetic.
+/// Uses checked arithmetic, so overflow is reported instead of wrapping or panicking.
+/// Returns the value with the integer type suffix, if any literal declares one.
+fn eval_const(expr: &Expr) -> core::result::Result<(ConstInt, Option<String>), ConstEvalError> {
+    use ConstEvalError::*;
+    let (value, suffix) = match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => int_literal(lit)?,
+        // `b'A'` is the `u8` 65
+        Expr::Lit(ExprLit {
+            lit: Lit::Byte(lit),
+            ..
+        }) => (
+            ConstInt::new(false, lit.value().into()),
+            Some("u8".to_string()),
+        ),
+        Expr::Paren(ExprParen { expr, .. }) | Expr::Group(ExprGroup { expr, .. }) => {
+            eval_const(expr)?
+        }
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => {
+            // `-128i8` is in range although `128i8` alone is not
+            let (value, suffix) = match expr.as_ref() {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(lit), ..
+                }) => int_literal(lit)?,
+                expr => eval_const(expr)?,
+            };
+            (value.neg(), suffix)
+        }
+        Expr::Binary(ExprBinary {
+            left, op, right, ..
+        }) => {
+            let (l, l_suffix) = eval_const(left)?;
+            let (r, r_suffix) = eval_const(right)?;
+            let suffix = match (l_suffix, r_suffix) {
+                (Some(l), Some(r)) if l != r => return Err(NotConst),
+                (l, r) => l.or(r),
+            };
+            let value = match op {
+                BinOp::Add(_) => l.checked_add(r),
+                BinOp::Sub(_) => l.checked_sub(r),
+                BinOp::Mul(_) => l.checked_mul(r),
+                BinOp::Div(_) => l.checked_div(r),
+                BinOp::Rem(_) => l.checked_rem(r),
+                _ => return Err(NotConst),
+            };
+            (value.ok_or(Overflow)?, suffix)
+        }
+        _ => return Err(NotConst),
+    };
+    if let Some((negative_max, positive_max)) = int_range(suffix.as_deref()) {
+        let max = if value.negative {
+            negative_max
+        } else {
+            positive_max
+        };
+        if value.magnitude > max {
+            return Err(Overflow);
+        }
+    }
+    Ok((value, suffix))
+}
+
+impl Matching for Expr {
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult {
+        match (eval_const(self), eval_const(other)) {
+            (Ok((l, l_suffix)), Ok((r, r_suffix))) => {
+                let compatible = l_suffix.is_none() || r_suffix.is_none() || l_suffix == r_suffix;
+                ensure(compatible, self, other, "different integer types")?;
+                ensure(l == r, self, other, "different values")?;
+                return Ok(Substitute::new());
+            }
+            // An overflowing const expression would not compile, so never match it
+            (Err(ConstEvalError::Overflow), _) | (_, Err(ConstEvalError::Overflow)) => {
+                return Err(MatchError::new(
+                    self,
+                    other,
+                    "the const expression overflows",
+                ))
+            }
+            _ => (),
+        }
+        match (self, other) {
+            (Expr::Path(l_path), other_expr) => {
+                if let Some(param) = l_path
+                    .path
+                    .get_ident()
+                    .and_then(|ident| find_const_param(cx.params, ident))
+                {
+                    return Substitute::from_param_arg(
+                        param,
+                        GenericArgument::Const(other_expr.clone()),
+                    );
+                }
+
+                if let Some(param) = cx.target_const_param(other_expr) {
+                    return Substitute::from_param_arg(param, GenericArgument::Const(self.clone()));
+                }
+
+                // If not a generic parameter, check if both are paths
+                if let Expr::Path(r_path) = other_expr {
+                    l_path.path.matches_in(&r_path.path, cx)
+                } else if let Some(inner) = const_block_expr(other_expr) {
+                    self.matches_in(inner, cx)
+                } else {
+                    Err(MatchError::new(self, other, "different const expressions"))
+                }
+            }
+            // `{ M }` binds `M` to the whole argument, braces included, as a value such as
+            // `Mode::Fast` is only read as a const argument when braced
+            (Expr::Block(_), _) if const_block_expr(self).is_some() => {
+                let inner = const_block_expr(self).unwrap();
+                match inner {
+                    Expr::Path(ExprPath { path, .. })
+                        if path
+                            .get_ident()
+                            .is_some_and(|ident| find_const_param(cx.params, ident).is_some()) =>
+                    {
+                        inner.matches_in(other, cx)
+                    }
+                    _ => inner.matches_in(const_block_expr(other).unwrap_or(other), cx),
+                }
+            }
+            (_, Expr::Path(_)) if cx.target_const_param(other).is_some() => {
+                Substitute::from_param_arg(
+                    cx.target_const_param(other).unwrap(),
+                    GenericArgument::Const(self.clone()),
+                )
+            }
+            (_, Expr::Block(_)) if const_block_expr(other).is_some() => {
+                self.matches_in(const_block_expr(other).unwrap(), cx)
+            }
+            // Integers were compared by value above, and the other literals are as well, so that
+            // escapes such as `'\x41'` equal the characters they denote
+            (Expr::Lit(l_lit), Expr::Lit(r_lit)) => {
+                let equal = match (&l_lit.lit, &r_lit.lit) {
+                    (Lit::Char(l), Lit::Char(r)) => l.value() == r.value(),
+                    (Lit::Str(l), Lit::Str(r)) => l.value() == r.value(),
+                    (Lit::ByteStr(l), Lit::ByteStr(r)) => l.value() == r.value(),
+                    (l, r) => l == r,
+                };
+                ensure(equal, self, other, "different literals")?;
+                Ok(Substitute::new())
+            }
+            (Expr::Paren(ExprParen { expr, .. }) | Expr::Group(ExprGroup { expr, .. }), _) => {
+                expr.matches_in(other, cx)
+            }
+            (_, Expr::Paren(ExprParen { expr, .. }) | Expr::Group(ExprGroup { expr, .. })) => {
+                self.matches_in(expr, cx)
+            }
+            // Operands which could not be evaluated above are matched one by one, so that
+            // `{ N + 1 }` binds `N` against `{ 4 + 1 }`
+            (Expr::Binary(l_binary), Expr::Binary(r_binary)) if l_binary.op == r_binary.op => {
+                l_binary
+                    .left
+                    .matches_in(&r_binary.left, cx)?
+                    .combine(l_binary.right.matches_in(&r_binary.right, cx)?, cx)
+            }
+            (Expr::Unary(l_unary), Expr::Unary(r_unary)) if l_unary.op == r_unary.op => {
+                l_unary.expr.matches_in(&r_unary.expr, cx)
+            }
+            // Other expressions cannot be compared without evaluating them, so they never match
+            _ => Err(MatchError::new(self, other, "different const expressions")),
+        }
+    }
+
+    fn replace(&mut self, dict: &Substitute) {
+        /// Params may appear anywhere in a const expression, e.g. `{ N + 1 }`
+        struct ConstReplacer<'a>(&'a Substitute);
+
+        impl visit_mut::VisitMut for ConstReplacer<'_> {
+            fn visit_expr_mut(&mut self, expr: &mut Expr) {
+                // A braced binding fills a braced hole `{ M }` as it is, without another brace
+                let braced = match const_block_expr(expr) {
+                    Some(Expr::Path(inner)) => inner
+                        .path
+                        .get_ident()
+                        .and_then(|ident| self.0.get_const(ident))
+                        .filter(|new_expr| const_block_expr(new_expr).is_some()),
+                    _ => None,
+                };
+                if let Some(new_expr) = braced {
+                    *expr = new_expr.clone();
+                    return;
+                }
+                let Expr::Path(expr_path) = expr else {
+                    return visit_mut::visit_expr_mut(self, expr);
+                };
+                if let Some(new_expr) = expr_path
+                    .path
+                    .get_ident()
+                    .and_then(|ident| self.0.get_const(ident))
+                {
+                    *expr = new_expr.clone();
+                    return;
+                }
+
+                expr_path.path.replace(self.0);
+            }
+
+            // Types within the expression, such as the target of a cast, mention params as well
+            fn visit_type_mut(&mut self, ty: &mut Type) {
+                ty.replace(self.0);
+            }
+        }
+
+        visit_mut::VisitMut::visit_expr_mut(&mut ConstReplacer(dict), self);
+    }
+}
+
+/// The expression of a braced const argument `{ expr }`
+fn const_block_expr(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::Block(ExprBlock {
+            attrs,
+            label: None,
+            block,
+        }) if attrs.is_empty() && block.stmts.len() == 1 => match &block.stmts[0] {
+            Stmt::Expr(inner, None) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Match two bound lists regardless of order, as multisets. Each bound on the left is paired
+/// with an unused bound on the right, backtracking when a pairing leads to conflicting bindings.
+/// The right bounds are tried in the order of their tokens, so the result is deterministic.
+fn match_bounds_unordered<P>(
+    lhs: &Punctuated<TypeParamBound, P>,
+    rhs: &Punctuated<TypeParamBound, P>,
+    cx: MatchContext,
+) -> MatchResult
+where
+    Punctuated<TypeParamBound, P>: Spanned,
+{
+    ensure(
+        lhs.len() == rhs.len(),
+        lhs,
+        rhs,
+        "different numbers of bounds",
+    )?;
+    let lhs = lhs.iter().collect::<Vec<_>>();
+    let mut candidates = rhs.iter().collect::<Vec<_>>();
+    candidates.sort_by_cached_key(|bound| template_quote::quote!(#bound).to_string());
+    let mut used = vec![false; candidates.len()];
+    assign_bounds(
+        &lhs,
+        &candidates,
+        &mut used,
+        Substitute::new(),
+        rhs.span(),
+        cx,
+    )
+}
+
+/// Pairs each of `lhs` with an unused one of `rhs`, extending `result` consistently
+fn assign_bounds(
+    lhs: &[&TypeParamBound],
+    rhs: &[&TypeParamBound],
+    used: &mut [bool],
+    result: Substitute,
+    rhs_span: Span,
+    cx: MatchContext,
+) -> MatchResult {
+    let Some((l, rest)) = lhs.split_first() else {
+        return Ok(result);
+    };
+    let mut deeper = None;
+    for (index, r) in rhs.iter().enumerate() {
+        if used[index] {
+            continue;
+        }
+        let Ok(combined) = l
+            .matches_in(r, cx)
+            .and_then(|substitute| result.clone().combine(substitute, cx))
+        else {
+            continue;
+        };
+        used[index] = true;
+        let assigned = assign_bounds(rest, rhs, used, combined, rhs_span, cx);
+        used[index] = false;
+        match assigned {
+            Ok(assigned) => return Ok(assigned),
+            Err(error) => deeper = deeper.or(Some(error)),
+        }
+    }
+    Err(deeper.unwrap_or_else(|| MatchError {
+        lhs: l.span(),
+        rhs: rhs_span,
+        reason: "no bound matches this one",
+        conflict: None,
+    }))
+}
+
+/// The ABI string of a function pointer, with a bare `extern` read as `extern "C"`.
+fn abi_name(abi: &Option<Abi>) -> Option<String> {
+    abi.as_ref().map(|abi| {
+        abi.name
+            .as_ref()
+            .map_or_else(|| "C".to_string(), |name| name.value())
+    })
+}
+
+/// The type param among `params` which `ty` consists of, if any
+fn type_param(ty: &Type, params: &HashSet<GenericParam>) -> Option<GenericParam> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let predicate = GenericParam::Type(TypeParam::from(path.get_ident()?.clone()));
+    params.contains(&predicate).then_some(predicate)
+}
+
+impl Matching for Type {
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult {
+        // Check if this is a generic parameter that needs substitution
+        if let Some(param) = type_param(self, cx.params) {
+            return Substitute::from_param_arg(param, GenericArgument::Type(other.clone()));
+        }
+        match (self, other) {
+            // `_` in a pattern accepts any type without binding it
+            (Type::Infer(_), _) => Ok(Substitute::new()),
+            // When unifying, a param of the right-hand side is bound to the left
+            (_, Type::Path(_)) if cx.target_type_param(other).is_some() => {
+                Substitute::from_param_arg(
+                    cx.target_type_param(other).unwrap(),
+                    GenericArgument::Type(self.clone()),
+                )
+            }
+            // Invisible groups and parentheses do not change the type, on either side
+            (Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. }), _) => {
+                elem.matches_in(other, cx)
+            }
+            (_, Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. })) => {
+                self.matches_in(elem, cx)
+            }
+            (Type::Path(lhs_path), Type::Path(rhs_path)) => {
+                match (&lhs_path.qself, &rhs_path.qself) {
+                    (Some(lhs_qself), Some(rhs_qself)) => {
+                        lhs_qself.ty.matches_in(&rhs_qself.ty, cx)?.combine(
+                            match_qualified_path(
+                                &lhs_path.path,
+                                lhs_qself.position,
+                                &rhs_path.path,
+                                rhs_qself.position,
+                                cx,
+                            )?,
+                            cx,
+                        )
+                    }
+                    (None, None) => lhs_path.path.matches_in(&rhs_path.path, cx),
+                    _ => Err(MatchError::new(
+                        self,
+                        other,
+                        "only one side is a qualified path",
+                    )),
+                }
+            }
+            (Type::Reference(lhs_ref), Type::Reference(rhs_ref)) => {
+                ensure(
+                    lhs_ref.mutability == rhs_ref.mutability,
+                    self,
+                    other,
+                    "different mutability",
+                )?;
+
+                match (&lhs_ref.lifetime, &rhs_ref.lifetime) {
+                    (Some(lhs_lt), Some(rhs_lt)) => lhs_lt.matches_in(rhs_lt, cx)?,
+                    // An elided lifetime is a fresh anonymous one, which unifies with anything;
+                    // a param facing it is bound to `'_`, so the replaced reference stays elided
+                    (Some(lhs_lt), None) => lhs_lt
+                        .matches_in(&Lifetime::new("'_", lhs_lt.span()), cx)
+                        .unwrap_or_default(),
+                    _ => Substitute::new(),
+                }
+            }
+            .combine(lhs_ref.elem.matches_in(&rhs_ref.elem, cx)?, cx),
+            (Type::Tuple(lhs_tuple), Type::Tuple(rhs_tuple)) => {
+                ensure(
+                    lhs_tuple.elems.len() == rhs_tuple.elems.len(),
+                    self,
+                    other,
+                    "different numbers of elements",
+                )?;
+                lhs_tuple
+                    .elems
+                    .iter()
+                    .zip(&rhs_tuple.elems)
+                    .try_fold(Substitute::new(), |substitute, (l, r)| {
+                        substitute.combine(l.matches_in(r, cx)?, cx)
+                    })
+            }
+            // `[T; _]` accepts any length without binding it, while `[T; N]` with a const param
+            // `N` still binds `N` to the length
+            (Type::Array(lhs_array), Type::Array(rhs_array))
+                if matches!(lhs_array.len, Expr::Infer(_)) =>
+            {
+                lhs_array.elem.matches_in(&rhs_array.elem, cx)
+            }
+            (Type::Array(lhs_array), Type::Array(rhs_array)) => lhs_array
+                .elem
+                .matches_in(&rhs_array.elem, cx)?
+                .combine(lhs_array.len.matches_in(&rhs_array.len, cx)?, cx),
+            (Type::Slice(lhs_slice), Type::Slice(rhs_slice)) => {
+                lhs_slice.elem.matches_in(&rhs_slice.elem, cx)
+            }
+            (Type::Ptr(lhs_ptr), Type::Ptr(rhs_ptr)) => {
+                ensure(
+                    lhs_ptr.const_token == rhs_ptr.const_token
+                        && lhs_ptr.mutability == rhs_ptr.mutability,
+                    self,
+                    other,
+                    "different mutability",
+                )?;
+                lhs_ptr.elem.matches_in(&rhs_ptr.elem, cx)
+            }
+            (Type::TraitObject(lhs_obj), Type::TraitObject(rhs_obj)) => {
+                ensure(
+                    lhs_obj.dyn_token.is_some() == rhs_obj.dyn_token.is_some(),
+                    self,
+                    other,
+                    "only one side is written with `dyn`",
+                )?;
+                match_bounds_unordered(&lhs_obj.bounds, &rhs_obj.bounds, cx)
+            }
+            (Type::Never(_), Type::Never(_)) => Ok(Substitute::new()),
+            (Type::ImplTrait(lhs_impl), Type::ImplTrait(rhs_impl)) => {
+                match_bounds_unordered(&lhs_impl.bounds, &rhs_impl.bounds, cx)
+            }
+            (Type::BareFn(lhs_fn), Type::BareFn(rhs_fn)) => {
+                let l_bound = bound_lifetime_params(&lhs_fn.lifetimes);
+                ensure(
+                    l_bound == bound_lifetime_params(&rhs_fn.lifetimes),
+                    self,
+                    other,
+                    "different `for<..>` lifetimes",
+                )?;
+                let (params, target_params) = cx.shadowed(&l_bound);
+                let cx = cx.with_params(&params, target_params.as_ref());
+                ensure(
+                    lhs_fn.unsafety == rhs_fn.unsafety
+                        && abi_name(&lhs_fn.abi) == abi_name(&rhs_fn.abi),
+                    self,
+                    other,
+                    "different function qualifiers",
+                )?;
+                ensure(
+                    lhs_fn.variadic.is_some() == rhs_fn.variadic.is_some()
+                        && lhs_fn.inputs.len() == rhs_fn.inputs.len(),
+                    self,
+                    other,
+                    "different numbers of parameters",
+                )?;
+                let result = lhs_fn
+                    .inputs
+                    .iter()
+                    .zip(&rhs_fn.inputs)
+                    .try_fold(Substitute::new(), |result, (l, r)| {
+                        result.combine(l.ty.matches_in(&r.ty, cx)?, cx)
+                    })?;
+                result.combine(lhs_fn.output.matches_in(&rhs_fn.output, cx)?, cx)
+            }
+            _ => Err(MatchError::new(self, other, "different kinds of types")),
+        }
+    }
+
+    fn replace(&mut self, dict: &Substitute) {
+        match self {
+            Type::Path(type_path) => {
+                if let (None, Some(ident)) = (&type_path.qself, type_path.path.get_ident()) {
+                    let predicate = GenericParam::Type(TypeParam {
+                        attrs: vec![],
+                        ident: ident.clone(),
+                        colon_token: None,
+                        bounds: Default::default(),
+                        eq_token: None,
+                        default: None,
+                    });
+                    if let Some(GenericArgument::Type(new_ty)) = dict.get(&predicate) {
+                        *self = new_ty.clone();
+                        return;
+                    }
+                }
+                if let Some(qself) = &mut type_path.qself {
+                    qself.ty.replace(dict);
+                }
+                type_path.path.replace(dict);
+            }
+            Type::Reference(type_ref) => {
+                if let Some(lt) = &mut type_ref.lifetime {
+                    lt.replace(dict);
+                    if is_anonymous(lt) {
+                        type_ref.lifetime = None;
+                    }
+                }
+                type_ref.elem.replace(dict);
+            }
+            Type::Tuple(type_tuple) => {
+                for elem in &mut type_tuple.elems {
+                    elem.replace(dict);
+                }
+            }
+            Type::Array(type_array) => {
+                type_array.elem.replace(dict);
+                type_array.len.replace(dict);
+            }
+            Type::Slice(TypeSlice { elem, .. })
+            | Type::Ptr(TypePtr { elem, .. })
+            | Type::Group(TypeGroup { elem, .. })
+            | Type::Paren(TypeParen { elem, .. }) => {
+                elem.replace(dict);
+            }
+            Type::TraitObject(TypeTraitObject { bounds, .. })
+            | Type::ImplTrait(TypeImplTrait { bounds, .. }) => {
+                for bound in bounds {
+                    bound.replace(dict);
+                }
+            }
+            Type::BareFn(type_fn) => {
+                let dict = &dict.without(&bound_lifetime_params(&type_fn.lifetimes));
+                for input in &mut type_fn.inputs {
+                    input.ty.replace(dict);
+                }
+                type_fn.output.replace(dict);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The written return type, unless it is the unit type, which is the same as not writing one
+fn returned_type(output: &ReturnType) -> Option<&Type> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let mut unwrapped = ty.as_ref();
+    while let Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. }) = unwrapped
+    {
+        unwrapped = elem;
+    }
+    match unwrapped {
+        Type::Tuple(tuple) if tuple.elems.is_empty() => None,
+        _ => Some(ty),
+    }
+}
+
+impl Matching for ReturnType {
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult {
+        // `Fn(T)` is `Fn(T) -> ()`, so an explicit unit matches a return type left out
+        match (returned_type(self), returned_type(other)) {
+            (None, None) => Ok(Substitute::new()),
+            (Some(l_ty), Some(r_ty)) => l_ty.matches_in(r_ty, cx),
+            _ => Err(MatchError::new(
+                self,
+                other,
+                "only one side returns a value",
+            )),
+        }
+    }
+
+    fn replace(&mut self, dict: &Substitute) {
+        if let ReturnType::Type(_, ty) = self {
+            ty.replace(dict);
+        }
+    }
+}
+
+/// The segments of `lhs` and `rhs` to be matched pairwise: all of them, or with loose paths only
+/// the last ones when one of the paths is a single segment
+fn paired_segments<'a>(
+    lhs: &'a Path,
+    rhs: &'a Path,
+    loose_paths: bool,
+) -> Option<Vec<(&'a PathSegment, &'a PathSegment)>> {
+    if lhs.segments.len() == rhs.segments.len() {
+        Some(lhs.segments.iter().zip(&rhs.segments).collect())
+    } else if loose_paths && (lhs.segments.len() == 1 || rhs.segments.len() == 1) {
+        Some(vec![(lhs.segments.last()?, rhs.segments.last()?)])
+    } else {
+        None
+    }
+}
+
+impl Matching for Path {
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult {
+        let Some(segments) = paired_segments(self, other, cx.loose_paths) else {
+            return Err(MatchError::new(
+                self,
+                other,
+                "different numbers of path segments",
+            ));
+        };
+
+        segments
+            .into_iter()
+            .try_fold(Substitute::new(), |result, (l_seg, r_seg)| {
+                ensure(
+                    l_seg.ident == r_seg.ident,
+                    l_seg,
+                    r_seg,
+                    "different path segments",
+                )?;
+                let new_subs = l_seg.arguments.matches_in(&r_seg.arguments, cx)?;
+                result.combine(new_subs, cx)
+            })
+    }
+
+    fn replace(&mut self, dict: &Substitute) {
+        // Check if this is a single identifier that matches a generic parameter
+        if let Some(ident) = self.get_ident() {
+            let predicate = GenericParam::Type(TypeParam {
+                attrs: vec![],
+                ident: ident.clone(),
+                colon_token: None,
+                bounds: Default::default(),
+                eq_token: None,
+                default: None,
+            });
+
+            if let Some(GenericArgument::Type(Type::Path(new_path))) = dict.get(&predicate) {
+                *self = new_path.path.clone();
+                return;
+            }
+        }
+
+        // Replace in path segments arguments
+        for segment in &mut self.segments {
+            segment.arguments.replace(dict);
+        }
+    }
+}
+
+impl Matching for AngleBracketedGenericArguments {
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult {
+        ensure(
+            self.args.len() == other.args.len(),
+            self,
+            other,
+            "different numbers of generic arguments",
+        )?;
+        // `Vec::<T>` is the same type as `Vec<T>`, so the turbofish is not compared
+        self.args
+            .iter()
+            .zip(&other.args)
+            .try_fold(Substitute::new(), |result, (l, r)| {
+                let new_subs = l.matches_in(r, cx)?;
+                result.combine(new_subs, cx)
+            })
+    }
+
+    fn replace(&mut self, dict: &Substitute) {
+        for arg in &mut self.args {
+            arg.replace(dict);
+        }
+    }
+}
+
+impl Matching for PathArguments {
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult {
+        match (self, other) {
+            (PathArguments::None, PathArguments::None) => Ok(Substitute::new()),
+            // `Foo<>` is the same type as `Foo`
+            (PathArguments::None, PathArguments::AngleBracketed(args))
+            | (PathArguments::AngleBracketed(args), PathArguments::None)
+                if args.args.is_empty() =>
+            {
+                Ok(Substitute::new())
+            }
+            (PathArguments::AngleBracketed(lhs_args), PathArguments::AngleBracketed(rhs_args)) => {
+                lhs_args.matches_in(rhs_args, cx)
+            }
+            (PathArguments::Parenthesized(lhs_args), PathArguments::Parenthesized(rhs_args)) => {
+                ensure(
+                    lhs_args.inputs.len() == rhs_args.inputs.len(),
+                    self,
+                    other,
+                    "different numbers of parameters",
+                )?;
+                let result = lhs_args
+                    .inputs
+                    .iter()
+                    .zip(&rhs_args.inputs)
+                    .try_fold(Substitute::new(), |result, (l, r)| {
+                        result.combine(l.matches_in(r, cx)?, cx)
+                    })?;
+                result.combine(lhs_args.output.matches_in(&rhs_args.output, cx)?, cx)
+            }
+            _ => Err(MatchError::new(
+                self,
+                other,
+                "different kinds of path arguments",
+            )),
+        }
+    }
+
+    fn replace(&mut self, dict: &Substitute) {
+        match self {
+            PathArguments::AngleBracketed(angle_args) => {
+                angle_args.replace(dict);
+            }
+            PathArguments::Parenthesized(paren_args) => {
+                for input in &mut paren_args.inputs {
+                    input.replace(dict);
+                }
+                paren_args.output.replace(dict);
+            }
+            PathArguments::None => {}
+        }
+    }
+}
+
+/// The const param among `params` which `expr` consists of, if any
+fn const_param_of_expr(expr: &Expr, params: &HashSet<GenericParam>) -> Option<GenericParam> {
+    match expr {
+        Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) => find_const_param(params, path.get_ident()?),
+        _ => None,
+    }
+}
+
+/// Finds the const parameter named `ident` in `params`, regardless of its declared type
+fn find_const_param(params: &HashSet<GenericParam>, ident: &Ident) -> Option<GenericParam> {
+    params
+        .iter()
+        .find(|param| matches!(param, GenericParam::Const(c) if &c.ident == ident))
+        .cloned()
+}
+
+/// Const arguments written as bare identifiers (`Pair<N>`) are parsed as types.
+/// Returns the identifier such an argument would refer to.
+fn const_ident_of_type(ty: &Type) -> Option<&Ident> {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => path.get_ident(),
+        _ => None,
+    }
+}
+
+/// View a generic argument as a const expression, if it can be one
+fn as_const_expr(arg: &GenericArgument) -> Option<Expr> {
+    match arg {
+        GenericArgument::Const(expr) => Some(expr.clone()),
+        GenericArgument::Type(Type::Path(TypePath { qself: None, path })) => {
+            Some(Expr::Path(ExprPath {
+                attrs: vec![],
+                qself: None,
+                path: path.clone(),
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// View `Name = value` as the name, its generics and the value as an argument; `LEN = N` is
+/// parsed as an associated type even when `N` is a const param
+fn assoc_binding(
+    arg: &GenericArgument,
+) -> Option<(
+    &Ident,
+    Option<&AngleBracketedGenericArguments>,
+    GenericArgument,
+)> {
+    match arg {
+        GenericArgument::AssocType(assoc) => Some((
+            &assoc.ident,
+            assoc.generics.as_ref(),
+            GenericArgument::Type(assoc.ty.clone()),
+        )),
+        GenericArgument::AssocConst(assoc) => Some((
+            &assoc.ident,
+            assoc.generics.as_ref(),
+            GenericArgument::Const(assoc.value.clone()),
+        )),
+        _ => None,
+    }
+}
+
+impl Matching for GenericArgument {
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult {
+        if let GenericArgument::Type(l_ty) = self {
+            if let Some(param) =
+                const_ident_of_type(l_ty).and_then(|ident| find_const_param(cx.params, ident))
+            {
+                let expr = as_const_expr(other)
+                    .ok_or_else(|| MatchError::new(self, other, "not a const argument"))?;
+                return Substitute::from_param_arg(param, GenericArgument::Const(expr));
+            }
+        }
+        if let GenericArgument::Type(r_ty) = other {
+            if let Some(param) = const_ident_of_type(r_ty)
+                .and_then(|ident| find_const_param(cx.target_params?, ident))
+            {
+                let expr = as_const_expr(self)
+                    .ok_or_else(|| MatchError::new(self, other, "not a const argument"))?;
+                return Substitute::from_param_arg(param, GenericArgument::Const(expr));
+            }
+        }
+        match (self, other) {
+            (GenericArgument::Type(l_ty), GenericArgument::Type(r_ty)) => l_ty.matches_in(r_ty, cx),
+            (GenericArgument::Lifetime(l_lt), GenericArgument::Lifetime(r_lt)) => {
+                l_lt.matches_in(r_lt, cx)
+            }
+            (GenericArgument::Const(l_const), GenericArgument::Const(r_const)) => {
+                l_const.matches_in(r_const, cx)
+            }
+            (
+                GenericArgument::AssocType(_) | GenericArgument::AssocConst(_),
+                GenericArgument::AssocType(_) | GenericArgument::AssocConst(_),
+            ) => {
+                let (l_ident, l_generics, l_value) = assoc_binding(self).unwrap();
+                let (r_ident, r_generics, r_value) = assoc_binding(other).unwrap();
+                ensure(
+                    l_ident == r_ident,
+                    l_ident,
+                    r_ident,
+                    "different associated items",
+                )?;
+                let result = match (l_generics, r_generics) {
+                    (Some(l_g), Some(r_g)) => l_g.matches_in(r_g, cx)?,
+                    (None, None) => Substitute::new(),
+                    _ => return Err(MatchError::new(self, other, "only one side has generics")),
+                };
+                result.combine(l_value.matches_in(&r_value, cx)?, cx)
+            }
+            (
+                GenericArgument::Constraint(l_constraint),
+                GenericArgument::Constraint(r_constraint),
+            ) => {
+                ensure(
+                    l_constraint.ident == r_constraint.ident,
+                    &l_constraint.ident,
+                    &r_constraint.ident,
+                    "different associated items",
+                )?;
+                let result = match (&l_constraint.generics, &r_constraint.generics) {
+                    (Some(l_g), Some(r_g)) => l_g.matches_in(r_g, cx)?,
+                    (None, None) => Substitute::new(),
+                    _ => return Err(MatchError::new(self, other, "only one side has generics")),
+                };
+                // `Item: Read + Write` is the same constraint as `Item: Write + Read`
+                result.combine(
+                    match_bounds_unordered(&l_constraint.bounds, &r_constraint.bounds, cx)?,
+                    cx,
+                )
+            }
+            _ => Err(MatchError::new(
+                self,
+                other,
+                "different kinds of generic arguments",
+            )),
+        }
+    }
+
+    fn replace(&mut self, dict: &Substitute) {
+        match self {
+            GenericArgument::Type(ty) => {
+                let const_arg = const_ident_of_type(ty)
+                    .and_then(|ident| dict.get_const(ident))
+                    .cloned();
+                match const_arg {
+                    // Keep bare paths in the form the parser would produce
+                    Some(Expr::Path(ExprPath {
+                        qself: None, path, ..
+                    })) => *ty = Type::Path(TypePath { qself: None, path }),
+                    Some(expr) => *self = GenericArgument::Const(expr),
+                    None => ty.replace(dict),
+                }
+            }
+            GenericArgument::Lifetime(lifetime) => {
+                lifetime.replace(dict);
+            }
+            GenericArgument::Const(expr) => {
+                expr.replace(dict);
+            }
+            GenericArgument::AssocType(assoc_type) => {
+                if let Some(generics) = &mut assoc_type.generics {
+                    generics.replace(dict);
+                }
+                // `LEN = N` becomes `LEN = 8` once the const param `N` is bound
+                let mut value = GenericArgument::Type(assoc_type.ty.clone());
+                value.replace(dict);
+                match value {
+                    GenericArgument::Const(expr) => {
+                        *self = GenericArgument::AssocConst(AssocConst {
+                            ident: assoc_type.ident.clone(),
+                            generics: assoc_type.generics.clone(),
+                            eq_token: assoc_type.eq_token,
+                            value: expr,
+                        })
+                    }
+                    GenericArgument::Type(ty) => assoc_type.ty = ty,
+                    _ => unreachable!("a type argument is replaced by a type or a const"),
+                }
+            }
+            GenericArgument::AssocConst(assoc_const) => {
+                if let Some(generics) = &mut assoc_const.generics {
+                    generics.replace(dict);
+                }
+                assoc_const.value.replace(dict);
+            }
+            GenericArgument::Constraint(constraint) => {
+                if let Some(generics) = &mut constraint.generics {
+                    generics.replace(dict);
+                }
+                for bound in &mut constraint.bounds {
+                    bound.replace(dict);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Matches the trait and associated item segments of `<X as Trait>::Assoc`. The trait may be
+/// named through different paths, e.g. `Trait` and `crate::Trait`, so a trait part of differing
+/// length is compared by its last segment only.
+fn match_qualified_path(
+    lhs: &Path,
+    lhs_position: usize,
+    rhs: &Path,
+    rhs_position: usize,
+    cx: MatchContext,
+) -> MatchResult {
+    let (lhs_trait, lhs_tail) = (
+        lhs.segments.iter().take(lhs_position).collect::<Vec<_>>(),
+        lhs.segments.iter().skip(lhs_position).collect::<Vec<_>>(),
+    );
+    let (rhs_trait, rhs_tail) = (
+        rhs.segments.iter().take(rhs_position).collect::<Vec<_>>(),
+        rhs.segments.iter().skip(rhs_position).collect::<Vec<_>>(),
+    );
+    ensure(
+        lhs_tail.len() == rhs_tail.len() && lhs_trait.is_empty() == rhs_trait.is_empty(),
+        lhs,
+        rhs,
+        "different qualified paths",
+    )?;
+    let trait_pairs = if lhs_trait.len() == rhs_trait.len() {
+        lhs_trait.into_iter().zip(rhs_trait).collect::<Vec<_>>()
+    } else {
+        lhs_trait
+            .last()
+            .copied()
+            .zip(rhs_trait.last().copied())
+            .into_iter()
+            .collect()
+    };
+    trait_pairs
+        .into_iter()
+        .chain(lhs_tail.into_iter().zip(rhs_tail))
+        .try_fold(Substitute::new(), |result, (l_seg, r_seg)| {
+            ensure(
+                l_seg.ident == r_seg.ident,
+                &l_seg.ident,
+                &r_seg.ident,
+                "different path segments",
+            )?;
+            result.combine(l_seg.arguments.matches_in(&r_seg.arguments, cx)?, cx)
+        })
+}
+
+/// `params` without the ones shadowed by a `for<..>` binder
+fn without_params(
+    params: &HashSet<GenericParam>,
+    shadowed: &[GenericParam],
+) -> HashSet<GenericParam> {
+    params
+        .iter()
+        .filter(|param| !shadowed.contains(param))
+        .cloned()
+        .collect()
+}
+
+/// The lifetimes introduced by a `for<..>` binder, in the form used as substitution keys
+fn bound_lifetime_params(lifetimes: &Option<BoundLifetimes>) -> Vec<GenericParam> {
+    lifetimes
+        .iter()
+        .flat_map(|bound| &bound.lifetimes)
+        .filter_map(|param| match param {
+            GenericParam::Lifetime(lifetime_param) => Some(GenericParam::Lifetime(
+                LifetimeParam::new(lifetime_param.lifetime.clone()),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+impl Matching for TypeParamBound {
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult {
+        match (self, other) {
+            (TypeParamBound::Trait(l_trait), TypeParamBound::Trait(r_trait)) => {
+                ensure(
+                    l_trait.paren_token == r_trait.paren_token
+                        && l_trait.modifier == r_trait.modifier,
+                    l_trait,
+                    r_trait,
+                    "different trait bound modifiers",
+                )?;
+                let l_bound = bound_lifetime_params(&l_trait.lifetimes);
+                ensure(
+                    l_bound == bound_lifetime_params(&r_trait.lifetimes),
+                    l_trait,
+                    r_trait,
+                    "different `for<..>` binders",
+                )?;
+                // Lifetimes bound by `for<..>` are local to the bound and never substituted
+                let (params, target_params) = cx.shadowed(&l_bound);
+                l_trait.path.matches_in(
+                    &r_trait.path,
+                    cx.with_params(&params, target_params.as_ref()),
+                )
+            }
+            (TypeParamBound::Lifetime(l_lifetime), TypeParamBound::Lifetime(r_lifetime)) => {
+                l_lifetime.matches_in(r_lifetime, cx)
+            }
+            (TypeParamBound::Verbatim(l_tokens), TypeParamBound::Verbatim(r_tokens)) => {
+                ensure(
+                    l_tokens.to_string() == r_tokens.to_string(),
+                    l_tokens,
+                    r_tokens,
+                    "different bounds",
+                )?;
+                Ok(Substitute::new())
+            }
+            _ => Err(MatchError::new(self, other, "different kinds of bounds")),
+        }
+    }
+
+    fn replace(&mut self, dict: &Substitute) {
+        match self {
+            TypeParamBound::Trait(trait_bound) => {
+                let bound = bound_lifetime_params(&trait_bound.lifetimes);
+                trait_bound.path.replace(&dict.without(&bound));
+            }
+            TypeParamBound::Lifetime(lifetime) => {
+                lifetime.replace(dict);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Matching for Constraint {
+    fn matches_in(&self, other: &Self, cx: MatchContext) -> MatchResult {
+        let bound = bound_lifetime_params(&self.lifetimes);
+        ensure(
+            bound == bound_lifetime_params(&other.lifetimes),
+            self,
+            other,
+            "different `for<..>` binders",
+        )?;
+        let (params, target_params) = cx.shadowed(&bound);
+        let cx = cx.with_params(&params, target_params.as_ref());
+        let trait_subs = self.trait_path.matches_in(&other.trait_path, cx)?;
+        let ty_subs = self.typ.matches_in(&other.typ, cx)?;
+        trait_subs.combine(ty_subs, cx)
+    }
+
+    fn replace(&mut self, dict: &Substitute) {
+        let dict = &dict.without(&bound_lifetime_params(&self.lifetimes));
+        self.typ.replace(dict);
+        self.trait_path.replace(dict);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_const_arithmetic_does_not_wrap() {
+        let lhs: Expr = parse_quote!(255u8 + 1);
+        let rhs: Expr = parse_quote!(0);
+        assert_eq!(eval_const(&lhs), Err(ConstEvalError::Overflow));
+        assert!(lhs.matches(&rhs, &HashSet::new()).is_err());
+        assert!(rhs.matches(&lhs, &HashSet::new()).is_err());
+        assert!(lhs.matches(&lhs, &HashSet::new()).is_err());
+    }
+
+    #[test]
+    fn test_const_arithmetic_overflow_without_suffix() {
+        let lhs: Expr = parse_quote!(340282366920938463463374607431768211455 * 2);
+        let rhs: Expr = parse_quote!(-2);
+        assert_eq!(eval_const(&lhs), Err(ConstEvalError::Overflow));
+        assert!(lhs.matches(&rhs, &HashSet::new()).is_err());
+    }
+
+    #[test]
+    fn test_const_arithmetic_matches_equal_values() {
+        let lhs: Expr = parse_quote!(254u8 + 1);
+        let rhs: Expr = parse_quote!(255);
+        assert_eq!(
+            eval_const(&lhs),
+            Ok((ConstInt::new(false, 255), Some("u8".to_string())))
+        );
+        assert_eq!(
+            lhs.matches(&rhs, &HashSet::new()).ok(),
+            Some(Substitute::new())
+        );
+        let divide_by_zero: Expr = parse_quote!(1 / 0);
+        assert!(divide_by_zero.matches(&rhs, &HashSet::new()).is_err());
+    }
+
+    #[test]
+    fn test_const_arithmetic_spans_u128_and_i128() {
+        let u128_max: Expr = parse_quote!(340282366920938463463374607431768211455u128);
+        let doubled: Expr = parse_quote!(170141183460469231731687303715884105727u128 * 2 + 1);
+        assert_eq!(
+            u128_max.matches(&doubled, &HashSet::new()).ok(),
+            Some(Substitute::new())
+        );
+        let i128_min: Expr = parse_quote!(-170141183460469231731687303715884105728i128);
+        let sum: Expr = parse_quote!(-170141183460469231731687303715884105727 - 1);
+        assert_eq!(
+            i128_min.matches(&sum, &HashSet::new()).ok(),
+            Some(Substitute::new())
+        );
+        let negative: Expr = parse_quote!(-1u128);
+        assert_eq!(eval_const(&negative), Err(ConstEvalError::Overflow));
+        let beyond: Expr = parse_quote!(170141183460469231731687303715884105728i128);
+        assert_eq!(eval_const(&beyond), Err(ConstEvalError::Overflow));
+        let remainder: Expr = parse_quote!(-7 % 2);
+        assert_eq!(eval_const(&remainder), Ok((ConstInt::new(true, 1), None)));
+    }
+
+    fn type_params(names: &[&str]) -> HashSet<GenericParam> {
+        names
+            .iter()
+            .map(|name| {
+                GenericParam::Type(TypeParam::from(Ident::new(
+                    name,
+                    proc_macro2::Span::call_site(),
+                )))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tuple_types() {
+        let params = type_params(&["T", "U"]);
+        let pattern: Type = parse_quote!((T, U));
+        let concrete: Type = parse_quote!((String, i32));
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert!(subs.verify_replacement(&pattern, &concrete));
+        assert!(pattern
+            .matches(&parse_quote!((String, i32, u8)), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_array_types() {
+        let params = type_params(&["T"]);
+        let pattern: Type = parse_quote!([T; 4]);
+        let concrete: Type = parse_quote!([String; 4]);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert!(subs.verify_replacement(&pattern, &concrete));
+        assert!(pattern
+            .matches(&parse_quote!([String; 5]), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_function_pointer_types() {
+        let params = type_params(&["T", "U"]);
+        let pattern: Type = parse_quote!(fn(T) -> U);
+        let concrete: Type = parse_quote!(fn(String) -> i32);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert!(subs.verify_replacement(&pattern, &concrete));
+
+        let named: Type = parse_quote!(fn(value: T));
+        let mut replaced = named.clone();
+        replaced.replace(&named.matches(&parse_quote!(fn(String)), &params).unwrap());
+        assert_eq!(replaced, parse_quote!(fn(value: String)));
+    }
+
+    #[test]
+    fn test_function_pointer_mismatches() {
+        let params = type_params(&["T", "U"]);
+        let pattern: Type = parse_quote!(fn(T) -> U);
+        for concrete in [
+            parse_quote!(fn(String, u8) -> i32),
+            parse_quote!(fn(String)),
+            parse_quote!(unsafe fn(String) -> i32),
+            parse_quote!(extern "C" fn(String) -> i32),
+        ] {
+            assert!(pattern.matches(&concrete, &params).is_err());
+        }
+        let extern_c: Type = parse_quote!(extern "C" fn(T) -> U);
+        assert!(extern_c
+            .matches(&parse_quote!(extern "C" fn(String) -> i32), &params)
+            .is_ok());
+        assert!(extern_c
+            .matches(&parse_quote!(extern "system" fn(String) -> i32), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_unit_return_matches_default() {
+        let params = type_params(&["F", "T"]);
+        let rule: Constraint = parse_quote!(F: Fn(T));
+        let subs = rule
+            .matches(&parse_quote!(Callback: Fn(u8) -> ()), &params)
+            .unwrap();
+        let mut replaced = rule.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, parse_quote!(Callback: Fn(u8)));
+        let rule: Constraint = parse_quote!(F: FnMut(T) -> ());
+        assert!(rule
+            .matches(&parse_quote!(Callback: FnMut(u8)), &params)
+            .is_ok());
+        assert!(rule
+            .matches(&parse_quote!(Callback: FnMut(u8) -> u8), &params)
+            .is_err());
+
+        let pattern: Type = parse_quote!(fn(T));
+        assert!(pattern
+            .matches(&parse_quote!(fn(u8) -> ()), &params)
+            .is_ok());
+        assert!(pattern
+            .matches(&parse_quote!(fn(u8) -> ((),)), &params)
+            .is_err());
+        let pattern: Type = parse_quote!(fn(T) -> ());
+        assert!(pattern.matches(&parse_quote!(fn(u8)), &params).is_ok());
+    }
+
+    #[test]
+    fn test_loose_paths() {
+        let params = type_params(&["T"]);
+        let loose = MatchContext {
+            loose_paths: true,
+            ..MatchContext::from(&params)
+        };
+        let pattern: Type = parse_quote!(Vec<T>);
+        let qualified: Type = parse_quote!(std::vec::Vec<u8>);
+        assert!(pattern.matches(&qualified, &params).is_err());
+        let subs = pattern.matches_in(&qualified, loose).unwrap();
+        let mut replaced = pattern.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, parse_quote!(Vec<u8>));
+        let qualified: Type = parse_quote!(alloc::vec::Vec<u8>);
+        assert!(qualified.matches_in(&parse_quote!(Vec<u8>), loose).is_ok());
+        // Only a single segment stands for any qualification
+        let other: Type = parse_quote!(std::collections::Vec<u8>);
+        assert!(qualified.matches_in(&other, loose).is_err());
+        assert!(pattern
+            .matches_in(&parse_quote!(std::vec::Deque<u8>), loose)
+            .is_err());
+        let rule: Constraint = parse_quote!(Chain<T>: Count);
+        let target: Constraint = parse_quote!(Chain<u8>: super::Count);
+        assert!(rule.matches_in(&target, loose).is_ok());
+        // Unifying keeps the paths loose as well
+        assert!(unify(&rule, &target, &params, &HashSet::new(), true).is_ok());
+        assert!(unify(&rule, &target, &params, &HashSet::new(), false).is_err());
+    }
+
+    #[test]
+    fn test_turbofish_is_ignored() {
+        let params = type_params(&["T"]);
+        let pattern: Type = parse_quote!(Vec<T>);
+        let turbofish: Type = parse_quote!(Vec::<String>);
+        let subs = pattern.matches(&turbofish, &params).unwrap();
+        assert_eq!(
+            subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(String)))
+        );
+        // The pattern keeps its own spelling when replaced
+        let mut replaced = pattern.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, parse_quote!(Vec<String>));
+
+        let pattern: Type = parse_quote!(Vec::<Option::<T>>);
+        assert!(pattern
+            .matches(&parse_quote!(Vec<Option<u8>>), &params)
+            .is_ok());
+        let rule: Constraint = parse_quote!(Vec<T>: Tr<T>);
+        assert!(rule
+            .matches(&parse_quote!(Vec::<u8>: Tr::<u8>), &params)
+            .is_ok());
+        // Both spellings are the same constraint
+        let plain: Constraint = parse_quote!(Vec<u8>: Tr<u8>);
+        assert_eq!(plain, parse_quote!(Vec::<u8>: Tr::<u8>));
+    }
+
+    #[test]
+    fn test_trait_object_types() {
+        let params = type_params(&["T"]);
+        let pattern: Type = parse_quote!(dyn Iterator<Item = T>);
+        let concrete: Type = parse_quote!(dyn Iterator<Item = String>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert!(subs.verify_replacement(&pattern, &concrete));
+        assert!(pattern
+            .matches(&parse_quote!(Iterator<Item = String>), &params)
+            .is_err());
+        assert!(pattern
+            .matches(&parse_quote!(dyn Iterator<Item = String> + Send), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_trait_object_bound_order() {
+        let params = type_params(&["T"]);
+        let pattern: Type = parse_quote!(dyn Iterator<Item = T> + Send + 'static);
+        let concrete: Type = parse_quote!(dyn Send + 'static + Iterator<Item = u8>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        let mut replaced = pattern.clone();
+        replaced.replace(&subs);
+        assert_eq!(
+            replaced,
+            parse_quote!(dyn Iterator<Item = u8> + Send + 'static)
+        );
+        assert!(pattern
+            .matches(&parse_quote!(dyn Send + 'static + Sync), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_trait_object_lifetime_bound_round_trip() {
+        let mut params = type_params(&["T"]);
+        params.insert(GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))));
+        let pattern: Type = parse_quote!(dyn Visitor<T> + 'a);
+        let concrete: Type = parse_quote!(dyn Visitor<String> + 'static);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert!(subs.verify_replacement(&pattern, &concrete));
+    }
+
+    #[test]
+    fn test_trait_object_replace() {
+        let subs = Substitute::new()
+            .insert(parse_quote!(T), parse_quote!(u8))
+            .unwrap();
+        let mut constraint: Constraint = parse_quote!(Box<dyn Handler<T> + Send>: Send);
+        constraint.replace(&subs);
+        assert_eq!(constraint, parse_quote!(Box<dyn Handler<u8> + Send>: Send));
+
+        // The node reached through the rule is the one spelled out elsewhere
+        let mut graph: gotgraph::prelude::VecGraph<Constraint, ()> = Default::default();
+        let mut index = crate::solver::ConstraintIndex::new(&graph);
+        let concrete = index.add(&mut graph, parse_quote!(Box<dyn Handler<u8> + Send>: Send));
+        assert_eq!(index.add(&mut graph, constraint), concrete);
+
+        // Lifetimes of a `for<..>` binder on a bound are left alone
+        let subs = subs
+            .insert(parse_quote!('a), parse_quote!('static))
+            .unwrap();
+        let mut ty: Type = parse_quote!(Box<dyn for<'a> Handler<&'a T> + 'a>);
+        ty.replace(&subs);
+        assert_eq!(ty, parse_quote!(Box<dyn for<'a> Handler<&'a u8> + 'static>));
+    }
+
+    #[test]
+    fn test_impl_trait_types() {
+        let params = type_params(&["T"]);
+        let pattern: Type = parse_quote!(impl Iterator<Item = T>);
+        let concrete: Type = parse_quote!(impl Iterator<Item = Vec<u8>>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert!(subs.verify_replacement(&pattern, &concrete));
+        assert!(pattern
+            .matches(&parse_quote!(dyn Iterator<Item = Vec<u8>>), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_impl_trait_with_lifetime_bound() {
+        let mut params = type_params(&["T"]);
+        params.insert(GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))));
+        let pattern: Type = parse_quote!(impl Iterator<Item = &'a T> + 'a);
+        let concrete: Type = parse_quote!(impl 'static + Iterator<Item = &'static str>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        let mut replaced = pattern.clone();
+        replaced.replace(&subs);
+        assert_eq!(
+            replaced,
+            parse_quote!(impl Iterator<Item = &'static str> + 'static)
+        );
+    }
+
+    #[test]
+    fn test_impl_trait_in_constraint() {
+        let params = type_params(&["T"]);
+        let pattern: Constraint = parse_quote!(Wrapper<impl Iterator<Item = T>>: MyTrait);
+        let concrete: Constraint = parse_quote!(Wrapper<impl Iterator<Item = String>>: MyTrait);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        let mut replaced = pattern.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, concrete);
+    }
+
+    #[test]
+    fn test_impl_trait_bound_count_mismatch() {
+        let params = type_params(&["T"]);
+        let pattern: Type = parse_quote!(impl Iterator<Item = T>);
+        assert!(pattern
+            .matches(&parse_quote!(impl Iterator<Item = u8> + Send), &params)
+            .is_err());
+        let wider: Type = parse_quote!(impl Iterator<Item = T> + Send);
+        assert!(wider
+            .matches(&parse_quote!(impl Iterator<Item = u8>), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_never_and_unit_types() {
+        let params = type_params(&["E"]);
+        let never: Type = parse_quote!(!);
+        assert_eq!(never.matches(&never, &params).ok(), Some(Substitute::new()));
+        assert!(never.matches(&parse_quote!(()), &params).is_err());
+
+        let pattern: Type = parse_quote!(Result<(), E>);
+        let concrete: Type = parse_quote!(Result<(), !>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert!(subs.verify_replacement(&pattern, &concrete));
+        assert!(pattern
+            .matches(&parse_quote!(Result<((),), !>), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_edge_case_empty_types() {
+        let params = type_params(&["T"]);
+        for empty in [parse_quote!(!), parse_quote!(())] {
+            let ty: Type = empty;
+            let result = ty.matches(&ty, &params);
+            assert!(result.is_ok());
+            assert!(result.unwrap().is_empty());
+        }
+
+        // A never-typed field behind a generic parameter
+        let pattern: Type = parse_quote!(Diverging<T>);
+        let concrete: Type = parse_quote!(Diverging<!>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(subs.len(), 1);
+        assert!(subs.verify_replacement(&pattern, &concrete));
+    }
+
+    #[test]
+    fn test_array_binds_type_and_const_params() {
+        let mut params = type_params(&["T"]);
+        params.insert(parse_quote!(const N: usize));
+        let pattern: Type = parse_quote!([T; N]);
+        let concrete: Type = parse_quote!([String; 8]);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(subs.len(), 2);
+        assert_eq!(
+            subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(String)))
+        );
+        assert_eq!(subs.get_const(&parse_quote!(N)), Some(&parse_quote!(8)));
+        assert!(subs.verify_replacement(&pattern, &concrete));
+    }
+
+    #[test]
+    fn test_assoc_const_binding() {
+        let params: HashSet<GenericParam> = [parse_quote!(const N: usize)].into_iter().collect();
+        let pattern: Constraint = parse_quote!(Buf: Trait<LEN = N>);
+        let concrete: Constraint = parse_quote!(Buf: Trait<LEN = 8>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(subs.get_const(&parse_quote!(N)), Some(&parse_quote!(8)));
+        let mut replaced = pattern.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, concrete);
+        // Unifying, either side may hold the param, and it may be braced
+        assert!(concrete.matches(&pattern, &params).is_err());
+        let (_, target_bindings) =
+            unify(&concrete, &pattern, &HashSet::new(), &params, false).unwrap();
+        assert_eq!(
+            target_bindings.get_const(&parse_quote!(N)),
+            Some(&parse_quote!(8))
+        );
+        let braced: Constraint = parse_quote!(Buf: Trait<LEN = { N }>);
+        let subs = braced.matches(&concrete, &params).unwrap();
+        assert_eq!(subs.get_const(&parse_quote!(N)), Some(&parse_quote!(8)));
+
+        // Concrete values compare by value
+        assert!(concrete
+            .matches(&parse_quote!(Buf: Trait<LEN = 8>), &params)
+            .unwrap()
+            .is_empty());
+        assert!(concrete
+            .matches(&parse_quote!(Buf: Trait<LEN = { 4 + 4 }>), &params)
+            .is_ok());
+        assert!(concrete
+            .matches(&parse_quote!(Buf: Trait<LEN = 4>), &params)
+            .is_err());
+        assert!(pattern
+            .matches(&parse_quote!(Buf: Trait<SIZE = 8>), &params)
+            .is_err());
+        // A type param is not bound to a const
+        let params = type_params(&["N"]);
+        let subs = pattern.matches(&parse_quote!(Buf: Trait<LEN = u8>), &params);
+        assert_eq!(
+            subs.unwrap().get(&parse_quote!(N)),
+            Some(&GenericArgument::Type(parse_quote!(u8)))
+        );
+        assert!(pattern.matches(&concrete, &params).is_err());
+    }
+
+    #[test]
+    fn test_array_length_wildcard() {
+        let mut params = type_params(&["T"]);
+        let pattern: Type = parse_quote!([T; _]);
+        for target in [
+            parse_quote!([u8; 3]),
+            parse_quote!([u8; 2 * LEN]),
+            parse_quote!([u8; { core::mem::size_of::<u64>() }]),
+        ] {
+            let subs = pattern.matches(&target, &params).unwrap();
+            assert_eq!(subs.len(), 1);
+            assert_eq!(
+                subs.get(&parse_quote!(T)),
+                Some(&GenericArgument::Type(parse_quote!(u8)))
+            );
+        }
+        assert!(pattern.matches(&parse_quote!([u8]), &params).is_err());
+        // The wildcard is left as written, as nothing is bound to it
+        let mut replaced = pattern.clone();
+        replaced.replace(&pattern.matches(&parse_quote!([u8; 3]), &params).unwrap());
+        assert_eq!(replaced, parse_quote!([u8; _]));
+
+        // A const param still binds the length
+        params.insert(parse_quote!(const N: usize));
+        let bound: Type = parse_quote!([T; N]);
+        let subs = bound.matches(&parse_quote!([u8; 3]), &params).unwrap();
+        assert_eq!(subs.get_const(&parse_quote!(N)), Some(&parse_quote!(3)));
+    }
+
+    #[test]
+    fn test_unusual_const_expressions_do_not_match() {
+        let params: HashSet<GenericParam> = [parse_quote!(const N: usize)].into_iter().collect();
+        let rule: Constraint = parse_quote!(Arr<{ N * 2 }>: Tr);
+        let unusual: [Constraint; 6] = [
+            parse_quote!(Arr<{ match N { 0 => 1, _ => 2 } }>: Tr),
+            parse_quote!(Arr<{ if N > 0 { 1 } else { 2 } }>: Tr),
+            parse_quote!(Arr<{ core::mem::size_of::<u8>() }>: Tr),
+            parse_quote!(Arr<{ [1, 2][0] }>: Tr),
+            parse_quote!(Arr<{ (|| 4)() }>: Tr),
+            parse_quote!(Arr<{ id!(4) }>: Tr),
+        ];
+        for constraint in &unusual {
+            assert!(rule.matches(constraint, &params).is_err());
+            assert!(constraint.matches(&rule, &params).is_err());
+        }
+        // Without evaluating them, not even the same expression is known to be equal
+        let call: Constraint = parse_quote!(Arr<{ f(4) }>: Tr);
+        assert!(call.matches(&call.clone(), &params).is_err());
+    }
+
+    #[test]
+    fn test_array_length_arithmetic() {
+        let mut params = type_params(&["T"]);
+        params.insert(parse_quote!(const N: usize));
+        // Identical lengths are equal by value, and so are lengths spelled differently
+        let literal: Type = parse_quote!([u8; 4 + 1]);
+        assert!(literal
+            .matches(&parse_quote!([u8; 4 + 1]), &params)
+            .unwrap()
+            .is_empty());
+        assert!(literal.matches(&parse_quote!([u8; 5]), &params).is_ok());
+        assert!(literal
+            .matches(&parse_quote!([u8; 4 + 2]), &params)
+            .is_err());
+
+        let pattern: Type = parse_quote!([T; N + 1]);
+        let concrete: Type = parse_quote!([String; 4 + 1]);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(
+            subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(String)))
+        );
+        assert_eq!(subs.get_const(&parse_quote!(N)), Some(&parse_quote!(4)));
+        assert!(subs.verify_replacement(&pattern, &concrete));
+        // `N` is not solved for, so an evaluated length does not match, without aborting
+        let error = pattern
+            .matches(&parse_quote!([String; 5]), &params)
+            .unwrap_err();
+        assert_eq!(error.reason, "different const expressions");
+    }
+
+    #[test]
+    fn test_const_params_of_any_type() {
+        let params: HashSet<GenericParam> =
+            [parse_quote!(const N: u8), parse_quote!(const B: bool)]
+                .into_iter()
+                .collect();
+        let pattern: Type = parse_quote!(Flags<N, B>);
+        let concrete: Type = parse_quote!(Flags<3, true>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert!(subs.contains_key(&parse_quote!(const N: u8)));
+        assert!(subs.contains_key(&parse_quote!(const B: bool)));
+        assert!(subs.verify_replacement(&pattern, &concrete));
+
+        let array: Type = parse_quote!([u8; N]);
+        let subs = array.matches(&parse_quote!([u8; 4]), &params).unwrap();
+        assert_eq!(subs.get_const(&parse_quote!(N)), Some(&parse_quote!(4)));
+    }
+
+    #[test]
+    fn test_bool_char_and_enum_const_params() {
+        let params: HashSet<GenericParam> = [
+            parse_quote!(const FLAG: bool),
+            parse_quote!(const C: char),
+            parse_quote!(const M: Mode),
+        ]
+        .into_iter()
+        .collect();
+        let pattern: Type = parse_quote!(Gate<FLAG, C, M>);
+        // Under `adt_const_params`, enum values are passed as braced blocks
+        let concrete: Type = parse_quote!(Gate<false, 'x', { Mode::Fast }>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(subs.len(), 3);
+        assert_eq!(
+            subs.get_const(&parse_quote!(FLAG)),
+            Some(&parse_quote!(false))
+        );
+        assert_eq!(subs.get_const(&parse_quote!(C)), Some(&parse_quote!('x')));
+        assert_eq!(
+            subs.get_const(&parse_quote!(M)),
+            Some(&parse_quote!({ Mode::Fast }))
+        );
+        assert!(subs.verify_replacement(&pattern, &concrete));
+
+        // Dependencies mentioning the params are rewritten with the same values
+        let mut dependency: Type = parse_quote!(Latch<{ M }, [u8; 2], FLAG>);
+        dependency.replace(&subs);
+        assert_eq!(
+            dependency,
+            parse_quote!(Latch<{ Mode::Fast }, [u8; 2], false>)
+        );
+
+        let fixed: Type = parse_quote!(Gate<true, 'x', { Mode::Fast }>);
+        assert!(fixed.matches(&concrete, &params).is_err());
+        assert!(fixed
+            .matches(&parse_quote!(Gate<true, 'x', { Mode::Fast }>), &params)
+            .is_ok());
+        assert!(fixed
+            .matches(&parse_quote!(Gate<true, 'x', { Mode::Slow }>), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_const_arithmetic_matches_structurally() {
+        let params: HashSet<GenericParam> = [parse_quote!(const N: usize)].into_iter().collect();
+        let pattern: Type = parse_quote!(Arr<{ N + 1 }>);
+        let concrete: Type = parse_quote!(Arr<{ 4 + 1 }>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(subs.get_const(&parse_quote!(N)), Some(&parse_quote!(4)));
+        assert!(subs.verify_replacement(&pattern, &concrete));
+
+        let nested: Type = parse_quote!(Arr<{ -(N * 2) }>);
+        let subs = nested
+            .matches(&parse_quote!(Arr<{ -(3 * 2) }>), &params)
+            .unwrap();
+        assert_eq!(subs.get_const(&parse_quote!(N)), Some(&parse_quote!(3)));
+
+        // A different operator, or an operand which is not a param, does not match
+        assert!(pattern
+            .matches(&parse_quote!(Arr<{ 4 - 1 }>), &params)
+            .is_err());
+        assert!(pattern
+            .matches(&parse_quote!(Arr<{ 4 + 2 }>), &params)
+            .is_err());
+        // Expressions which cannot be compared give up instead of aborting
+        let call: Type = parse_quote!(Arr<{ f(N) }>);
+        assert!(call.matches(&parse_quote!(Arr<{ f(4) }>), &params).is_err());
+    }
+
+    #[test]
+    fn test_empty_angle_brackets() {
+        // Parsed from strings, as rustfmt drops the empty `<>` within `parse_quote!`
+        let parse = |source: &str| syn::parse_str::<Type>(source).unwrap();
+        let params = type_params(&["T"]);
+        let bare = parse("Foo");
+        let empty = parse("Foo<>");
+        assert_eq!(bare.matches(&empty, &params).ok(), Some(Substitute::new()));
+        assert_eq!(empty.matches(&bare, &params).ok(), Some(Substitute::new()));
+        let nested = parse("Vec<Foo<>>");
+        let pattern = parse("Vec<T>");
+        let subs = pattern.matches(&nested, &params).unwrap();
+        assert_eq!(
+            subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(empty.clone()))
+        );
+        assert!(parse("Vec<Foo>").matches(&nested, &params).is_ok());
+        assert!(nested.matches(&parse("Vec<Foo>"), &params).is_ok());
+        assert!(empty.matches(&parse("Foo<u8>"), &params).is_err());
+        let path: Path = syn::parse_str("a::Foo<>::Bar").unwrap();
+        assert!(path.matches(&parse_quote!(a::Foo::Bar), &params).is_ok());
+    }
+
+    #[test]
+    fn test_literals_compare_by_value() {
+        let params = type_params(&["T"]);
+        let same = |l: Type, r: Type| l.matches(&r, &params).is_ok();
+        assert!(same(parse_quote!([T; 0x10]), parse_quote!([u8; 16])));
+        assert!(same(
+            parse_quote!([u8; 0b1_0000usize]),
+            parse_quote!([u8; 16])
+        ));
+        assert!(same(parse_quote!([u8; 0o20]), parse_quote!([u8; { 16 }])));
+        assert!(same(
+            parse_quote!(Reg<{ 0x10 }>),
+            parse_quote!(Reg<{ (16) }>)
+        ));
+        assert!(!same(parse_quote!([u8; 0x10]), parse_quote!([u8; 17])));
+        assert!(same(parse_quote!(Glyph<'\x41'>), parse_quote!(Glyph<'A'>)));
+        assert!(same(
+            parse_quote!(Glyph<'\u{e9}'>),
+            parse_quote!(Glyph<'é'>)
+        ));
+        assert!(!same(parse_quote!(Glyph<'a'>), parse_quote!(Glyph<'A'>)));
+        assert!(same(parse_quote!(Byte<b'A'>), parse_quote!(Byte<65u8>)));
+        assert!(same(parse_quote!(Byte<b'A'>), parse_quote!(Byte<0x41>)));
+        assert!(!same(parse_quote!(Byte<b'A'>), parse_quote!(Byte<65u16>)));
+        assert!(same(parse_quote!(Flag<{ true }>), parse_quote!(Flag<true>)));
+    }
+
+    #[test]
+    fn test_replace_within_const_expressions() {
+        let mut params = type_params(&["T"]);
+        params.insert(parse_quote!(const ROWS: usize));
+        params.insert(parse_quote!(const COLS: usize));
+        let pattern: Type = parse_quote!(Grid<T, ROWS, COLS>);
+        let subs = pattern
+            .matches(&parse_quote!(Grid<u8, 2, 3>), &params)
+            .unwrap();
+        let mut replaced: Constraint = parse_quote!(Matrix<T, { ROWS * COLS }>: Storage);
+        replaced.replace(&subs);
+        assert_eq!(replaced, parse_quote!(Matrix<u8, { 2 * 3 }>: Storage));
+        // Every kind of expression is descended into, including the types it mentions
+        let mut replaced: Type = parse_quote!(
+            Arr<{ -(f::<T>(ROWS)[COLS] as usize) + *&ROWS + { COLS } + size_of::<[T; ROWS]>() }>
+        );
+        replaced.replace(&subs);
+        let expected: Type =
+            parse_quote!(Arr<{ -(f::<u8>(2)[3] as usize) + *&2 + { 3 } + size_of::<[u8; 2]>() }>);
+        assert_eq!(replaced, expected);
+        let mut cast: Type = parse_quote!(Arr<{ ROWS as T }>);
+        cast.replace(&subs);
+        assert_eq!(cast, parse_quote!(Arr<{ 2 as u8 }>));
+    }
+
+    #[test]
+    fn test_infer_wildcard() {
+        let params = type_params(&["K", "V"]);
+        let pattern: Type = parse_quote!(Vec<_>);
+        for concrete in [parse_quote!(Vec<String>), parse_quote!(Vec<Vec<u8>>)] {
+            let subs = pattern.matches(&concrete, &params).unwrap();
+            assert!(subs.is_empty());
+            let mut replaced = pattern.clone();
+            replaced.replace(&subs);
+            assert_eq!(replaced, pattern);
+        }
+        assert!(pattern
+            .matches(&parse_quote!(HashMap<K, V>), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_higher_ranked_trait_bounds() {
+        let mut params = type_params(&["T"]);
+        params.insert(GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))));
+        let pattern: TypeParamBound = parse_quote!(for<'a> Trait<&'a T>);
+        let concrete: TypeParamBound = parse_quote!(for<'a> Trait<&'a String>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        // `'a` is bound by `for<'a>`, so only `T` is substituted
+        assert_eq!(subs.len(), 1);
+        let mut replaced = pattern.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, concrete);
+
+        assert!(pattern
+            .matches(&parse_quote!(for<'b> Trait<&'b String>), &params)
+            .is_err());
+        assert!(pattern
+            .matches(&parse_quote!(Trait<&'a String>), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_higher_ranked_lifetime_is_not_replaced() {
+        let mut subs = Substitute::new()
+            .insert(
+                GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))),
+                GenericArgument::Lifetime(parse_quote!('static)),
+            )
+            .unwrap();
+        subs = subs
+            .insert(parse_quote!(T), GenericArgument::Type(parse_quote!(u8)))
+            .unwrap();
+        let mut bound: TypeParamBound = parse_quote!(for<'a> Fn(&'a T) -> &'a T);
+        bound.replace(&subs);
+        assert_eq!(bound, parse_quote!(for<'a> Fn(&'a u8) -> &'a u8));
+        let mut free: TypeParamBound = parse_quote!(Fn(&'a T));
+        free.replace(&subs);
+        assert_eq!(free, parse_quote!(Fn(&'static u8)));
+    }
+
+    #[test]
+    fn test_nested_associated_type_binding() {
+        let params = type_params(&["T"]);
+        let pattern: Path = parse_quote!(Trait<Inner = Vec<T>>);
+        let concrete: Path = parse_quote!(Trait<Inner = Vec<String>>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(
+            subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(String)))
+        );
+        let mut replaced = pattern.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, concrete);
+        assert!(pattern
+            .matches(&parse_quote!(Trait<Outer = Vec<String>>), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_associated_constraint_bound_order() {
+        let params = type_params(&["T"]);
+        let pattern: Path = parse_quote!(Trait<Item: Read + Into<T>>);
+        let concrete: Path = parse_quote!(Trait<Item: Into<u8> + Read>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(
+            subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(u8)))
+        );
+        assert!(pattern
+            .matches(&parse_quote!(Trait<Item: Into<u8> + Write>), &params)
+            .is_err());
+        assert!(pattern
+            .matches(&parse_quote!(Trait<Item: Read>), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_bound_matching_backtracks() {
+        let params = type_params(&["T"]);
+        // Pairing `Into<T>` with `Into<u16>` first leaves nothing for `Into<u16>`
+        let pattern: Path = parse_quote!(Trait<Item: Into<T> + Into<u16>>);
+        let concrete: Path = parse_quote!(Trait<Item: Into<u16> + Into<u8>>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(
+            subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(u8)))
+        );
+        // Of several pairings, the same one is found however the bounds are written
+        let pattern: Type = parse_quote!(dyn Into<T> + From<T>);
+        let either: [Type; 2] = [
+            parse_quote!(dyn Into<u8> + From<u8>),
+            parse_quote!(dyn From<u8> + Into<u8>),
+        ];
+        for concrete in either {
+            let subs = pattern.matches(&concrete, &params).unwrap();
+            assert_eq!(
+                subs.get(&parse_quote!(T)),
+                Some(&GenericArgument::Type(parse_quote!(u8)))
+            );
+        }
+        let conflicting: Type = parse_quote!(dyn Into<u8> + From<u16>);
+        assert!(pattern.matches(&conflicting, &params).is_err());
+    }
+
+    #[test]
+    fn test_elided_reference_lifetimes() {
+        let mut params = type_params(&["T"]);
+        params.insert(GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))));
+        let pattern: Type = parse_quote!(&'a T);
+        let subs = pattern.matches(&parse_quote!(&Foo), &params).unwrap();
+        assert_eq!(subs.len(), 2);
+        let mut replaced = pattern.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, parse_quote!(&Foo));
+
+        // The elided lifetime gives way to an explicit one bound elsewhere
+        let pair: Type = parse_quote!((&'a T, &'a u8));
+        let subs = pair
+            .matches(&parse_quote!((&Foo, &'static u8)), &params)
+            .unwrap();
+        let mut replaced = pair.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, parse_quote!((&'static Foo, &'static u8)));
+        let subs = pair
+            .matches(&parse_quote!((&'static Foo, &u8)), &params)
+            .unwrap();
+        let mut replaced = pair.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, parse_quote!((&'static Foo, &'static u8)));
+
+        let elided: Type = parse_quote!(&T);
+        let subs = elided
+            .matches(&parse_quote!(&'static Foo), &params)
+            .unwrap();
+        let mut replaced = elided.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, parse_quote!(&Foo));
+
+        assert!(pattern.matches(&parse_quote!(&mut Foo), &params).is_err());
+        assert!(elided
+            .matches(&parse_quote!(&'static mut Foo), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_lifetimes_replaced_at_any_depth() {
+        let subs = Substitute::new()
+            .insert(
+                GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))),
+                GenericArgument::Lifetime(parse_quote!('static)),
+            )
+            .unwrap();
+        let cases: Vec<(Type, Type)> = vec![
+            (parse_quote!(Cow<'a, str>), parse_quote!(Cow<'static, str>)),
+            (
+                parse_quote!(Ref<'a, Node<'a>>),
+                parse_quote!(Ref<'static, Node<'static>>),
+            ),
+            (
+                parse_quote!(Box<dyn Iterator<Item = &'a Node<'a>> + 'a>),
+                parse_quote!(Box<dyn Iterator<Item = &'static Node<'static>> + 'static>),
+            ),
+            (
+                parse_quote!(<&'a T as Tr<'a>>::Out),
+                parse_quote!(<&'static T as Tr<'static>>::Out),
+            ),
+            (
+                parse_quote!(fn(&'a T) -> Cow<'a, str>),
+                parse_quote!(fn(&'static T) -> Cow<'static, str>),
+            ),
+            (
+                parse_quote!([*const &'a (Cow<'a, str>,); 2]),
+                parse_quote!([*const &'static (Cow<'static, str>,); 2]),
+            ),
+            (
+                parse_quote!(impl Fn(&'a T) -> &'a T),
+                parse_quote!(impl Fn(&'static T) -> &'static T),
+            ),
+        ];
+        for (mut ty, expected) in cases {
+            ty.replace(&subs);
+            assert_eq!(ty, expected);
+        }
+    }
+
+    #[test]
+    fn test_lifetime_and_type_params_in_path() {
+        let mut params = type_params(&["T"]);
+        params.insert(GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))));
+        let pattern: Type = parse_quote!(Cow<'a, T>);
+        let concrete: Type = parse_quote!(Cow<'static, String>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(
+            subs.get(&GenericParam::Lifetime(LifetimeParam::new(
+                parse_quote!('a)
+            ))),
+            Some(&GenericArgument::Lifetime(parse_quote!('static)))
+        );
+        assert_eq!(
+            subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(String)))
+        );
+        assert!(subs.verify_replacement(&pattern, &concrete));
+    }
+
+    #[test]
+    fn test_higher_ranked_fn_pointer_lifetimes() {
+        let mut params = type_params(&["T"]);
+        params.insert(GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))));
+        let pattern: Type = parse_quote!(for<'a> fn(&'a T) -> &'a T);
+        let concrete: Type = parse_quote!(for<'a> fn(&'a u8) -> &'a u8);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(subs.len(), 1);
+        let subs = subs
+            .insert(
+                GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))),
+                GenericArgument::Lifetime(parse_quote!('static)),
+            )
+            .unwrap();
+        assert!(subs.verify_replacement(&pattern, &concrete));
+    }
+
+    #[test]
+    fn test_higher_ranked_constraint() {
+        let mut params = type_params(&["F"]);
+        params.insert(GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))));
+        let pattern: Constraint = parse_quote!(for<'a> F: Fn(&'a str) -> &'a str);
+        let concrete: Constraint = parse_quote!(for<'a> fn(&str) -> &str: Fn(&'a str) -> &'a str);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(subs.len(), 1);
+        let mut replaced = pattern.clone();
+        let subs = subs
+            .insert(
+                GenericParam::Lifetime(LifetimeParam::new(parse_quote!('a))),
+                GenericArgument::Lifetime(parse_quote!('static)),
+            )
+            .unwrap();
+        replaced.replace(&subs);
+        assert_eq!(replaced, concrete);
+        // Binders must agree, as the bound lifetime is not a parameter
+        let unbound: Constraint = parse_quote!(fn(&str) -> &str: Fn(&'a str) -> &'a str);
+        assert!(pattern.matches(&unbound, &params).is_err());
+    }
+
+    #[test]
+    fn test_assoc_const_binding_constraint() {
+        let mut params = type_params(&["T"]);
+        params.insert(parse_quote!(const M: usize));
+        let pattern: Constraint = parse_quote!(Node<T>: Tr<N = { M }>);
+        let concrete: Constraint = parse_quote!(Node<u8>: Tr<N = 4>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs.get_const(&parse_quote!(M)), Some(&parse_quote!(4)));
+        let mut replaced: Constraint = parse_quote!(Leaf<T>: Tr<N = { M }>);
+        replaced.replace(&subs);
+        assert_eq!(replaced, parse_quote!(Leaf<u8>: Tr<N = { 4 }>));
+        // Bindings of other consts, or of other values, do not match
+        assert!(pattern
+            .matches(&parse_quote!(Node<u8>: Tr<K = 4>), &params)
+            .is_err());
+        let fixed: Constraint = parse_quote!(Node<T>: Tr<N = 4>);
+        assert!(fixed
+            .matches(&parse_quote!(Node<u8>: Tr<N = 5>), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_qualified_self_paths() {
+        let params = type_params(&["T"]);
+        let pattern: Type = parse_quote!(<T as Collection>::Item);
+        let subs = pattern
+            .matches(&parse_quote!(<Vec<u8> as Collection>::Item), &params)
+            .unwrap();
+        assert_eq!(subs.len(), 1);
+        let mut replaced = pattern.clone();
+        replaced.replace(&subs);
+        assert_eq!(replaced, parse_quote!(<Vec<u8> as Collection>::Item));
+        // The trait may be named through a longer path
+        let subs = pattern
+            .matches(&parse_quote!(<Vec<u8> as crate::Collection>::Item), &params)
+            .unwrap();
+        assert_eq!(subs.len(), 1);
+        assert!(pattern
+            .matches(&parse_quote!(<Vec<u8> as Collection>::Key), &params)
+            .is_err());
+        assert!(pattern
+            .matches(&parse_quote!(<Vec<u8> as Other>::Item), &params)
+            .is_err());
+        assert!(pattern
+            .matches(&parse_quote!(<Vec<u8>>::Item), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_qualified_self_with_generic_arguments() {
+        let params = type_params(&["T", "U"]);
+        let pattern: Type = parse_quote!(<Wrapper<T> as Collection<U>>::Item);
+        let concrete: Type = parse_quote!(<Wrapper<Vec<u8>> as Collection<String>>::Item);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert_eq!(subs.len(), 2);
+        assert!(subs.verify_replacement(&pattern, &concrete));
+        // The same parameter in the self type and the trait arguments must agree
+        let pattern: Type = parse_quote!(<Wrapper<T> as Collection<T>>::Item);
+        assert!(pattern.matches(&concrete, &params).is_err());
+    }
+
+    #[test]
+    fn test_occurs_check() {
+        let params = type_params(&["T"]);
+        let pattern: Type = parse_quote!((T, T));
+        let target: Type = parse_quote!((Vec<T>, Vec<T>));
+        assert!(pattern.matches(&target, &params).is_err());
+        assert_eq!(
+            cyclic_binding(&pattern, &target, &params, false),
+            Some((parse_quote!(T), GenericArgument::Type(parse_quote!(Vec<T>))))
+        );
+        // Binding a param to itself does not grow anything
+        assert!(pattern.matches(&parse_quote!((T, T)), &params).is_ok());
+        // Nor does binding it to an unrelated term
+        let target: Type = parse_quote!((Vec<u8>, Vec<u8>));
+        assert!(pattern.matches(&target, &params).is_ok());
+        assert!(cyclic_binding(&pattern, &target, &params, false).is_none());
+        // Unifying over shared params, a binding recorded earlier may become cyclic once a later
+        // one is applied to it
+        let params = type_params(&["T", "U"]);
+        let shared = MatchContext {
+            target_params: Some(&params),
+            ..MatchContext::from(&params)
+        };
+        let pattern: Type = parse_quote!((T, U));
+        assert!(pattern
+            .matches_in(&parse_quote!((Vec<U>, Option<T>)), shared)
+            .is_err());
+
+        // Inserted directly, a binding is checked all the same
+        let error = Substitute::new()
+            .insert(parse_quote!(T), parse_quote!(Vec<T>))
+            .unwrap_err();
+        assert_eq!(error.reason, "the param occurs in its own binding");
+        let subs = Substitute::new()
+            .insert(parse_quote!(T), parse_quote!(Vec<U>))
+            .unwrap();
+        let mut ty: Type = parse_quote!(Option<T>);
+        ty.replace(&subs);
+        assert_eq!(ty, parse_quote!(Option<Vec<U>>));
+        // The dictionary is idempotent, so replacing again changes nothing
+        ty.replace(&subs);
+        assert_eq!(ty, parse_quote!(Option<Vec<U>>));
+    }
+
+    #[test]
+    fn test_compose() {
+        let binding = |param: &str, arg: Type| -> Substitute {
+            let ident = Ident::new(param, proc_macro2::Span::call_site());
+            Substitute::new()
+                .insert(parse_quote!(#ident), GenericArgument::Type(arg))
+                .unwrap()
+        };
+        let composed = binding("T", parse_quote!(U))
+            .compose(binding("U", parse_quote!(String)))
+            .unwrap();
+        let expected = binding("T", parse_quote!(String))
+            .combine(binding("U", parse_quote!(String)), &HashSet::new())
+            .unwrap();
+        assert_eq!(composed.0, expected.0);
+
+        // Applying the composition is applying one substitution after the other
+        let (first, second) = (
+            binding("T", parse_quote!(Vec<U>)),
+            binding("U", parse_quote!(Box<V>)),
+        );
+        let mut stepwise: Type = parse_quote!((T, U, V));
+        stepwise.replace(&first);
+        stepwise.replace(&second);
+        let mut at_once: Type = parse_quote!((T, U, V));
+        at_once.replace(&first.compose(second).unwrap());
+        assert_eq!(stepwise, at_once);
+        assert_eq!(at_once, parse_quote!((Vec<Box<V>>, Box<V>, V)));
+
+        // Associative
+        let (a, b, c) = (
+            binding("T", parse_quote!(Option<U>)),
+            binding("U", parse_quote!(Vec<V>)),
+            binding("V", parse_quote!(u8)),
+        );
+        let left = a.clone().compose(b.clone()).unwrap().compose(c.clone());
+        let right = a.compose(b.compose(c).unwrap());
+        assert_eq!(left.unwrap().0, right.unwrap().0);
+
+        // A param bound to itself in the end is dropped
+        let swapped = binding("T", parse_quote!(U))
+            .compose(binding("U", parse_quote!(T)))
+            .unwrap();
+        assert_eq!(swapped.0, binding("U", parse_quote!(T)).0);
+
+        // Different arguments for the same param are not unified
+        let error = binding("T", parse_quote!(u8))
+            .compose(binding("T", parse_quote!(u16)))
+            .unwrap_err();
+        assert_eq!(error.conflict.unwrap().param, parse_quote!(T));
+        // Nor may a param end up in its own argument
+        let error = binding("T", parse_quote!(U))
+            .compose(binding("U", parse_quote!(Vec<T>)))
+            .unwrap_err();
+        assert_eq!(error.reason, "the param occurs in its own binding");
+    }
+
+    #[test]
+    fn test_conflicting_param_is_reported() {
+        let params = type_params(&["T"]);
+        let pattern: Type = parse_quote!((T, Vec<T>));
+        let error = pattern
+            .matches(&parse_quote!((Vec<u8>, Vec<Vec<u16>>)), &params)
+            .unwrap_err();
+        let conflict = error.conflict.as_deref().expect("a conflict");
+        assert_eq!(conflict.param, parse_quote!(T));
+        assert_eq!(conflict.first, parse_quote!(Vec<u8>));
+        assert_eq!(conflict.second, parse_quote!(Vec<u16>));
+        assert!(error
+            .to_string()
+            .starts_with("`T` would need to be both `Vec < u8 >` and `Vec < u16 >`: "));
+
+        // The param of the trait arguments conflicts with the one of the type
+        let params = type_params(&["S"]);
+        let rule: Constraint = parse_quote!(Pair<S, S>: Tr<S>);
+        let error = rule
+            .matches(&parse_quote!(Pair<u8, u8>: Tr<u16>), &params)
+            .unwrap_err();
+        let conflict = error.conflict.expect("a conflict");
+        assert_eq!(conflict.param, parse_quote!(S));
+        let arguments: HashSet<GenericArgument> = [conflict.first, conflict.second].into();
+        assert_eq!(
+            arguments,
+            [parse_quote!(u8), parse_quote!(u16)].into_iter().collect()
+        );
+
+        // Mismatches of terms without params have no conflict
+        let pattern: Type = parse_quote!(Vec<S>);
+        let error = pattern
+            .matches(&parse_quote!(Box<u8>), &params)
+            .unwrap_err();
+        assert!(error.conflict.is_none());
+    }
+
+    #[test]
+    fn test_mismatch_reasons() {
+        let params = type_params(&["T"]);
+        let reason =
+            |pattern: Type, target: Type| pattern.matches(&target, &params).unwrap_err().reason;
+        assert_eq!(
+            reason(parse_quote!(Vec<T>), parse_quote!(Box<u8>)),
+            "different path segments"
+        );
+        // A plain match takes arguments as they are, so differing ones conflict
+        assert_eq!(
+            reason(parse_quote!((T, T)), parse_quote!((u8, u16))),
+            "the param is bound to different arguments"
+        );
+        let conflict = Substitute::new()
+            .insert(parse_quote!(T), parse_quote!(u8))
+            .and_then(|substitute| substitute.insert(parse_quote!(T), parse_quote!(u16)));
+        assert_eq!(
+            conflict.unwrap_err().reason,
+            "the param is bound to different arguments"
+        );
+        assert_eq!(
+            reason(parse_quote!((T, T)), parse_quote!((Vec<T>, Vec<T>))),
+            "the param occurs in its own binding"
+        );
+        assert_eq!(
+            reason(parse_quote!([T; 3]), parse_quote!((u8,))),
+            "different kinds of types"
+        );
+    }
+
+    #[test]
+    fn test_groups_and_parens_are_transparent() {
+        let params = type_params(&["T"]);
+        let grouped = Type::Group(TypeGroup {
+            group_token: Default::default(),
+            elem: Box::new(parse_quote!(Vec<u8>)),
+        });
+        let pattern: Type = parse_quote!(Vec<T>);
+        let subs = pattern.matches(&grouped, &params).unwrap();
+        assert_eq!(
+            subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(u8)))
+        );
+        assert!(pattern.matches(&parse_quote!((Vec<u8>)), &params).is_ok());
+        let pattern: Type = parse_quote!((Vec<T>));
+        assert!(pattern.matches(&parse_quote!(Vec<u8>), &params).is_ok());
+        // The same param bound through a group and directly does not conflict
+        let pattern: Type = parse_quote!((T, T));
+        let target: Type = parse_quote!((#grouped, (Vec<u8>)));
+        assert!(pattern.matches(&target, &params).is_ok());
+        assert!(pattern
+            .matches(&parse_quote!((#grouped, Vec<u16>)), &params)
+            .is_err());
+    }
+
+    #[test]
+    fn test_inert_attributes_are_stripped() {
+        let param: GenericParam = parse_quote!(T);
+        let arg: GenericArgument = parse_quote!(
+            [u8; {
+                #![allow(unused)]
+                #[doc = "four"]
+                let n = 4;
+                n
+            }]
+        );
+        let stripped: GenericArgument = parse_quote!(
+            [u8; {
+                let n = 4;
+                n
+            }]
+        );
+        let substitute = Substitute::from_param_arg(param.clone(), arg).unwrap();
+        assert_eq!(substitute.get(&param), Some(&stripped));
+
+        // Dropping `cfg` would make the gated code unconditional, so it is kept
+        let gated: GenericArgument = parse_quote!(
+            [u8; {
+                #[cfg(feature = "wide")]
+                let n = 4;
+                n
+            }]
+        );
+        assert_eq!(strip_inert_attributes(&gated), gated);
+        assert!(has_attributes_recursive(&gated));
+        let error = Substitute::from_param_arg(param.clone(), gated).unwrap_err();
+        assert!(error.is_unsupported());
+        let pattern: Type = parse_quote!([u8; N]);
+        let params = [parse_quote!(const N: usize)].into_iter().collect();
+        let target: Type = parse_quote!(
+            [u8; {
+                #[cfg(feature = "wide")]
+                let n = 4;
+                n
+            }]
+        );
+        assert!(pattern
+            .matches(&target, &params)
+            .unwrap_err()
+            .is_unsupported());
+
+        // Tokens of a macro invocation are not attributes
+        let arg: GenericArgument = parse_quote!(m!(
+            #[allow(unused)]
+            u8
+        ));
+        assert_eq!(strip_inert_attributes(&arg), arg);
+        assert!(!has_attributes_recursive(&arg));
+    }
+
+    #[test]
+    fn test_replacement_in_nested_generics() {
+        let params = type_params(&["K", "V"]);
+        let pattern: Type = parse_quote!(HashMap<K, Vec<Option<(K, Box<V>)>>>);
+        let concrete: Type = parse_quote!(HashMap<String, Vec<Option<(String, Box<Node<u8>>)>>>);
+        let subs = pattern.matches(&concrete, &params).unwrap();
+        assert!(subs.verify_replacement(&pattern, &concrete));
+        assert!(!subs.verify_replacement(
+            &pattern,
+            &parse_quote!(HashMap<String, Vec<Option<(String, Box<u8>)>>>)
+        ));
+        assert!(!Substitute::new().verify_replacement(&pattern, &concrete));
+    }
+
+    #[test]
+    fn test_unification_binds_either_side() {
+        let pattern_params = type_params(&["X"]);
+        let target_params = type_params(&["T"]);
+        let pattern: Type = parse_quote!(Pair<X, u8>);
+        let target: Type = parse_quote!(Pair<T, T>);
+        let (pattern_subs, target_subs) =
+            unify(&pattern, &target, &pattern_params, &target_params, false).unwrap();
+        assert_eq!(
+            pattern_subs.get(&parse_quote!(X)),
+            Some(&GenericArgument::Type(parse_quote!(u8)))
+        );
+        assert_eq!(
+            target_subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(u8)))
+        );
+
+        // The second `X` is unified with what the first one is bound to
+        let pattern: Type = parse_quote!(Pair<X, X>);
+        let target: Type = parse_quote!(Pair<T, u8>);
+        let (pattern_subs, target_subs) =
+            unify(&pattern, &target, &pattern_params, &target_params, false).unwrap();
+        assert!(pattern_subs.verify_replacement(&pattern, &parse_quote!(Pair<u8, u8>)));
+        assert!(target_subs.verify_replacement(&target, &parse_quote!(Pair<u8, u8>)));
+
+        // A target which is an instance of the pattern leaves the target params alone
+        let target: Type = parse_quote!(Pair<Vec<T>, Vec<T>>);
+        let (pattern_subs, target_subs) =
+            unify(&pattern, &target, &pattern_params, &target_params, false).unwrap();
+        assert!(pattern_subs.verify_replacement(&pattern, &target));
+        assert!(target_subs.is_empty());
+    }
+
+    #[test]
+    fn test_combine_unifies_double_bindings() {
+        // The arguments hold params only when unifying, here over params shared by both sides
+        let params = type_params(&["T", "U"]);
+        let shared = MatchContext {
+            target_params: Some(&params),
+            ..MatchContext::from(&params)
+        };
+        let first = Substitute::from_param_arg(
+            parse_quote!(U),
+            GenericArgument::Type(parse_quote!(Vec<T>)),
+        )
+        .unwrap();
+        let second = Substitute::from_param_arg(
+            parse_quote!(U),
+            GenericArgument::Type(parse_quote!(Vec<String>)),
+        )
+        .unwrap();
+        let combined = first.clone().combine(second.clone(), shared).unwrap();
+        assert_eq!(
+            combined.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(String)))
+        );
+        assert_eq!(
+            combined.get(&parse_quote!(U)),
+            Some(&GenericArgument::Type(parse_quote!(Vec<String>)))
+        );
+        assert_eq!(
+            second.clone().combine(first.clone(), shared).unwrap(),
+            combined
+        );
+
+        // The same param flowing through both elements of a tuple
+        let pattern: Type = parse_quote!((U, U));
+        let subs = pattern
+            .matches_in(&parse_quote!((Vec<T>, Vec<String>)), shared)
+            .unwrap();
+        assert_eq!(subs, combined);
+        // Only a failing unification of the two bindings fails
+        let error = pattern
+            .matches_in(&parse_quote!((Vec<T>, Box<String>)), shared)
+            .unwrap_err();
+        assert_eq!(error.conflict.expect("a conflict").param, parse_quote!(U));
+        // A plain match takes the arguments as terms of the target, which are not unified
+        assert!(first.combine(second, &params).is_err());
+    }
+
+    #[test]
+    fn test_unification_detects_cycles_between_sides() {
+        let pattern_params = type_params(&["X"]);
+        let target_params = type_params(&["T"]);
+        let pattern: Type = parse_quote!(Pair<X, Vec<X>>);
+        let target: Type = parse_quote!(Pair<T, T>);
+        assert!(unify(&pattern, &target, &pattern_params, &target_params, false).is_err());
+        let pattern: Type = parse_quote!(Pair<Vec<X>, X>);
+        assert!(unify(&pattern, &target, &pattern_params, &target_params, false).is_err());
+    }
+
+    #[test]
+    fn test_unification_with_shared_param_names() {
+        let params = type_params(&["T", "S"]);
+        let pattern: Type = parse_quote!(Pair<T, S>);
+        let target: Type = parse_quote!(Pair<S, Node<T, S>>);
+        let (pattern_subs, target_subs) =
+            unify(&pattern, &target, &params, &HashSet::new(), false).unwrap();
+        assert!(pattern_subs.verify_replacement(&pattern, &target));
+        assert!(target_subs.is_empty());
+        let mut body: Type = parse_quote!((S, T));
+        body.replace(&pattern_subs);
+        assert_eq!(body, parse_quote!((Node<T, S>, S)));
+    }
+
+    #[test]
+    fn test_rule_params_do_not_bind_target_params() {
+        let params = type_params(&["T"]);
+        let pattern: Type = parse_quote!(Wrap<T, T>);
+        let target: Type = parse_quote!(Wrap<u16, T>);
+        assert!(pattern.matches(&target, &params).is_err());
+        assert!(unify(&pattern, &target, &params, &HashSet::new(), false).is_err());
+        let bare: Type = parse_quote!(Vec<u8>);
+        assert!(bare.matches(&parse_quote!(T), &params).is_err());
+        let constraint: Constraint = parse_quote!(Wrap<T, T>: Tr);
+        let target: Constraint = parse_quote!(Wrap<u16, T>: Tr);
+        assert!(constraint.matches(&target, &params).is_err());
+        assert!(unify(&constraint, &target, &params, &HashSet::new(), false).is_err());
+
+        // The target's `T` is a term the rule's `T` is bound to like any other
+        let (subs, _) = unify(
+            &constraint,
+            &parse_quote!(Wrap<T, T>: Tr),
+            &params,
+            &HashSet::new(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            subs.get(&parse_quote!(T)),
+            Some(&GenericArgument::Type(parse_quote!(T)))
+        );
+    }
+
+    #[test]
+    fn test_raw_identifiers_in_paths() {
+        let raw: Path = parse_quote!(r#match);
+        assert!(raw.matches(&parse_quote!(r#match), &HashSet::new()).is_ok());
+        // `match` is a keyword, so the plain identifier is only built by hand
+        let plain = Path::from(Ident::new("match", Span::call_site()));
+        assert!(raw.matches(&plain, &HashSet::new()).is_err());
+        assert!(plain.matches(&raw, &HashSet::new()).is_err());
+    }
+}
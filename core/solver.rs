@@ -0,0 +1,1531 @@
+use gotgraph::graph::{Graph, GraphUpdate};
+use gotgraph::prelude::VecGraph;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::visit_mut::{self, VisitMut};
+use syn::*;
+use template_quote::{quote, ToTokens};
+
+use crate::error;
+use crate::matching::{clean_param, unify, Matching, Substitute};
+
+/// A `Type: Trait` bound, which compares equal to the same bound spelled differently, see
+/// [`GroupStripper`]
+#[derive(Clone, Debug)]
+pub struct Constraint {
+    pub typ: Type,
+    pub trait_path: Path,
+    /// The `for<..>` binder of a higher-ranked bound, whose lifetimes are local to the constraint
+    pub lifetimes: Option<BoundLifetimes>,
+}
+
+/// Removes the invisible groups which `macro_rules!` wraps around `$t:ty` and `$e:expr`
+/// fragments, along with parentheses; the syntax tree already encodes precedence, so neither
+/// changes the meaning. The `::` of a turbofish `Vec::<T>`, the leading `::` of a path and
+/// trailing commas, as in `Vec<T,>`, are dropped as well. Only for comparison, as printing
+/// `&(dyn A + B)` without them does not parse.
+pub struct GroupStripper;
+
+/// Drops the trailing separator of `punctuated`, which syn's comparison does not ignore
+fn strip_trailing<T, P>(punctuated: &mut Punctuated<T, P>) {
+    if punctuated.trailing_punct() {
+        punctuated.pop_punct();
+    }
+}
+
+impl VisitMut for GroupStripper {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        while let Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. }) = ty {
+            *ty = core::mem::replace(elem.as_mut(), Type::Verbatim(Default::default()));
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        while let Expr::Group(ExprGroup { expr: inner, .. })
+        | Expr::Paren(ExprParen { expr: inner, .. }) = expr
+        {
+            *expr = core::mem::replace(inner.as_mut(), Expr::Verbatim(Default::default()));
+        }
+        visit_mut::visit_expr_mut(self, expr);
+    }
+
+    fn visit_path_mut(&mut self, path: &mut Path) {
+        path.leading_colon = None;
+        visit_mut::visit_path_mut(self, path);
+    }
+
+    fn visit_angle_bracketed_generic_arguments_mut(
+        &mut self,
+        args: &mut AngleBracketedGenericArguments,
+    ) {
+        args.colon2_token = None;
+        strip_trailing(&mut args.args);
+        visit_mut::visit_angle_bracketed_generic_arguments_mut(self, args);
+    }
+
+    fn visit_parenthesized_generic_arguments_mut(
+        &mut self,
+        args: &mut ParenthesizedGenericArguments,
+    ) {
+        strip_trailing(&mut args.inputs);
+        visit_mut::visit_parenthesized_generic_arguments_mut(self, args);
+    }
+
+    fn visit_type_tuple_mut(&mut self, tuple: &mut TypeTuple) {
+        // `(T,)` needs its comma to stay a tuple
+        if tuple.elems.len() > 1 {
+            strip_trailing(&mut tuple.elems);
+        }
+        visit_mut::visit_type_tuple_mut(self, tuple);
+    }
+
+    fn visit_type_bare_fn_mut(&mut self, bare_fn: &mut TypeBareFn) {
+        strip_trailing(&mut bare_fn.inputs);
+        visit_mut::visit_type_bare_fn_mut(self, bare_fn);
+    }
+
+    fn visit_type_trait_object_mut(&mut self, object: &mut TypeTraitObject) {
+        strip_trailing(&mut object.bounds);
+        visit_mut::visit_type_trait_object_mut(self, object);
+    }
+
+    fn visit_type_impl_trait_mut(&mut self, impl_trait: &mut TypeImplTrait) {
+        strip_trailing(&mut impl_trait.bounds);
+        visit_mut::visit_type_impl_trait_mut(self, impl_trait);
+    }
+}
+
+/// Replaces the `Self` type of an impl's where clause with the impl's self type
+struct SelfReplacer<'a>(&'a Type);
+
+impl VisitMut for SelfReplacer<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if matches!(ty, Type::Path(TypePath { qself: None, path }) if path.is_ident("Self")) {
+            *ty = self.0.clone();
+        } else {
+            visit_mut::visit_type_mut(self, ty);
+        }
+    }
+}
+
+impl Constraint {
+    /// A constraint `typ: trait_path` without a `for<..>` binder
+    pub fn new(typ: Type, trait_path: Path) -> Self {
+        Constraint {
+            typ,
+            trait_path,
+            lifetimes: None,
+        }
+    }
+
+    /// The same constraint on another trait, keeping the type and the binder
+    pub fn with_trait_path(self, trait_path: Path) -> Self {
+        Constraint { trait_path, ..self }
+    }
+
+    /// The same constraint on another type, keeping the trait and the binder
+    pub fn with_type(self, typ: Type) -> Self {
+        Constraint { typ, ..self }
+    }
+
+    /// Spells `Self` as `self_ty`, so that a bound such as `Box<Self>: Tr` in an impl's where
+    /// clause can be matched against the rewrite rules, which mention the concrete type
+    pub fn replace_self(&mut self, self_ty: &Type) {
+        let mut replacer = SelfReplacer(self_ty);
+        replacer.visit_type_mut(&mut self.typ);
+        replacer.visit_path_mut(&mut self.trait_path);
+    }
+
+    /// The constraint as compared and hashed; spans are ignored by syn's comparison, so the same
+    /// constraint reached through different relays is stored only once
+    fn structural(&self) -> Self {
+        let mut constraint = self.clone();
+        GroupStripper.visit_type_mut(&mut constraint.typ);
+        GroupStripper.visit_path_mut(&mut constraint.trait_path);
+        constraint
+    }
+
+    /// The tokens of the constraint as compared, without whitespace or spans, which
+    /// [`ConstraintIndex`] keys its nodes by; `Vec<T,>: Tr` and `Vec<(T)>: Tr` have the same key
+    /// as `Vec<T>: Tr`
+    pub fn canonical_key(&self) -> String {
+        let structural = self.structural();
+        quote!(#structural).to_string()
+    }
+}
+
+impl PartialEq for Constraint {
+    fn eq(&self, other: &Self) -> bool {
+        let (lhs, rhs) = (self.structural(), other.structural());
+        lhs.typ == rhs.typ && lhs.trait_path == rhs.trait_path && lhs.lifetimes == rhs.lifetimes
+    }
+}
+
+impl Eq for Constraint {}
+
+impl std::hash::Hash for Constraint {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let constraint = self.structural();
+        constraint.typ.hash(state);
+        constraint.trait_path.hash(state);
+        constraint.lifetimes.hash(state);
+    }
+}
+
+/// Finds the node holding `constraint` by a linear scan, the reference for [`ConstraintIndex`]
+#[cfg(test)]
+pub fn find_constraint<G: Graph<Node = Constraint>>(
+    graph: &G,
+    constraint: &Constraint,
+) -> Option<G::NodeIx> {
+    graph
+        .node_pairs()
+        .find(|(_, node)| *node == constraint)
+        .map(|(ix, _)| ix)
+}
+
+/// Looks nodes up by their constraint in constant time, for loops which add many constraints
+/// to a graph; every node has to be added through the index while it is in use. Nodes are keyed
+/// by [`Constraint::canonical_key`].
+pub struct ConstraintIndex<Ix>(HashMap<String, Ix>);
+
+impl<Ix: Copy> ConstraintIndex<Ix> {
+    /// Indexes the nodes `graph` already has; of duplicate nodes, the first one is found
+    pub fn new<G: Graph<Node = Constraint, NodeIx = Ix>>(graph: &G) -> Self {
+        let mut index = HashMap::new();
+        for (ix, node) in graph.node_pairs() {
+            index.entry(node.canonical_key()).or_insert(ix);
+        }
+        ConstraintIndex(index)
+    }
+
+    /// Finds the node holding `constraint`
+    pub fn find(&self, constraint: &Constraint) -> Option<Ix> {
+        self.0.get(&constraint.canonical_key()).copied()
+    }
+
+    /// Returns the node holding `constraint`, adding one only if the graph does not have it yet
+    pub fn add<G: GraphUpdate<Node = Constraint, NodeIx = Ix>>(
+        &mut self,
+        graph: &mut G,
+        constraint: Constraint,
+    ) -> Ix {
+        let key = constraint.canonical_key();
+        if let Some(ix) = self.0.get(&key) {
+            return *ix;
+        }
+        let ix = graph.add_node(constraint);
+        self.0.insert(key, ix);
+        ix
+    }
+}
+
+/// Adds an edge from `from` to `to` unless the graph already has one, so a dependency reached
+/// through several rules is followed only once
+pub fn add_dependency<G: GraphUpdate<Edge = ()>>(graph: &mut G, from: G::NodeIx, to: G::NodeIx) {
+    let exists = graph
+        .outgoing_edge_indices(from)
+        .any(|edge_ix| graph.endpoints(edge_ix)[1] == to);
+    if !exists {
+        graph.add_edge((), from, to);
+    }
+}
+
+impl Constraint {
+    pub fn map_where_clause(wc: &mut WhereClause, mut f: impl FnMut(Self) -> Vec<Self>) {
+        for mut pair in core::mem::take(&mut wc.predicates).into_pairs() {
+            match pair.value_mut() {
+                WherePredicate::Type(PredicateType {
+                    lifetimes,
+                    bounded_ty,
+                    bounds,
+                    ..
+                }) => {
+                    let additional_predicates =
+                        Self::map_bounds(bounds, bounded_ty, lifetimes.as_ref(), &mut f);
+                    let has_bounds = !bounds.is_empty();
+                    if has_bounds {
+                        wc.predicates.extend(core::iter::once(pair));
+                    }
+                    wc.predicates.extend(additional_predicates);
+                }
+                _ => wc.predicates.extend(core::iter::once(pair)),
+            }
+        }
+    }
+
+    fn map_bounds(
+        bounds: &mut Punctuated<TypeParamBound, Token![+]>,
+        bounded_ty: &Type,
+        lifetimes: Option<&BoundLifetimes>,
+        mut f: impl FnMut(Self) -> Vec<Self>,
+    ) -> Vec<WherePredicate> {
+        let mut additional_predicates = Vec::new();
+        for bound in core::mem::take(bounds).into_pairs() {
+            let punct = bound.punct().cloned();
+            match bound.into_value() {
+                // `?Sized` relaxes a default bound rather than adding an obligation, so keep it as is
+                bound @ TypeParamBound::Trait(TraitBound {
+                    modifier: TraitBoundModifier::Maybe(_),
+                    ..
+                }) => bounds.extend(core::iter::once(bound)),
+                // Marker traits are never coinductive, even if one was taken as a working trait
+                TypeParamBound::Trait(bound) if crate::is_marker_trait(&bound.path) => {
+                    bounds.extend(core::iter::once(TypeParamBound::Trait(bound)))
+                }
+                TypeParamBound::Trait(TraitBound {
+                    modifier,
+                    lifetimes: bound_lifetimes,
+                    path,
+                    ..
+                }) => {
+                    // Rust rejects binders on both the predicate and the bound, so at most one is set
+                    let constraint = Constraint {
+                        typ: bounded_ty.clone(),
+                        trait_path: path,
+                        lifetimes: bound_lifetimes.clone().or_else(|| lifetimes.cloned()),
+                    }
+                    .without_unused_binder();
+                    let binder = constraint.lifetimes.clone();
+                    for replacing in f(constraint) {
+                        if crate::unwrap_type_group(replacing.typ.clone())
+                            == crate::unwrap_type_group(bounded_ty.clone())
+                            && replacing.lifetimes == binder
+                        {
+                            bounds.push(TypeParamBound::Trait(TraitBound {
+                                paren_token: None,
+                                modifier,
+                                lifetimes: bound_lifetimes.clone(),
+                                path: replacing.trait_path,
+                            }));
+                            if let Some(punct) = punct {
+                                bounds.push_punct(punct);
+                            }
+                        } else {
+                            // The replacing constraint brings its own binder, if any
+                            additional_predicates.push(WherePredicate::Type(PredicateType {
+                                lifetimes: replacing.lifetimes,
+                                bounded_ty: replacing.typ,
+                                colon_token: Default::default(),
+                                bounds: core::iter::once(TypeParamBound::Trait(TraitBound {
+                                    paren_token: None,
+                                    modifier,
+                                    lifetimes: None,
+                                    path: replacing.trait_path,
+                                }))
+                                .collect(),
+                            }))
+                        }
+                    }
+                }
+                bound => bounds.extend(core::iter::once(bound)),
+            }
+        }
+        additional_predicates
+    }
+
+    pub fn map_generics(generics: &mut Generics, mut f: impl FnMut(Self) -> Vec<Self>) {
+        let mut additional_predicates = Vec::new();
+        for param in generics.params.iter_mut() {
+            additional_predicates.extend(Self::map_generic_param(param, &mut f));
+        }
+        if let Some(wc) = &mut generics.where_clause {
+            Self::map_where_clause(wc, &mut f);
+            wc.predicates.extend(additional_predicates);
+        } else if !additional_predicates.is_empty() {
+            generics.where_clause = Some(WhereClause {
+                where_token: Default::default(),
+                predicates: additional_predicates.into_iter().collect(),
+            });
+        }
+    }
+
+    pub fn map_generic_param(
+        param: &mut GenericParam,
+        f: impl FnMut(Self) -> Vec<Self>,
+    ) -> Vec<WherePredicate> {
+        match param {
+            GenericParam::Type(TypeParam { ident, bounds, .. }) => {
+                let bounded_ty = Type::Path(TypePath {
+                    qself: None,
+                    path: ident.clone().into(),
+                });
+                Self::map_bounds(bounds, &bounded_ty, None, f)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Drops the binder if none of its lifetimes are referenced, so that `for<'a> T: Trait` is
+    /// the same constraint as `T: Trait`
+    fn without_unused_binder(mut self) -> Self {
+        if let Some(binder) = &self.lifetimes {
+            let bound = binder.lifetimes.iter().cloned().collect();
+            if self.referenced_params(&bound).is_empty() {
+                self.lifetimes = None;
+            }
+        }
+        self
+    }
+
+    /// Cheaply checks whether `self` could match `other` under some substitution, by comparing
+    /// the last segment of the trait paths and the outermost constructor of the types. A `false`
+    /// result guarantees that matching fails; `true` only means it has to be attempted.
+    pub fn could_unify(&self, other: &Self) -> bool {
+        fn head_could_unify(lhs: &Type, rhs: &Type) -> bool {
+            match (lhs, rhs) {
+                (Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. }), _) => {
+                    head_could_unify(elem, rhs)
+                }
+                (_, Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. })) => {
+                    head_could_unify(lhs, elem)
+                }
+                // A bare identifier may be a generic parameter, which unifies with anything
+                (Type::Path(TypePath { qself: None, path }), _)
+                | (_, Type::Path(TypePath { qself: None, path }))
+                    if path.get_ident().is_some() =>
+                {
+                    true
+                }
+                (Type::Path(lhs_path), Type::Path(rhs_path)) => {
+                    lhs_path.qself.is_some() == rhs_path.qself.is_some()
+                        && lhs_path.path.segments.len() == rhs_path.path.segments.len()
+                        && lhs_path.path.segments.last().map(|seg| &seg.ident)
+                            == rhs_path.path.segments.last().map(|seg| &seg.ident)
+                }
+                (Type::Tuple(lhs_tuple), Type::Tuple(rhs_tuple)) => {
+                    lhs_tuple.elems.len() == rhs_tuple.elems.len()
+                }
+                (Type::Reference(lhs_ref), Type::Reference(rhs_ref)) => {
+                    lhs_ref.mutability.is_some() == rhs_ref.mutability.is_some()
+                }
+                (Type::BareFn(lhs_fn), Type::BareFn(rhs_fn)) => {
+                    lhs_fn.inputs.len() == rhs_fn.inputs.len()
+                }
+                _ => core::mem::discriminant(lhs) == core::mem::discriminant(rhs),
+            }
+        }
+
+        self.trait_path.segments.last().map(|seg| &seg.ident)
+            == other.trait_path.segments.last().map(|seg| &seg.ident)
+            && head_could_unify(&self.typ, &other.typ)
+    }
+
+    /// Returns the parameters among `candidates` which are mentioned in the type or the trait path
+    pub fn referenced_params(&self, candidates: &HashSet<GenericParam>) -> HashSet<GenericParam> {
+        #[derive(Default)]
+        struct Collector {
+            idents: HashSet<Ident>,
+            lifetimes: HashSet<Ident>,
+        }
+
+        impl<'ast> Visit<'ast> for Collector {
+            fn visit_path(&mut self, path: &'ast Path) {
+                // Both `T` and `T::Assoc` refer to the parameter `T`
+                if path.leading_colon.is_none() {
+                    if let Some(first) = path.segments.first() {
+                        self.idents.insert(first.ident.clone());
+                    }
+                }
+                visit::visit_path(self, path);
+            }
+
+            fn visit_lifetime(&mut self, lifetime: &'ast Lifetime) {
+                self.lifetimes.insert(lifetime.ident.clone());
+            }
+        }
+
+        let mut collector = Collector::default();
+        collector.visit_type(&self.typ);
+        collector.visit_path(&self.trait_path);
+        candidates
+            .iter()
+            .filter(|param| match param {
+                GenericParam::Type(TypeParam { ident, .. })
+                | GenericParam::Const(ConstParam { ident, .. }) => collector.idents.contains(ident),
+                GenericParam::Lifetime(LifetimeParam { lifetime, .. }) => {
+                    collector.lifetimes.contains(&lifetime.ident)
+                }
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ConstraintTuple {
+    from: Constraint,
+    to: Constraint,
+}
+
+impl Parse for Constraint {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lifetimes = input.parse::<Option<BoundLifetimes>>()?;
+        let typ = input.parse::<Type>()?;
+        if !input.peek(Token![:]) {
+            return Err(error::expected_constraint(input.span()));
+        }
+        input.parse::<Token![:]>()?;
+        // Parsed as a bound, as a plain path does not accept the `Fn(A) -> B` sugar
+        let trait_path = input.parse::<TraitBound>()?.path;
+        Ok(Constraint {
+            typ,
+            trait_path,
+            lifetimes,
+        })
+    }
+}
+
+impl ToTokens for Constraint {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let typ = &self.typ;
+        let trait_path = &self.trait_path;
+        let lifetimes = &self.lifetimes;
+        tokens.extend(quote! { #lifetimes #typ : #trait_path });
+    }
+}
+
+impl Parse for ConstraintTuple {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::parenthesized!(content in input);
+        let from = content.parse::<Constraint>()?;
+        content.parse::<Token![,]>()?;
+        let to = content.parse::<Constraint>()?;
+        Ok(ConstraintTuple { from, to })
+    }
+}
+
+/// The graph of constraints and the constraints each of them depends on, together with the
+/// generic params the constraints are stated over
+pub struct Solver {
+    pub graph: VecGraph<Constraint, ()>,
+    /// The params without their bounds, attributes and defaults, as they are looked up by name
+    pub generic_params: HashSet<GenericParam>,
+}
+
+impl Solver {
+    /// An empty solver over `generic_params`, which are stripped of their bounds, attributes and
+    /// defaults, so that `'a: 'static` or `T: Clone` is found as the param `'a` or `T`
+    pub fn new(generic_params: impl IntoIterator<Item = GenericParam>) -> Self {
+        Solver {
+            graph: Default::default(),
+            generic_params: generic_params.into_iter().map(clean_param).collect(),
+        }
+    }
+
+    /// Whether some constraints of the graph depend on each other, which is what
+    /// [`Solver::break_cycles`] replaces
+    pub fn has_cycle(&self) -> bool {
+        self.graph
+            .scope(|graph| gotgraph::algo::tarjan(graph).any(|component| component.len() > 1))
+    }
+
+    /// The strongly connected components of the graph in resolution order: a component comes
+    /// after every component it depends on, so leaves precede the cycles which reach them
+    pub fn condensation_order(&self) -> Vec<Vec<Constraint>> {
+        self.graph.scope(|graph| {
+            let components: Vec<_> = gotgraph::algo::tarjan(graph).collect();
+            let component_of: HashMap<_, _> = components
+                .iter()
+                .enumerate()
+                .flat_map(|(i, component)| component.iter().map(move |ix| (*ix, i)))
+                .collect();
+            // Kahn's algorithm on the condensation, following edges from dependencies
+            let mut dependents = vec![BTreeSet::new(); components.len()];
+            let mut pending = vec![0usize; components.len()];
+            // Tarjan assigns every node to a component, so an edge between unassigned nodes
+            // cannot occur; it is skipped rather than aborting the expansion
+            let edges = graph.edge_indices().filter_map(|edge_ix| {
+                let [from, to] = graph.endpoints(edge_ix);
+                Some((*component_of.get(&from)?, *component_of.get(&to)?))
+            });
+            for (from, to) in edges {
+                if from != to && dependents[to].insert(from) {
+                    pending[from] += 1;
+                }
+            }
+            let mut ready: VecDeque<_> =
+                (0..components.len()).filter(|i| pending[*i] == 0).collect();
+            let mut order = Vec::new();
+            while let Some(i) = ready.pop_front() {
+                order.push(
+                    components[i]
+                        .iter()
+                        .map(|ix| graph.node(*ix).clone())
+                        .collect(),
+                );
+                for &dependent in &dependents[i] {
+                    pending[dependent] -= 1;
+                    if pending[dependent] == 0 {
+                        ready.push_back(dependent);
+                    }
+                }
+            }
+            order
+        })
+    }
+
+    /// Renders the graph in Graphviz DOT as a digraph named `name`, with each strongly connected
+    /// component of more than one constraint, i.e. each cycle, drawn as a cluster
+    pub fn to_dot(&self, name: &str) -> String {
+        self.graph.scope(|graph| {
+            let ids: HashMap<_, _> = graph
+                .node_indices()
+                .enumerate()
+                .map(|(id, ix)| (ix, id))
+                .collect();
+            let label = |ix| {
+                let node = graph.node(ix);
+                quote!(#node)
+                    .to_string()
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+            };
+            let mut dot = format!("digraph {} {{\n", name);
+            for (id, ix) in graph.node_indices().enumerate() {
+                dot += &format!("    n{} [label=\"{}\"];\n", id, label(ix));
+            }
+            for edge_ix in graph.edge_indices() {
+                let [from, to] = graph.endpoints(edge_ix);
+                if let (Some(from), Some(to)) = (ids.get(&from), ids.get(&to)) {
+                    dot += &format!("    n{} -> n{};\n", from, to);
+                }
+            }
+            let cycles = gotgraph::algo::tarjan(graph).filter(|component| component.len() > 1);
+            for (i, component) in cycles.enumerate() {
+                dot += &format!("    subgraph cluster_{} {{\n        label=\"cycle\";\n", i);
+                for id in component.iter().filter_map(|ix| ids.get(ix)) {
+                    dot += &format!("        n{};\n", id);
+                }
+                dot += "    }\n";
+            }
+            dot + "}\n"
+        })
+    }
+
+    /// The renaming of the solver's params which turns `node` into `constraint`, whose params
+    /// are `params`: each of the solver's params is bound to a distinct one of `params`
+    fn renaming(
+        &self,
+        node: &Constraint,
+        constraint: &Constraint,
+        params: &HashSet<Ident>,
+    ) -> Option<Substitute> {
+        let substitute = node.matches(constraint, &self.generic_params).ok()?;
+        let param_ident = |arg: &GenericArgument| match arg {
+            GenericArgument::Lifetime(lifetime) => Some(lifetime.ident.clone()),
+            GenericArgument::Type(Type::Path(TypePath { qself: None, path })) => {
+                path.get_ident().cloned()
+            }
+            GenericArgument::Const(Expr::Path(ExprPath {
+                qself: None, path, ..
+            })) => path.get_ident().cloned(),
+            _ => None,
+        };
+        let mut targets = HashSet::new();
+        substitute
+            .values()
+            .all(|arg| {
+                param_ident(arg)
+                    .is_some_and(|ident| params.contains(&ident) && targets.insert(ident))
+            })
+            .then_some(substitute)
+    }
+
+    /// Replaces the bounds of `generics` which lie on a cycle of the graph by the dependencies
+    /// leaving that cycle; every such dependency is added once, however many bounds reach it
+    ///
+    /// A bound is looked up structurally first. Failing that, it is matched against the cycles'
+    /// members with the solver's params renamed to those of `generics`, which the graph may
+    /// spell differently, and the dependencies are renamed the same way.
+    pub fn break_cycles(&self, generics: &mut Generics, self_ty: &Type) {
+        let params: HashSet<Ident> = generics
+            .params
+            .iter()
+            .map(|param| match param {
+                GenericParam::Lifetime(param) => param.lifetime.ident.clone(),
+                GenericParam::Type(param) => param.ident.clone(),
+                GenericParam::Const(param) => param.ident.clone(),
+            })
+            .collect();
+        self.graph.scope(|graph| {
+            let loops = gotgraph::algo::tarjan(graph)
+                .filter_map(|lp| {
+                    (lp.len() > 1).then_some(
+                        lp.iter()
+                            .map(|ix| (graph.node(*ix), *ix))
+                            .collect::<HashMap<_, _>>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            // The dependencies leaving a cycle, which replace the bounds lying on it
+            let exits = |lp: &HashMap<&Constraint, _>| {
+                let members = lp.values().cloned().collect::<HashSet<_>>();
+                lp.values()
+                    .flat_map(|ix| {
+                        graph
+                            .outgoing_edge_indices(*ix)
+                            .map(|eix| graph.endpoints(eix)[1])
+                    })
+                    .filter(|ix| !members.contains(ix))
+                    .collect::<HashSet<_>>()
+            };
+            let mut added = HashSet::new();
+            Constraint::map_generics(generics, |constraint| {
+                // Bounds which stay are emitted as written, keeping their `Self` spelling
+                let mut resolved = constraint.clone();
+                resolved.replace_self(self_ty);
+                let found = loops
+                    .iter()
+                    .find(|lp| lp.contains_key(&resolved))
+                    .map(|lp| (lp, Substitute::new()))
+                    .or_else(|| {
+                        loops.iter().find_map(|lp| {
+                            let renaming = lp
+                                .keys()
+                                .find_map(|node| self.renaming(node, &resolved, &params))?;
+                            // A param of the solver left unbound by the renaming would leak
+                            // into the bounds, where it names nothing or another param
+                            exits(lp)
+                                .iter()
+                                .flat_map(|ix| {
+                                    graph.node(*ix).referenced_params(&self.generic_params)
+                                })
+                                .all(|param| renaming.get(&param).is_some())
+                                .then_some((lp, renaming))
+                        })
+                    });
+                if let Some((the_loop, renaming)) = found {
+                    exits(the_loop)
+                        .into_iter()
+                        .filter(|ix| added.insert(*ix))
+                        .map(|ix| {
+                            let mut dependency = graph.node(ix).clone();
+                            dependency.replace(&renaming);
+                            dependency
+                        })
+                        .collect()
+                } else {
+                    vec![constraint]
+                }
+            });
+        })
+    }
+
+    /// Debug-only invariant check: every edge endpoint must be a live node, and no
+    /// constraint may be stored twice, as nodes are looked up by their constraint.
+    pub fn debug_assert_consistent(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        for edge_ix in self.graph.edge_indices() {
+            let [from, to] = self.graph.endpoints(edge_ix);
+            debug_assert!(
+                self.graph.exists_node_index(from) && self.graph.exists_node_index(to),
+                "coinduction: dangling edge in solver graph"
+            );
+        }
+        let mut seen = HashSet::new();
+        for node in self.graph.nodes() {
+            debug_assert!(
+                seen.insert(node),
+                "coinduction: duplicate constraint `{}` in solver graph",
+                quote!(#node)
+            );
+        }
+    }
+}
+
+/// How many constraints the rules may expand before they are taken to run away
+pub const MAX_EXPANSIONS: usize = 1000;
+
+/// Reduces the bounds of the impl whose head matches `seed`, the way `#[coinduction]` does
+///
+/// Each rule is an impl: its generics, its head and its bounds. The where clause of the generics
+/// holds any other predicates, which are kept as they are. Starting from `seed`, every constraint
+/// is rewritten to the bounds of the first rule whose head matches it, and one which no head
+/// matches is a leaf of the graph. The generics of the seed's rule are returned with its bounds
+/// appended, where those which lie on a cycle are replaced by the dependencies leaving it, see
+/// [`Solver::break_cycles`]. If no head matches `seed`, the generics are empty.
+///
+/// # Panics
+///
+/// Panics if the rules expand more than [`MAX_EXPANSIONS`] constraints, as they do when a rule
+/// asks for ever larger types.
+pub fn solve(rules: Vec<(Generics, Constraint, Vec<Constraint>)>, seed: Constraint) -> Generics {
+    let rules: Vec<_> = rules
+        .into_iter()
+        .map(|(generics, head, bounds)| {
+            let params = head.referenced_params(&generics.params.iter().cloned().collect());
+            (generics, params, head, bounds)
+        })
+        .collect();
+    let expand = |constraint: &Constraint| {
+        rules.iter().find_map(|(generics, params, head, bounds)| {
+            // The rule's params are renamed apart, so those of the seed's impl stay rigid
+            let (substitute, _) = unify(head, constraint, params, &HashSet::new(), false).ok()?;
+            let bounds: Vec<_> = bounds
+                .iter()
+                .map(|bound| {
+                    let mut bound = bound.clone();
+                    bound.replace(&substitute);
+                    bound
+                })
+                .collect();
+            Some((generics, bounds))
+        })
+    };
+    let Some((generics, bounds)) = expand(&seed) else {
+        return Generics::default();
+    };
+    let mut solver = Solver::new(generics.params.iter().cloned());
+    solver.graph.scope_mut(|mut graph| {
+        let mut index = ConstraintIndex::new(&graph);
+        let mut working_list = VecDeque::from([index.add(&mut graph, seed.clone())]);
+        let mut expansions = 0;
+        while let Some(node_ix) = working_list.pop_front() {
+            expansions += 1;
+            let constraint = graph.node(node_ix).clone();
+            assert!(
+                expansions <= MAX_EXPANSIONS,
+                "coinduction: the rules expanded more than {} constraints at `{}`",
+                MAX_EXPANSIONS,
+                quote!(#constraint)
+            );
+            for dependency in expand(&constraint)
+                .map(|(_, bounds)| bounds)
+                .unwrap_or_default()
+            {
+                let dependency_ix = index.find(&dependency).unwrap_or_else(|| {
+                    let ix = index.add(&mut graph, dependency);
+                    working_list.push_back(ix);
+                    ix
+                });
+                add_dependency(&mut graph, node_ix, dependency_ix);
+            }
+        }
+    });
+    let mut generics = generics.clone();
+    generics.make_where_clause().predicates.extend(
+        bounds
+            .iter()
+            .map(|bound| -> WherePredicate { parse_quote!(#bound) }),
+    );
+    solver.break_cycles(&mut generics, &seed.typ);
+    generics
+}
+
+impl Parse for Solver {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Parse { [...], [...], [...] }
+        let content;
+        syn::braced!(content in input);
+        // Parse vertices
+        let vertices = crate::parse_list(&content, "vertices", Constraint::parse)?;
+        content.parse::<Token![,]>()?;
+        // Parse edges
+        let edge_tuples = crate::parse_list(&content, "edges", ConstraintTuple::parse)?;
+        content.parse::<Token![,]>()?;
+        // Parse generic_params
+        let generic_param_list =
+            crate::parse_list(&content, "generic parameters", GenericParam::parse)?;
+        let mut solver = Solver::new(generic_param_list);
+        let graph = &mut solver.graph;
+        // Add vertices
+        for vertex in &vertices {
+            graph.add_node(vertex.clone());
+        }
+        // Add edges
+        let mut index = ConstraintIndex::new(graph);
+        for edge_tuple in &edge_tuples {
+            let from_id = index.add(graph, edge_tuple.from.clone());
+            let to_id = index.add(graph, edge_tuple.to.clone());
+            add_dependency(graph, from_id, to_id);
+        }
+        solver.debug_assert_consistent();
+        Ok(solver)
+    }
+}
+
+impl ToTokens for Solver {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        // Only emit orphan vertices (vertices with no incoming or outgoing edges)
+        let orphan_vertices: Vec<_> = self
+            .graph
+            .node_pairs()
+            .filter(|(node_id, _)| {
+                self.graph.incoming_edge_indices(*node_id).count() == 0
+                    && self.graph.outgoing_edge_indices(*node_id).count() == 0
+            })
+            .map(|(_, vertex)| vertex)
+            .cloned()
+            .collect();
+
+        // Serialize edges as tuples of constraints
+        let mut edges = Vec::new();
+        for edge_idx in self.graph.edge_indices() {
+            let endpoints = self.graph.endpoints(edge_idx);
+            let [from_idx, to_idx] = endpoints;
+            let from_constraint = self.graph.node(from_idx);
+            let to_constraint = self.graph.node(to_idx);
+            edges.push((from_constraint.clone(), to_constraint.clone()));
+        }
+
+        let generic_params: Vec<_> = self.generic_params.iter().collect();
+
+        let edge_tokens: Vec<_> = edges
+            .iter()
+            .map(|(from, to)| {
+                quote! { (#from, #to) }
+            })
+            .collect();
+
+        tokens.extend(quote! {
+            {
+                [#(#orphan_vertices),*],
+                [#(#edge_tokens),*],
+                [#(#generic_params),*]
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referenced_params() {
+        let constraint: Constraint = parse_quote!(Vec<T>: From<U>);
+        let candidates: HashSet<GenericParam> = [
+            parse_quote!(T),
+            parse_quote!(U),
+            parse_quote!(V),
+            parse_quote!('a),
+        ]
+        .into_iter()
+        .collect();
+        let expected: HashSet<GenericParam> =
+            [parse_quote!(T), parse_quote!(U)].into_iter().collect();
+        assert_eq!(constraint.referenced_params(&candidates), expected);
+    }
+
+    #[test]
+    fn test_referenced_params_projection_and_lifetime() {
+        let constraint: Constraint = parse_quote!(&'a T::Item: Into<[u8; N]>);
+        let candidates: HashSet<GenericParam> = [
+            parse_quote!(T),
+            parse_quote!('a),
+            parse_quote!('b),
+            parse_quote!(const N: usize),
+        ]
+        .into_iter()
+        .collect();
+        let expected: HashSet<GenericParam> = [
+            parse_quote!(T),
+            parse_quote!('a),
+            parse_quote!(const N: usize),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(constraint.referenced_params(&candidates), expected);
+    }
+
+    #[test]
+    fn test_could_unify() {
+        let pattern: Constraint = parse_quote!(Vec<T>: A);
+        assert!(pattern.could_unify(&parse_quote!(Vec<String>: A)));
+        assert!(!pattern.could_unify(&parse_quote!(HashMap<T>: A)));
+        assert!(!pattern.could_unify(&parse_quote!(Vec<String>: B)));
+        assert!(!pattern.could_unify(&parse_quote!((String, u8): A)));
+    }
+
+    #[test]
+    fn test_could_unify_generic_head() {
+        let pattern: Constraint = parse_quote!(T: A);
+        assert!(pattern.could_unify(&parse_quote!(Vec<String>: A)));
+        assert!(pattern.could_unify(&parse_quote!((u8, u16): A)));
+        let tuple: Constraint = parse_quote!((T, U): A);
+        assert!(tuple.could_unify(&parse_quote!((u8, u16): A)));
+        assert!(!tuple.could_unify(&parse_quote!((u8, u16, u32): A)));
+    }
+
+    #[test]
+    fn test_maybe_bounds_are_not_mapped() {
+        let mut generics: Generics = parse_quote!(<T: ?Sized + Tr, U>);
+        generics.where_clause = Some(parse_quote!(where U: Tr + ?Sized));
+        let mut seen = Vec::new();
+        Constraint::map_generics(&mut generics, |c| {
+            seen.push(c.clone());
+            vec![c]
+        });
+        let expected: Vec<Constraint> = vec![parse_quote!(T: Tr), parse_quote!(U: Tr)];
+        assert_eq!(seen, expected);
+        let expected: Generics = parse_quote!(<T: ?Sized + Tr, U>);
+        assert_eq!(generics.params, expected.params);
+        let expected: WhereClause = parse_quote!(where U: Tr + ?Sized);
+        assert_eq!(generics.where_clause, Some(expected));
+    }
+
+    #[test]
+    fn test_higher_ranked_where_predicates_keep_binder() {
+        let mut wc: WhereClause = parse_quote!(where for<'a> Wrap<T>: Tr + Peek<'a>);
+        let mut seen = Vec::new();
+        Constraint::map_where_clause(&mut wc, |c| {
+            seen.push(c.clone());
+            if c.trait_path == parse_quote!(Tr) {
+                vec![parse_quote!(T: Tr)]
+            } else {
+                vec![c]
+            }
+        });
+        // The binder is only kept where its lifetimes are used
+        let expected: Vec<Constraint> = vec![
+            parse_quote!(Wrap<T>: Tr),
+            parse_quote!(for<'a> Wrap<T>: Peek<'a>),
+        ];
+        assert_eq!(seen, expected);
+        let expected: WhereClause = parse_quote!(where for<'a> Wrap<T>: Peek<'a>, T: Tr);
+        assert_eq!(wc, expected);
+    }
+
+    #[test]
+    fn test_higher_ranked_bounds_keep_binder() {
+        let mut generics: Generics = parse_quote!(<F: Tr + for<'a> Fn(&'a str), G>);
+        Constraint::map_generics(&mut generics, |c| {
+            if c.trait_path == parse_quote!(Tr) {
+                // A dependency carried over from another impl keeps the binder it was declared with
+                vec![parse_quote!(for<'b> G: Fn(&'b str))]
+            } else {
+                vec![c]
+            }
+        });
+        let expected: Generics = parse_quote!(<F: for<'a> Fn(&'a str), G>);
+        assert_eq!(generics.params, expected.params);
+        let expected: WhereClause = parse_quote!(where for<'b> G: Fn(&'b str));
+        assert_eq!(generics.where_clause, Some(expected));
+    }
+
+    #[test]
+    fn test_index_dedups_differently_spanned() {
+        use proc_macro2::Span;
+        let respanned = |span: Span| {
+            let typ = Ident::new("Node", span);
+            let trait_path = Ident::new("Tr", span);
+            Constraint::new(parse_quote!(Vec<#typ>), parse_quote!(#trait_path))
+        };
+        let mut graph: VecGraph<Constraint, ()> = VecGraph::default();
+        let mut index = ConstraintIndex::new(&graph);
+        let first = index.add(&mut graph, respanned(Span::call_site()));
+        let second = index.add(&mut graph, respanned(Span::mixed_site()));
+        assert_eq!(first, second);
+        assert_eq!(graph.len_nodes(), 1);
+        assert_eq!(
+            find_constraint(&graph, &parse_quote!(Vec<Node>: Tr)),
+            Some(first)
+        );
+        assert_eq!(
+            find_constraint(&graph, &parse_quote!(Vec<Node>: Other)),
+            None
+        );
+        // `$t:ty` fragments arrive wrapped in invisible groups
+        let grouped = Constraint::new(
+            Type::Group(TypeGroup {
+                group_token: Default::default(),
+                elem: Box::new(parse_quote!(Vec<Node>)),
+            }),
+            parse_quote!(Tr),
+        );
+        assert_eq!(index.add(&mut graph, grouped), first);
+        assert_eq!(graph.len_nodes(), 1);
+    }
+
+    #[test]
+    fn test_lifetime_predicates_are_kept() {
+        let mut generics: Generics = parse_quote!(<'a, 'b: 'a, T>);
+        generics.where_clause = Some(parse_quote!(where 'a: 'b, T: Tr, T: 'a));
+        Constraint::map_generics(&mut generics, |_| Vec::new());
+        let expected: Generics = parse_quote!(<'a, 'b: 'a, T>);
+        assert_eq!(generics.params, expected.params);
+        let expected: WhereClause = parse_quote!(where 'a: 'b, T: 'a);
+        assert_eq!(generics.where_clause, Some(expected));
+    }
+
+    #[test]
+    fn test_constraint_builders() {
+        let constraint = Constraint::new(parse_quote!(Vec<T>), parse_quote!(Tr));
+        assert_eq!(constraint, parse_quote!(Vec<T>: Tr));
+        assert!(constraint.lifetimes.is_none());
+        let constraint = constraint
+            .with_trait_path(parse_quote!(Other<u8>))
+            .with_type(parse_quote!(Box<T>));
+        assert_eq!(constraint, parse_quote!(Box<T>: Other<u8>));
+        // The binder belongs to the constraint and is kept by both builders
+        let higher_ranked: Constraint = parse_quote!(for<'a> &'a T: Tr);
+        let expected: Constraint = parse_quote!(for<'a> &'a U: Peek<'a>);
+        assert_eq!(
+            higher_ranked
+                .with_type(parse_quote!(&'a U))
+                .with_trait_path(parse_quote!(Peek<'a>)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_replace_self() {
+        let mut constraint: Constraint = parse_quote!(Vec<Box<Self>>: Into<Self>);
+        constraint.replace_self(&parse_quote!(Tree<T>));
+        assert_eq!(constraint, parse_quote!(Vec<Box<Tree<T>>>: Into<Tree<T>>));
+        // Only the `Self` type is replaced, not paths merely starting with it
+        let mut constraint: Constraint = parse_quote!(Self::Item: Tr);
+        constraint.replace_self(&parse_quote!(Tree<T>));
+        assert_eq!(constraint, parse_quote!(Self::Item: Tr));
+    }
+
+    #[test]
+    fn test_marker_bounds_are_kept() {
+        let mut generics: Generics = parse_quote!(<T: Tr + Sized, U: core::marker::Copy>);
+        generics.where_clause = Some(parse_quote!(where T: Send + Tr, U: Sized));
+        let mut seen = Vec::new();
+        Constraint::map_generics(&mut generics, |c| {
+            seen.push(c);
+            Vec::new()
+        });
+        assert_eq!(seen, vec![parse_quote!(T: Tr), parse_quote!(T: Tr)]);
+        let expected: Generics = parse_quote!(<T: Sized, U: core::marker::Copy>);
+        assert_eq!(generics.params, expected.params);
+        let expected: WhereClause = parse_quote!(where T: Send, U: Sized);
+        assert_eq!(generics.where_clause, Some(expected));
+    }
+
+    #[test]
+    fn test_condensation_order_puts_dependencies_first() {
+        let mut solver = Solver {
+            graph: Default::default(),
+            generic_params: HashSet::new(),
+        };
+        solver.graph.scope_mut(|mut graph| {
+            let expr = graph.add_node(parse_quote!(Expr: Tr));
+            let term = graph.add_node(parse_quote!(Term: Tr));
+            let lit = graph.add_node(parse_quote!(Lit: Tr));
+            let leaf = graph.add_node(parse_quote!(u8: Tr));
+            graph.add_edge((), expr, term);
+            graph.add_edge((), term, expr);
+            graph.add_edge((), term, lit);
+            graph.add_edge((), lit, leaf);
+        });
+        let order = solver.condensation_order();
+        assert_eq!(order.len(), 3);
+        assert_eq!(order[0], vec![parse_quote!(u8: Tr)]);
+        assert_eq!(order[1], vec![parse_quote!(Lit: Tr)]);
+        let cycle: HashSet<Constraint> = order[2].iter().cloned().collect();
+        let expected = [parse_quote!(Expr: Tr), parse_quote!(Term: Tr)];
+        assert_eq!(cycle, expected.into_iter().collect());
+    }
+
+    #[test]
+    fn test_diamond_dependency_is_added_once() {
+        let mut solver = Solver {
+            graph: Default::default(),
+            generic_params: HashSet::new(),
+        };
+        solver.graph.scope_mut(|mut graph| {
+            let top = graph.add_node(parse_quote!(Top: Show));
+            let left = graph.add_node(parse_quote!(Left: Show));
+            let right = graph.add_node(parse_quote!(Right: Show));
+            let bottom = graph.add_node(parse_quote!(Bottom: Show));
+            for (from, to) in [
+                (top, left),
+                (top, right),
+                (left, top),
+                (right, top),
+                (left, bottom),
+                (right, bottom),
+                (left, bottom),
+            ] {
+                add_dependency(&mut graph, from, to);
+            }
+        });
+        assert_eq!(solver.graph.len_edges(), 6);
+
+        let mut generics: Generics = parse_quote!(<>);
+        generics.where_clause = Some(parse_quote!(where Left: Show, Right: Show));
+        solver.break_cycles(&mut generics, &parse_quote!(Top));
+        let expected: WhereClause = parse_quote!(where Bottom: Show);
+        assert_eq!(generics.where_clause, Some(expected));
+    }
+
+    #[test]
+    fn test_cycle_bounds_with_renamed_params() {
+        let mut solver = Solver {
+            graph: Default::default(),
+            generic_params: [parse_quote!(T0), parse_quote!('a0)].into_iter().collect(),
+        };
+        solver.graph.scope_mut(|mut graph| {
+            let node = graph.add_node(parse_quote!(Node<'a0, T0>: Tr));
+            let leaf = graph.add_node(parse_quote!(Leaf<'a0, T0>: Tr));
+            let value = graph.add_node(parse_quote!(Value<T0>: Tr));
+            add_dependency(&mut graph, node, leaf);
+            add_dependency(&mut graph, leaf, node);
+            add_dependency(&mut graph, leaf, value);
+        });
+
+        assert!(solver.has_cycle());
+
+        // The impl spells the params differently from the graph, so no member equals the bound
+        let mut generics: Generics = parse_quote!(<'a, T>);
+        generics.where_clause = Some(parse_quote!(where Leaf<'a, T>: Tr, T: Clone));
+        solver.break_cycles(&mut generics, &parse_quote!(Node<'a, T>));
+        let expected: WhereClause = parse_quote!(where Value<T>: Tr, T: Clone);
+        assert_eq!(generics.where_clause, Some(expected));
+
+        // A bound which only matches by binding a param to a concrete type is kept
+        let mut generics: Generics = parse_quote!(<'a>);
+        generics.where_clause = Some(parse_quote!(where Leaf<'a, u8>: Tr));
+        solver.break_cycles(&mut generics, &parse_quote!(Node<'a, u8>));
+        let expected: WhereClause = parse_quote!(where Leaf<'a, u8>: Tr);
+        assert_eq!(generics.where_clause, Some(expected));
+
+        // As is one which binds two of the solver's params to the same one
+        let mut solver = Solver {
+            graph: Default::default(),
+            generic_params: [parse_quote!(T0), parse_quote!(U0)].into_iter().collect(),
+        };
+        solver.graph.scope_mut(|mut graph| {
+            let pair = graph.add_node(parse_quote!(Pair<T0, U0>: Tr));
+            let swap = graph.add_node(parse_quote!(Pair<U0, T0>: Tr));
+            add_dependency(&mut graph, pair, swap);
+            add_dependency(&mut graph, swap, pair);
+        });
+        let mut generics: Generics = parse_quote!(<T>);
+        generics.where_clause = Some(parse_quote!(where Pair<T, T>: Tr));
+        solver.break_cycles(&mut generics, &parse_quote!(Single<T>));
+        let expected: WhereClause = parse_quote!(where Pair<T, T>: Tr);
+        assert_eq!(generics.where_clause, Some(expected));
+    }
+
+    #[test]
+    fn test_renaming_binds_the_params_of_the_dependencies() {
+        let mut solver = Solver {
+            graph: Default::default(),
+            generic_params: [parse_quote!(T0), parse_quote!(U0)].into_iter().collect(),
+        };
+        solver.graph.scope_mut(|mut graph| {
+            let node = graph.add_node(parse_quote!(Node<T0>: Tr));
+            let leaf = graph.add_node(parse_quote!(Leaf<T0>: Tr));
+            let value = graph.add_node(parse_quote!(Value<U0>: Tr));
+            add_dependency(&mut graph, node, leaf);
+            add_dependency(&mut graph, leaf, node);
+            add_dependency(&mut graph, leaf, value);
+        });
+
+        // Renaming `Leaf<T0>` binds `T0` alone, so `U0` would leak into the impl's bounds
+        let mut generics: Generics = parse_quote!(<T>);
+        generics.where_clause = Some(parse_quote!(where Leaf<T>: Tr));
+        solver.break_cycles(&mut generics, &parse_quote!(Node<T>));
+        let expected: WhereClause = parse_quote!(where Leaf<T>: Tr);
+        assert_eq!(generics.where_clause, Some(expected));
+    }
+
+    #[test]
+    fn test_solve() {
+        // The expression grammar of the README: `Expr` and `Term` need each other
+        let rules = vec![
+            (
+                parse_quote!(<>),
+                parse_quote!(Expr: Eval),
+                vec![parse_quote!(Term: Eval)],
+            ),
+            (
+                parse_quote!(<>),
+                parse_quote!(Term: Eval),
+                vec![parse_quote!(Expr: Eval), parse_quote!(i32: Eval)],
+            ),
+        ];
+        let generics = crate::solve(rules.clone(), parse_quote!(Expr: Eval));
+        let expected: WhereClause = parse_quote!(where i32: Eval);
+        assert_eq!(generics.where_clause, Some(expected));
+
+        // A seed which no head matches has nothing to reduce
+        let generics = crate::solve(rules, parse_quote!(Stmt: Eval));
+        assert_eq!(generics, Generics::default());
+
+        // The params of the other rules are renamed apart from those of the seed's rule, and the
+        // predicates written in the where clause are kept
+        let mut generics: Generics = parse_quote!(<'a, T>);
+        generics.where_clause = Some(parse_quote!(where T: 'a));
+        let rules = vec![
+            (
+                generics,
+                parse_quote!(Tree<'a, T>: Show),
+                vec![parse_quote!(Forest<'a, T>: Show)],
+            ),
+            (
+                parse_quote!(<'b, U>),
+                parse_quote!(Forest<'b, U>: Show),
+                vec![parse_quote!(Tree<'b, U>: Show), parse_quote!(U: Show)],
+            ),
+        ];
+        let generics = crate::solve(rules, parse_quote!(Tree<'a, T>: Show));
+        let expected: Generics = parse_quote!(<'a, T>);
+        assert_eq!(generics.params, expected.params);
+        let expected: WhereClause = parse_quote!(where T: 'a, T: Show);
+        assert_eq!(generics.where_clause, Some(expected));
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        let solver = Solver {
+            graph: Default::default(),
+            generic_params: HashSet::new(),
+        };
+        solver.debug_assert_consistent();
+        assert!(!solver.has_cycle());
+        assert!(solver.condensation_order().is_empty());
+        assert_eq!(solver.to_dot("empty"), "digraph empty {\n}\n");
+
+        let mut generics: Generics = parse_quote!(<T: Tr>);
+        solver.break_cycles(&mut generics, &parse_quote!(Node<T>));
+        let expected: Generics = parse_quote!(<T: Tr>);
+        assert_eq!(generics.params, expected.params);
+
+        let reparsed: Solver = syn::parse2(quote!(#solver)).unwrap();
+        assert_eq!(reparsed.graph.len_nodes(), 0);
+        assert_eq!(reparsed.graph.len_edges(), 0);
+    }
+
+    #[test]
+    fn test_single_isolated_node() {
+        let mut solver = Solver {
+            graph: Default::default(),
+            generic_params: HashSet::new(),
+        };
+        solver.graph.scope_mut(|mut graph| {
+            graph.add_node(parse_quote!(Leaf: Tr));
+        });
+        solver.debug_assert_consistent();
+        assert_eq!(
+            solver.condensation_order(),
+            vec![vec![parse_quote!(Leaf: Tr)]]
+        );
+        let dot = solver.to_dot("single");
+        assert!(dot.contains("n0 [label=\"Leaf : Tr\"];"), "{}", dot);
+        assert!(!dot.contains("->") && !dot.contains("cluster"), "{}", dot);
+
+        // A lone node is no cycle, so the bound on it stays
+        assert!(!solver.has_cycle());
+        let mut generics: Generics = parse_quote!(<>);
+        generics.where_clause = Some(parse_quote!(where Leaf: Tr));
+        solver.break_cycles(&mut generics, &parse_quote!(Leaf));
+        let expected: WhereClause = parse_quote!(where Leaf: Tr);
+        assert_eq!(generics.where_clause, Some(expected));
+
+        // It is relayed as an orphan vertex
+        let reparsed: Solver = syn::parse2(quote!(#solver)).unwrap();
+        assert_eq!(reparsed.graph.len_nodes(), 1);
+        assert_eq!(reparsed.graph.len_edges(), 0);
+        assert_eq!(
+            find_constraint(&reparsed.graph, &parse_quote!(Leaf: Tr)),
+            reparsed.graph.node_indices().next()
+        );
+    }
+
+    #[test]
+    fn test_constraint_index_on_many_constraints() {
+        const COUNT: usize = 2000;
+        let constraint = |i: usize| -> Constraint {
+            let ident = Ident::new(&format!("Node{}", i), proc_macro2::Span::call_site());
+            parse_quote!(Vec<#ident>: Tr)
+        };
+        let mut graph: VecGraph<Constraint, ()> = VecGraph::default();
+        let root = graph.add_node(parse_quote!(Root: Tr));
+        let mut index = ConstraintIndex::new(&graph);
+        assert_eq!(index.find(&parse_quote!(Root: Tr)), Some(root));
+        let nodes: Vec<_> = (0..COUNT)
+            .map(|i| index.add(&mut graph, constraint(i)))
+            .collect();
+        // Adding again, even through an invisible group, finds the existing nodes
+        for (i, ix) in nodes.iter().enumerate() {
+            let grouped = Constraint::new(
+                Type::Group(TypeGroup {
+                    group_token: Default::default(),
+                    elem: Box::new(constraint(i).typ),
+                }),
+                parse_quote!(Tr),
+            );
+            assert_eq!(index.add(&mut graph, grouped), *ix);
+        }
+        assert_eq!(graph.len_nodes(), COUNT + 1);
+        for i in (0..COUNT).step_by(97) {
+            assert_eq!(index.find(&constraint(i)), Some(nodes[i]));
+            assert_eq!(find_constraint(&graph, &constraint(i)), Some(nodes[i]));
+        }
+        assert_eq!(index.find(&parse_quote!(Vec<Node0>: Other)), None);
+    }
+
+    #[test]
+    fn test_assoc_const_binding_survives() {
+        // Relayed constraints are printed and parsed again
+        let constraint: Constraint = parse_quote!(Node<T>: Tr<N = 4>);
+        let reparsed: Constraint = syn::parse2(quote!(#constraint)).unwrap();
+        assert_eq!(reparsed, constraint);
+        assert!(matches!(
+            &reparsed.trait_path.segments[0].arguments,
+            PathArguments::AngleBracketed(args)
+                if matches!(args.args[0], GenericArgument::AssocConst(_))
+        ));
+
+        let mut generics: Generics = parse_quote!(<T>);
+        generics.where_clause = Some(parse_quote!(where Node<T>: Tr<N = 4>, T: Tr<N = { 2 + 2 }>));
+        let mut seen = Vec::new();
+        Constraint::map_generics(&mut generics, |c| {
+            seen.push(c.clone());
+            vec![c]
+        });
+        assert_eq!(
+            seen,
+            vec![
+                parse_quote!(Node<T>: Tr<N = 4>),
+                parse_quote!(T: Tr<N = { 2 + 2 }>)
+            ]
+        );
+        let expected: WhereClause = parse_quote!(where Node<T>: Tr<N = 4>, T: Tr<N = { 2 + 2 }>);
+        assert_eq!(generics.where_clause, Some(expected));
+    }
+
+    #[test]
+    fn test_groups_and_parens_are_ignored() {
+        let group = |ty: Type| {
+            Type::Group(TypeGroup {
+                group_token: Default::default(),
+                elem: Box::new(ty),
+            })
+        };
+        let plain: Constraint = parse_quote!(Vec<[u8; 3]>: Tr);
+        let inner = group(parse_quote!([u8; 3]));
+        // `[$t; $n]` with both fragments grouped
+        let array = Type::Array(TypeArray {
+            bracket_token: Default::default(),
+            elem: Box::new(group(parse_quote!(u8))),
+            semi_token: Default::default(),
+            len: Expr::Group(ExprGroup {
+                attrs: vec![],
+                group_token: Default::default(),
+                expr: Box::new(parse_quote!(3)),
+            }),
+        });
+        let variants = [
+            Constraint::new(group(parse_quote!(Vec<[u8; 3]>)), parse_quote!(Tr)),
+            Constraint::new(parse_quote!((Vec<[u8; 3]>)), parse_quote!(Tr)),
+            Constraint::new(parse_quote!(Vec<#inner>), parse_quote!(Tr)),
+            Constraint::new(parse_quote!(Vec<[(u8); (3)]>), parse_quote!(Tr)),
+            Constraint::new(
+                Type::Path(TypePath {
+                    qself: None,
+                    path: parse_quote!(Vec<#array>),
+                }),
+                parse_quote!(Tr),
+            ),
+        ];
+        let mut graph: VecGraph<Constraint, ()> = VecGraph::default();
+        let mut index = ConstraintIndex::new(&graph);
+        let ix = index.add(&mut graph, plain.clone());
+        for variant in variants {
+            assert_eq!(variant, plain);
+            assert_eq!(index.add(&mut graph, variant.clone()), ix);
+            assert_eq!(find_constraint(&graph, &variant), Some(ix));
+        }
+        assert_eq!(graph.len_nodes(), 1);
+        // The nodes keep their spelling, which may need the parentheses
+        let kept: Constraint = parse_quote!(&(dyn Tr + Send): Tr);
+        let ix = index.add(&mut graph, kept.clone());
+        assert_eq!(
+            quote!(#{graph.node(ix)}).to_string(),
+            quote!(#kept).to_string()
+        );
+    }
+
+    #[test]
+    fn test_leading_colon_is_ignored() {
+        let plain: Constraint = parse_quote!(std::vec::Vec<u8>: core::fmt::Debug);
+        let absolute: Constraint = parse_quote!(::std::vec::Vec<u8>: ::core::fmt::Debug);
+        assert_eq!(absolute, plain);
+        let mut graph: VecGraph<Constraint, ()> = VecGraph::default();
+        let mut index = ConstraintIndex::new(&graph);
+        let ix = index.add(&mut graph, absolute.clone());
+        assert_eq!(index.add(&mut graph, plain), ix);
+        assert_eq!(graph.len_nodes(), 1);
+        // The node keeps the spelling it was added with
+        assert_eq!(
+            quote!(#{graph.node(ix)}).to_string(),
+            quote!(#absolute).to_string()
+        );
+    }
+
+    #[test]
+    fn test_canonical_key() {
+        let plain: Constraint = parse_quote!(Vec<(u8, u16)>: Tr<u8>);
+        let variants: [Constraint; 4] = [
+            parse_quote!(Vec<(u8, u16,),>: Tr<u8,>),
+            parse_quote!(Vec<((u8, u16))>: Tr<(u8)>),
+            parse_quote!(::Vec::<(u8,u16)>: Tr<u8>),
+            parse_quote!(Vec  <  (u8 , u16)  >  :  Tr  <  u8  >),
+        ];
+        let mut graph: VecGraph<Constraint, ()> = VecGraph::default();
+        let mut index = ConstraintIndex::new(&graph);
+        let ix = index.add(&mut graph, plain.clone());
+        for variant in variants {
+            assert_eq!(variant.canonical_key(), plain.canonical_key());
+            assert_eq!(variant, plain);
+            assert_eq!(index.add(&mut graph, variant), ix);
+        }
+        assert_eq!(graph.len_nodes(), 1);
+
+        // A one-tuple keeps its comma, and different constraints keep different keys
+        let one: Constraint = parse_quote!((u8,): Tr);
+        assert_ne!(one, parse_quote!((u8): Tr));
+        assert_ne!(
+            plain.canonical_key(),
+            Constraint::new(parse_quote!(Vec<(u16, u8)>), parse_quote!(Tr<u8>)).canonical_key()
+        );
+        let working_list: VecDeque<Constraint> = [parse_quote!(Vec<u8>: Tr)].into();
+        assert!(working_list.contains(&parse_quote!(Vec<u8,>: Tr)));
+    }
+
+    #[test]
+    fn test_bounded_params_are_substituted() {
+        let mut solver = Solver::new([
+            parse_quote!('a0: 'static),
+            parse_quote!(T0: Clone + Default),
+        ]);
+        assert_eq!(
+            solver.generic_params,
+            [parse_quote!('a0), parse_quote!(T0)].into_iter().collect()
+        );
+        solver.graph.scope_mut(|mut graph| {
+            let node = graph.add_node(parse_quote!(Node<'a0, T0>: Tr));
+            let leaf = graph.add_node(parse_quote!(Leaf<'a0, T0>: Tr));
+            let value = graph.add_node(parse_quote!(Value<'a0, T0>: Tr));
+            add_dependency(&mut graph, node, leaf);
+            add_dependency(&mut graph, leaf, node);
+            add_dependency(&mut graph, leaf, value);
+        });
+
+        // Both params are renamed, though they are declared with bounds
+        let mut generics: Generics = parse_quote!(<'a: 'static, T: Clone>);
+        generics.where_clause = Some(parse_quote!(where Leaf<'a, T>: Tr));
+        solver.break_cycles(&mut generics, &parse_quote!(Node<'a, T>));
+        let expected: WhereClause = parse_quote!(where Value<'a, T>: Tr);
+        assert_eq!(generics.where_clause, Some(expected));
+
+        // The params are relayed without their bounds as well
+        let reparsed: Solver = syn::parse2(quote!(#solver)).unwrap();
+        assert_eq!(reparsed.generic_params, solver.generic_params);
+        let relayed: Solver = syn::parse_quote!({ [], [], ['a0: 'static, T0: Clone] });
+        assert_eq!(relayed.generic_params, solver.generic_params);
+    }
+}
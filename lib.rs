@@ -1,21 +1,66 @@
-/// Attribute macro for marking trait definitions that will support coinduction.
+/// Registers a trait with the relay, so that [`coinduction`] modules can resolve its bounds.
 ///
-/// This is currently an empty attribute macro that serves as a placeholder for future
-/// implementation of coinductive trait definition functionality.
+/// The trait is kept as it is, and a hidden macro of the same name is generated next to it. During
+/// the relay, this macro hands a constraint on the trait over to the macro named after the
+/// constrained type, such as one generated by [`typedef`].
+///
+/// Rules for types without such a macro are written as `(pattern) => { constraints }`: a
+/// constraint whose type matches the pattern is rewritten to the constraints.
 pub use coinduction_macro::traitdef;
 
-/// Attribute macro for marking type definitions involved in circular references.
+/// Registers the types of a module with the relay, so that [`coinduction`] modules can look
+/// through the impls in it.
 ///
-/// This is currently an empty attribute macro that serves as a placeholder for future
-/// implementation of circular type reference functionality.
+/// The module is kept as it is, and a relay macro is generated for each type the module
+/// implements the listed traits for, or all of its traits when none are listed, which rewrites
+/// constraints on the type into the bounds of those impls. Bounds mentioning types by relative
+/// paths are carried through the type given as `marker = <path>`, and `coinduction = <path>`,
+/// given first, is the path of this crate when it is imported under another name.
 pub use coinduction_macro::typedef;
 
-/// Attribute macro for enabling coinductive reasoning on specific items.
+/// Resolves the cyclic trait bounds between the impls of a module.
+///
+/// Every bound of an impl is expanded through the impls of the module, and through the
+/// [`traitdef`] and [`typedef`] macros for traits and types defined elsewhere. The bounds which
+/// lie on a cycle are removed, as the cycle is satisfied coinductively; all others are kept, so
+/// the impls still require what they need from outside the cycle.
+///
+/// The arguments are the paths of the traits to resolve, which default to the traits the module
+/// implements, together with any of these flags:
+///
+/// - `coinduction = <path>`, given first: the path of this crate when it is imported under
+///   another name.
+/// - `stats`: emits a hidden `__COINDUCTION_STATS` constant counting the work of the expansion.
+/// - `show_order`: emits a hidden `__COINDUCTION_ORDER` constant listing the components of the
+///   constraint graphs in the order they are resolved.
+/// - `dump_graph`: emits a hidden `__COINDUCTION_GRAPH` constant holding the constraint graphs as
+///   Graphviz DOT.
+/// - `dump_relay`: reports each trait macro the expansion relays to as a warning note, which is
+///   printed on nightly only.
+/// - `inherent`: also resolves the bounds of inherent impls which mention the traits.
+/// - `deep_args`: looks through foreign types, such as `Vec<T>`, to the module types among their
+///   arguments.
+/// - `loose_paths`: lets a bare trait name stand for a qualified path ending in it.
+/// - `allow_trivial`: silences the warning for a module in which no impl lies on a cycle.
+/// - `facts(Type: Trait, ..)`: constraints which are taken as satisfied and never relayed.
 ///
-/// This is currently an empty attribute macro that serves as a placeholder for future
-/// implementation of coinductive reasoning functionality.
+/// Naming a trait which no impl of the module implements is an error.
 pub use coinduction_macro::coinduction;
 
+/// Alias of [`coinduction`] for code bases preferring an adjective spelling.
+///
+/// Expands exactly like `#[coinduction]`; the relay still resolves its helpers through the
+/// facade crate path, so pass `coinduction = <path>` when the crate is imported under another name.
+pub use coinduction_macro::coinduction as coinductive;
+
+/// Attribute macro checking that each listed trait is registered with [`traitdef`].
+///
+/// The item it is put on is left unchanged. A trait without `#[traitdef]` has no relay macro,
+/// so naming it fails to compile, pointing at the trait in the attribute; a trait registered by
+/// another release of this crate is reported as a version mismatch. `coinduction = <path>`, given
+/// first, is the path of this crate when it is imported under another name.
+pub use coinduction_macro::check_traits;
+
 #[doc(hidden)]
 pub use coinduction_macro::__next_step;
 
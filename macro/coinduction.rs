@@ -1,32 +1,903 @@
 use gotgraph::prelude::*;
 use proc_macro2::TokenStream;
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
+use syn::visit_mut::{self, VisitMut};
 use syn::*;
 use template_quote::quote;
 
-use crate::matching::Matching;
-use crate::next_step::{next_step, NextStepArgs, NextStepKind};
-use crate::solver::{Constraint, Solver};
-use crate::{remove_path_args, NoArgPath};
+use crate::matching::{rename_apart, MatchContext, Matching, Substitute};
+use crate::next_step::{
+    abort_if_unsupported, abort_runaway_expansion, apply_rewrite_rules, next_step, NextStepArgs,
+    NextStepKind, Stats,
+};
+use crate::solver::{add_dependency, Constraint, ConstraintIndex, Solver, MAX_EXPANSIONS};
+use crate::{names_trait, remove_path_args, NoArgPath};
+
+mod kw {
+    syn::custom_keyword!(stats);
+    syn::custom_keyword!(dump_relay);
+    syn::custom_keyword!(show_order);
+    syn::custom_keyword!(dump_graph);
+    syn::custom_keyword!(facts);
+    syn::custom_keyword!(inherent);
+    syn::custom_keyword!(deep_args);
+    syn::custom_keyword!(loose_paths);
+    syn::custom_keyword!(allow_trivial);
+}
 
 pub struct CoinductionArgs {
     pub paths: Punctuated<NoArgPath, Token![,]>,
     pub coinduction: NoArgPath,
+    pub stats: bool,
+    pub dump_relay: bool,
+    pub show_order: bool,
+    pub dump_graph: bool,
+    /// Whether inherent impls whose bounds mention the working traits are resolved as well
+    pub inherent: bool,
+    /// Whether foreign types are looked through to the module types among their arguments
+    pub deep_args: bool,
+    /// Whether a single-segment path matches a qualified one ending in the same segment
+    pub loose_paths: bool,
+    /// Whether a module none of whose impls lie on a cycle is expanded without a warning
+    pub allow_trivial: bool,
+    pub facts: Vec<Constraint>,
 }
 
 impl Parse for CoinductionArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let coinduction = crate::try_parse_coinduction_args(input)?;
-        let paths: Punctuated<NoArgPath, Token![,]> =
-            input.parse_terminated(NoArgPath::parse, Token![,])?;
-        Ok(CoinductionArgs { paths, coinduction })
+        let mut stats = false;
+        let mut dump_relay = false;
+        let mut show_order = false;
+        let mut dump_graph = false;
+        let mut inherent = false;
+        let mut deep_args = false;
+        let mut loose_paths = false;
+        let mut allow_trivial = false;
+        let mut facts = Vec::new();
+        let mut paths = Punctuated::new();
+
+        while !input.is_empty() {
+            // Check for flags, which are not followed by a path separator
+            if input.peek(kw::stats) && !input.peek2(Token![::]) {
+                input.parse::<kw::stats>()?;
+                stats = true;
+            } else if input.peek(kw::dump_relay) && !input.peek2(Token![::]) {
+                input.parse::<kw::dump_relay>()?;
+                dump_relay = true;
+            } else if input.peek(kw::show_order) && !input.peek2(Token![::]) {
+                input.parse::<kw::show_order>()?;
+                show_order = true;
+            } else if input.peek(kw::dump_graph) && !input.peek2(Token![::]) {
+                input.parse::<kw::dump_graph>()?;
+                dump_graph = true;
+            } else if input.peek(kw::inherent) && !input.peek2(Token![::]) {
+                input.parse::<kw::inherent>()?;
+                inherent = true;
+            } else if input.peek(kw::deep_args) && !input.peek2(Token![::]) {
+                input.parse::<kw::deep_args>()?;
+                deep_args = true;
+            } else if input.peek(kw::loose_paths) && !input.peek2(Token![::]) {
+                input.parse::<kw::loose_paths>()?;
+                loose_paths = true;
+            } else if input.peek(kw::allow_trivial) && !input.peek2(Token![::]) {
+                input.parse::<kw::allow_trivial>()?;
+                allow_trivial = true;
+            } else if input.peek(kw::facts) && input.peek2(token::Paren) {
+                input.parse::<kw::facts>()?;
+                let content;
+                parenthesized!(content in input);
+                facts.extend(content.parse_terminated(Constraint::parse, Token![,])?);
+            } else {
+                paths.push(input.parse::<NoArgPath>()?);
+            }
+
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            } else {
+                break;
+            }
+        }
+        if !input.is_empty() {
+            return Err(input.error("Expected comma"));
+        }
+
+        Ok(CoinductionArgs {
+            paths,
+            coinduction,
+            stats,
+            dump_relay,
+            show_order,
+            dump_graph,
+            inherent,
+            deep_args,
+            loose_paths,
+            allow_trivial,
+            facts,
+        })
+    }
+}
+
+/// The trait paths `paths` read from inside a module nested in the one they are written for; a
+/// bare `Tr` is kept as well, as the nested module may import it
+fn nested_paths<'a>(
+    paths: impl IntoIterator<Item = &'a NoArgPath>,
+) -> punctuated::Punctuated<NoArgPath, Token![,]> {
+    paths
+        .into_iter()
+        .flat_map(|NoArgPath(path)| {
+            let first = &path.segments[0].ident;
+            if path.leading_colon.is_some() || first == "crate" {
+                return vec![path.clone()];
+            }
+            let mut outer = path.clone();
+            if first == "self" {
+                outer.segments = outer.segments.into_iter().skip(1).collect();
+            }
+            let outer_first = outer.segments[0].ident.clone();
+            let nested = parse_quote!(super::#outer);
+            if outer_first == "super" || first == "self" {
+                vec![nested]
+            } else {
+                vec![path.clone(), nested]
+            }
+        })
+        .map(NoArgPath)
+        .collect()
+}
+
+impl CoinductionArgs {
+    /// The arguments for a module nested in the annotated one, with the trait paths read from
+    /// inside it, see [`nested_paths`]
+    fn nested(&self) -> Self {
+        let paths = nested_paths(&self.paths);
+        CoinductionArgs {
+            paths,
+            coinduction: self.coinduction.clone(),
+            facts: self.facts.clone(),
+            // A nested module is a part of the annotated one, which need not have cycles itself
+            allow_trivial: true,
+            ..*self
+        }
+    }
+}
+
+/// Whether `attr` is `#[coinduction]` or its alias, which resolves the module by itself
+fn is_coinduction_attr(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "coinduction" || segment.ident == "coinductive")
+}
+
+/// Fills omitted defaulted generic arguments of module-local types, so that
+/// `Pair<M>` is matched as `Pair<M, M>` for `struct Pair<const M: usize, const N: usize = M>`
+struct DefaultArgFiller<'a>(&'a HashMap<Ident, Generics>);
+
+impl DefaultArgFiller<'_> {
+    /// Const arguments which are bare paths are parsed as types; convert them for substitution
+    fn as_param_arg(param: &GenericParam, arg: &GenericArgument) -> GenericArgument {
+        match (param, arg) {
+            (
+                GenericParam::Const(_),
+                GenericArgument::Type(Type::Path(TypePath { qself: None, path })),
+            ) => GenericArgument::Const(Expr::Path(ExprPath {
+                attrs: Vec::new(),
+                qself: None,
+                path: path.clone(),
+            })),
+            _ => arg.clone(),
+        }
+    }
+
+    /// Bare-path const defaults are emitted as types, as the parser would produce them
+    fn as_written_arg(arg: GenericArgument) -> GenericArgument {
+        match arg {
+            GenericArgument::Const(Expr::Path(ExprPath {
+                qself: None, path, ..
+            })) => GenericArgument::Type(Type::Path(TypePath { qself: None, path })),
+            arg => arg,
+        }
+    }
+}
+
+impl VisitMut for DefaultArgFiller<'_> {
+    fn visit_type_path_mut(&mut self, type_path: &mut TypePath) {
+        visit_mut::visit_type_path_mut(self, type_path);
+        if type_path.qself.is_some() || type_path.path.segments.len() != 1 {
+            return;
+        }
+        let segment = &mut type_path.path.segments[0];
+        let Some(generics) = self.0.get(&segment.ident) else {
+            return;
+        };
+        let mut args = match &segment.arguments {
+            PathArguments::None => Punctuated::new(),
+            PathArguments::AngleBracketed(angle_args) => angle_args.args.clone(),
+            PathArguments::Parenthesized(_) => return,
+        };
+        let params: Vec<_> = generics
+            .params
+            .iter()
+            .filter(|param| !matches!(param, GenericParam::Lifetime(_)))
+            .collect();
+        let given: Vec<_> = args
+            .iter()
+            .filter(|arg| !matches!(arg, GenericArgument::Lifetime(_)))
+            .cloned()
+            .collect();
+        if given.len() >= params.len() {
+            return;
+        }
+        let mut substitute = Substitute::new();
+        for (param, arg) in params.iter().zip(&given) {
+            let Ok(s) = substitute.insert((*param).clone(), Self::as_param_arg(param, arg)) else {
+                return;
+            };
+            substitute = s;
+        }
+        for param in &params[given.len()..] {
+            let mut default = match param {
+                GenericParam::Type(TypeParam {
+                    default: Some(ty), ..
+                }) => GenericArgument::Type(ty.clone()),
+                GenericParam::Const(ConstParam {
+                    default: Some(expr),
+                    ..
+                }) => GenericArgument::Const(expr.clone()),
+                _ => return,
+            };
+            default.replace(&substitute);
+            let Ok(s) = substitute.insert((*param).clone(), default.clone()) else {
+                return;
+            };
+            substitute = s;
+            args.push(Self::as_written_arg(default));
+        }
+        segment.arguments = PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+            colon2_token: None,
+            lt_token: Default::default(),
+            args,
+            gt_token: Default::default(),
+        });
+    }
+}
+
+/// Associated type definitions of the module: `(params, self type, trait path, assoc, type)`,
+/// where the params include those of generic associated types such as `type Apply<X>`
+type AssocTypeRule = (HashSet<GenericParam>, Type, Path, PathSegment, Type);
+
+/// Normalizes projections `<X as Trait>::Assoc` using the associated type definitions of
+/// the impls in the module, so the projected type can take part in the cycle
+struct ProjectionNormalizer<'a> {
+    rules: &'a [AssocTypeRule],
+    depth: usize,
+    loose_paths: bool,
+}
+
+impl ProjectionNormalizer<'_> {
+    const MAX_DEPTH: usize = 32;
+
+    fn normalize(&self, type_path: &TypePath) -> Option<Type> {
+        let qself = type_path.qself.as_ref()?;
+        if qself.position + 1 != type_path.path.segments.len() {
+            return None;
+        }
+        let assoc = type_path.path.segments.last()?;
+        // Trait paths are compared by their last segment, as they may be imported
+        let segment = &type_path.path.segments[qself.position.checked_sub(1)?];
+        self.rules
+            .iter()
+            .find_map(|(params, self_ty, rule_trait, rule_assoc, ty)| {
+                let rule_segment = rule_trait.segments.last()?;
+                if rule_assoc.ident != assoc.ident || rule_segment.ident != segment.ident {
+                    return None;
+                }
+                let cx = MatchContext {
+                    loose_paths: self.loose_paths,
+                    ..MatchContext::from(params)
+                };
+                let substitute = self_ty
+                    .matches_in(&qself.ty, cx)
+                    .and_then(|substitute| {
+                        substitute.combine(
+                            rule_segment.arguments.matches_in(&segment.arguments, cx)?,
+                            cx,
+                        )
+                    })
+                    .and_then(|substitute| {
+                        substitute
+                            .combine(rule_assoc.arguments.matches_in(&assoc.arguments, cx)?, cx)
+                    })
+                    .inspect_err(abort_if_unsupported)
+                    .ok()?;
+                let mut ty = ty.clone();
+                ty.replace(&substitute);
+                Some(ty)
+            })
+    }
+}
+
+impl VisitMut for ProjectionNormalizer<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        visit_mut::visit_type_mut(self, ty);
+        if self.depth >= Self::MAX_DEPTH {
+            return;
+        }
+        if let Type::Path(type_path) = ty {
+            if let Some(normalized) = self.normalize(type_path) {
+                *ty = normalized;
+                self.depth += 1;
+                self.visit_type_mut(ty);
+                self.depth -= 1;
+            }
+        }
+    }
+}
+
+/// Type aliases of the module: `(params, alias applied to its params, aliased type)`, with the
+/// params renamed apart from those of the types the alias is used in
+pub(crate) type TypeAlias = (HashSet<GenericParam>, Type, Type);
+
+/// Collects the type aliases defined among `items`. Aliases imported from elsewhere are unknown
+/// and are left as they are written.
+pub(crate) fn type_aliases<'a>(items: impl IntoIterator<Item = &'a Item>) -> Vec<TypeAlias> {
+    items
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::Type(ItemType {
+                ident,
+                generics,
+                ty,
+                ..
+            }) => {
+                let (_, ty_generics, _) = generics.split_for_impl();
+                let mut pattern: Type = parse2(quote!(#ident #ty_generics)).ok()?;
+                let mut ty = ty.as_ref().clone();
+                let (renaming, renamed) = rename_apart(&generics.params.iter().cloned().collect());
+                pattern.replace(&renaming);
+                ty.replace(&renaming);
+                Some((renamed.into_keys().collect(), pattern, ty))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Expands the type aliases of the module, so that `NodePtr<T>` for
+/// `type NodePtr<T> = Option<Box<Node<T>>>` is compared as the type it stands for
+pub(crate) struct AliasExpander<'a> {
+    pub aliases: &'a [TypeAlias],
+    pub depth: usize,
+}
+
+impl AliasExpander<'_> {
+    const MAX_DEPTH: usize = 32;
+
+    fn expand(&self, type_path: &TypePath) -> Option<Type> {
+        if type_path.qself.is_some()
+            || type_path.path.leading_colon.is_some()
+            || type_path.path.segments.len() != 1
+        {
+            return None;
+        }
+        let written = Type::Path(type_path.clone());
+        self.aliases.iter().find_map(|(params, pattern, ty)| {
+            let substitute = pattern
+                .matches(&written, params)
+                .inspect_err(abort_if_unsupported)
+                .ok()?;
+            let mut ty = ty.clone();
+            ty.replace(&substitute);
+            Some(ty)
+        })
+    }
+}
+
+impl VisitMut for AliasExpander<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        visit_mut::visit_type_mut(self, ty);
+        if self.depth >= Self::MAX_DEPTH {
+            return;
+        }
+        if let Type::Path(type_path) = ty {
+            if let Some(expanded) = self.expand(type_path) {
+                *ty = expanded;
+                self.depth += 1;
+                self.visit_type_mut(ty);
+                self.depth -= 1;
+            }
+        }
+    }
+}
+
+/// Type parameters which only occur in bindings such as `I: Iterator<Item = U>` are not
+/// determined by matching the impl's self type. Express each of them as the projection it is
+/// equal to, `<I as Iterator>::Item`, so it does not leak into other impls as an unbound name.
+fn project_bound_params(rules: &mut [Constraint], generics: &Generics, head: &Constraint) {
+    let determined = head.referenced_params(&generics.params.iter().cloned().collect());
+    let mut substitute = Substitute::new();
+    let mut projected_idents = HashSet::new();
+    for rule in rules.iter_mut() {
+        let Some(PathArguments::AngleBracketed(angle_args)) = rule
+            .trait_path
+            .segments
+            .last()
+            .map(|segment| &segment.arguments)
+        else {
+            continue;
+        };
+        let mut kept = Punctuated::<GenericArgument, Token![,]>::new();
+        let mut projected = Vec::new();
+        for arg in &angle_args.args {
+            let param = match arg {
+                GenericArgument::AssocType(AssocType {
+                    ident: assoc,
+                    generics: None,
+                    ty: Type::Path(TypePath { qself: None, path }),
+                    ..
+                }) => path
+                    .get_ident()
+                    .filter(|ident| !projected_idents.contains(*ident))
+                    .and_then(|ident| {
+                        let param = generics.params.iter().find(|param| {
+                            matches!(param, GenericParam::Type(type_param) if type_param.ident == *ident)
+                                && !determined.contains(param)
+                        })?;
+                        Some((param, ident.clone(), assoc))
+                    }),
+                _ => None,
+            };
+            if let Some((param, ident, assoc)) = param {
+                projected_idents.insert(ident);
+                projected.push((param.clone(), assoc.clone()));
+            } else {
+                kept.push(arg.clone());
+            }
+        }
+        if projected.is_empty() {
+            continue;
+        }
+        // The binding which defines the parameter is dropped, as it would only restate itself
+        let mut trait_path = rule.trait_path.clone();
+        trait_path.segments.last_mut().unwrap().arguments = if kept.is_empty() {
+            PathArguments::None
+        } else {
+            PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                args: kept,
+                ..angle_args.clone()
+            })
+        };
+        for (param, assoc) in projected {
+            let typ = &rule.typ;
+            let projection: Type = parse2(quote!(<#typ as #trait_path>::#assoc)).unwrap();
+            substitute = substitute
+                .insert(param, GenericArgument::Type(projection))
+                .unwrap();
+        }
+        rule.trait_path = trait_path;
+    }
+    if !substitute.is_empty() {
+        for rule in rules.iter_mut() {
+            rule.replace(&substitute);
+        }
+    }
+}
+
+/// The predicates of the `#[cfg(..)]` attributes of an impl
+fn cfg_predicates_of(item_impl: &ItemImpl) -> Vec<TokenStream> {
+    item_impl
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .filter_map(|attr| attr.meta.require_list().ok())
+        .map(|list| list.tokens.clone())
+        .collect()
+}
+
+/// The distinct `cfg` predicates of the impls, in order of appearance
+fn collect_cfg_predicates(target_impls: &[&ItemImpl]) -> Vec<TokenStream> {
+    let mut predicates: Vec<TokenStream> = Vec::new();
+    for item_impl in target_impls {
+        for predicate in cfg_predicates_of(item_impl) {
+            if predicates
+                .iter()
+                .all(|p| p.to_string() != predicate.to_string())
+            {
+                predicates.push(predicate);
+            }
+        }
+    }
+    predicates
+}
+
+/// Partitions the impls, by index, into groups which never rewrite into each other: an impl
+/// joins every impl for a type it mentions, and an impl for a type parameter or a type which is
+/// not a path joins all of them
+fn dependent_groups(target_impls: &[ItemImpl]) -> Vec<Vec<usize>> {
+    struct Idents(HashSet<Ident>);
+
+    impl<'ast> syn::visit::Visit<'ast> for Idents {
+        fn visit_ident(&mut self, ident: &'ast Ident) {
+            self.0.insert(ident.clone());
+        }
+    }
+
+    fn root(parents: &mut [usize], mut ix: usize) -> usize {
+        while parents[ix] != ix {
+            parents[ix] = parents[parents[ix]];
+            ix = parents[ix];
+        }
+        ix
+    }
+
+    let heads: Vec<Option<&Ident>> = target_impls
+        .iter()
+        .map(|item_impl| match item_impl.self_ty.as_ref() {
+            Type::Path(p) if p.qself.is_none() => p
+                .path
+                .segments
+                .last()
+                .map(|segment| &segment.ident)
+                .filter(|ident| {
+                    p.path.segments.len() > 1
+                        || !item_impl
+                            .generics
+                            .type_params()
+                            .any(|tp| &tp.ident == *ident)
+                }),
+            _ => None,
+        })
+        .collect();
+    let mut parents: Vec<usize> = (0..target_impls.len()).collect();
+    for (ix, item_impl) in target_impls.iter().enumerate() {
+        let mut idents = Idents(HashSet::new());
+        syn::visit::Visit::visit_item_impl(&mut idents, item_impl);
+        for (other, head) in heads.iter().enumerate() {
+            if head.is_none_or(|head| idents.0.contains(head)) || heads[ix].is_none() {
+                let (a, b) = (root(&mut parents, ix), root(&mut parents, other));
+                parents[a] = b;
+            }
+        }
+    }
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of = HashMap::new();
+    for ix in 0..target_impls.len() {
+        let root = root(&mut parents, ix);
+        let group = *group_of.entry(root).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(ix);
+    }
+    groups
+}
+
+/// Lists constraints for a diagnostic, eliding all but the first few and cutting long ones short
+fn describe_constraints(constraints: &[Constraint]) -> String {
+    const SHOWN: usize = 8;
+    const MAX_LEN: usize = 60;
+    let mut description = constraints
+        .iter()
+        .take(SHOWN)
+        .map(|constraint| {
+            let text = quote!(#constraint).to_string();
+            match text.char_indices().nth(MAX_LEN) {
+                Some((cut, _)) => format!("`{}..`", &text[..cut]),
+                None => format!("`{}`", text),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if constraints.len() > SHOWN {
+        description += &format!(" and {} more", constraints.len() - SHOWN);
+    }
+    description
+}
+
+/// The bounds of `item_impl` on `working_traits`, with `Self` spelled as the self type
+fn working_bounds(
+    item_impl: &ItemImpl,
+    working_traits: &HashSet<NoArgPath>,
+    loose_paths: bool,
+) -> Vec<Constraint> {
+    let mut bounds = Vec::new();
+    Constraint::map_generics(&mut item_impl.generics.clone(), |constraint| {
+        if names_trait(working_traits, &constraint.trait_path, loose_paths) {
+            let mut bound = constraint.clone();
+            bound.replace_self(&item_impl.self_ty);
+            bounds.push(bound);
+        }
+        vec![constraint]
+    });
+    bounds
+}
+
+/// Aborts when impls of the working traits name two different trait paths ending in the same
+/// identifier, such as `a::Show` and `b::Show`; parts of the resolution compare traits by that
+/// identifier only. A bare name such as `Show` may be an import of any path ending in it.
+fn check_trait_name_collisions(
+    target_impls: &[ItemImpl],
+    working_traits: &HashSet<NoArgPath>,
+    loose_paths: bool,
+) {
+    let mut seen: HashMap<Ident, NoArgPath> = HashMap::new();
+    for (_, trait_path, _) in target_impls
+        .iter()
+        .filter_map(|item_impl| item_impl.trait_.as_ref())
+        .filter(|(_, trait_path, _)| names_trait(working_traits, trait_path, loose_paths))
+    {
+        let trait_path = remove_path_args(trait_path);
+        let ident = trait_path.0.segments.last().unwrap().ident.clone();
+        match seen.get(&ident) {
+            Some(first)
+                if first.0.segments.len() > 1
+                    && trait_path.0.segments.len() > 1
+                    && *first != trait_path =>
+            {
+                proc_macro_error::abort!(
+                    &trait_path,
+                    "coinduction: the trait name `{}` refers to both `{}` and `{}`",
+                    ident,
+                    quote!(#first).to_string().replace(' ', ""),
+                    quote!(#trait_path).to_string().replace(' ', "");
+                    help = "import one of them under another name, e.g. `use {} as Other{};`, or name both the same way if they are the same trait",
+                        quote!(#trait_path).to_string().replace(' ', ""),
+                        ident
+                )
+            }
+            // The qualified path is kept, so it is compared with the paths which follow
+            Some(first) if first.0.segments.len() == 1 => {
+                seen.insert(ident, trait_path);
+            }
+            Some(_) => (),
+            None => {
+                seen.insert(ident, trait_path);
+            }
+        }
+    }
+}
+
+/// Aborts when a trait named in the arguments is not implemented in the module or the nested
+/// modules resolved with it, which is most likely a misspelled path. Only the headers of the
+/// impls are read, as a trait which merely bounds an impl has nothing to resolve; with
+/// `inherent`, the bounds of inherent impls are resolved and read as well. The paths are
+/// compared as the resolution compares them, with each nested module reading them by
+/// [`nested_paths`].
+pub(crate) fn check_named_traits(module: &ItemMod, args: &CoinductionArgs) {
+    /// The impls among `items` and in the nested modules resolved with them, each with the
+    /// depth of its module
+    fn collect_impls<'a>(items: &'a [Item], depth: usize, impls: &mut Vec<(&'a ItemImpl, usize)>) {
+        for item in items {
+            match item {
+                Item::Impl(item_impl) => impls.push((item_impl, depth)),
+                Item::Mod(ItemMod {
+                    attrs,
+                    content: Some((_, items)),
+                    ..
+                }) if !attrs.iter().any(is_coinduction_attr) => {
+                    collect_impls(items, depth + 1, impls)
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let mut impls = Vec::new();
+    if let Some((_, items)) = &module.content {
+        collect_impls(items, 0, &mut impls);
+    }
+    let mut found: Vec<(Path, usize)> = Vec::new();
+    for (item_impl, depth) in impls {
+        match &item_impl.trait_ {
+            Some((_, trait_path, _)) => found.push((trait_path.clone(), depth)),
+            None if args.inherent => {
+                Constraint::map_generics(&mut item_impl.generics.clone(), |constraint| {
+                    found.push((constraint.trait_path.clone(), depth));
+                    vec![constraint]
+                })
+            }
+            None => (),
+        }
+    }
+    let max_depth = found.iter().map(|(_, depth)| *depth).max().unwrap_or(0);
+    for named in &args.paths {
+        let paths_by_depth: Vec<_> = std::iter::successors(
+            Some(std::iter::once(named.clone()).collect()),
+            |paths: &punctuated::Punctuated<_, _>| Some(nested_paths(paths)),
+        )
+        .take(max_depth + 1)
+        .collect();
+        if found
+            .iter()
+            .any(|(path, depth)| names_trait(&paths_by_depth[*depth], path, args.loose_paths))
+        {
+            continue;
+        }
+        let mut listed: Vec<String> = Vec::new();
+        for (path, _) in &found {
+            let path = remove_path_args(path);
+            let path = format!("`{}`", quote!(#path).to_string().replace(' ', ""));
+            if !listed.contains(&path) {
+                listed.push(path);
+            }
+        }
+        let what = if args.inherent {
+            "implemented or bounding an inherent impl"
+        } else {
+            "implemented"
+        };
+        proc_macro_error::abort!(
+            named,
+            "coinduction: no impl in this module implements `{}`",
+            quote!(#named).to_string().replace(' ', "");
+            help = "{}",
+                if listed.is_empty() {
+                    format!("no traits are {} in this module", what)
+                } else {
+                    format!("the traits {} in this module are {}", what, listed.join(", "))
+                }
+        );
     }
 }
 
-pub fn coinduction(module: ItemMod, args: CoinductionArgs) -> TokenStream {
-    let (target_impls, other_contents): (Vec<ItemImpl>, Vec<Item>) = module
+/// Whether `ty` mentions any of the `idents`, e.g. a module type in the arguments of a projection
+/// With `deep_args`, the constraints on the arguments of a foreign path type which mention the
+/// module's types, e.g. `Node<T>: Tr` for `HashMap<String, Node<T>>: Tr`
+///
+/// The foreign impl is assumed to require no more of its arguments than the trait itself, so a
+/// cycle through the argument closes within the module instead of relaying to the foreign type.
+pub(crate) fn argument_constraints(
+    constraint: &Constraint,
+    ignore_tys: &HashSet<Ident>,
+) -> Option<Vec<Constraint>> {
+    let Type::Path(TypePath { qself: None, path }) =
+        crate::unwrap_type_group(constraint.typ.clone())
+    else {
+        return None;
+    };
+    if path.leading_colon.is_none() && ignore_tys.contains(&path.segments[0].ident) {
+        return None;
+    }
+    let arguments: Vec<_> = path
+        .segments
+        .iter()
+        .filter_map(|segment| match &segment.arguments {
+            PathArguments::AngleBracketed(args) => Some(&args.args),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(typ) if mentions_any(typ, ignore_tys) => {
+                Some(constraint.clone().with_type(typ.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    (!arguments.is_empty()).then_some(arguments)
+}
+
+pub(crate) fn mentions_any(ty: &Type, idents: &HashSet<Ident>) -> bool {
+    struct Finder<'a>(&'a HashSet<Ident>, bool);
+
+    impl<'ast> syn::visit::Visit<'ast> for Finder<'_> {
+        fn visit_ident(&mut self, ident: &'ast Ident) {
+            self.1 |= self.0.contains(ident);
+        }
+    }
+
+    let mut finder = Finder(idents, false);
+    syn::visit::Visit::visit_type(&mut finder, ty);
+    finder.1
+}
+
+/// Rewrites shorthand projections `T::Assoc` in the rules to `<T as Trait>::Assoc` when `T` has
+/// a single trait bound, so that substituting `T` yields a projection which is still valid Rust
+fn qualify_shorthand_projections(rules: &mut [Constraint]) {
+    struct Qualifier<'a>(&'a [Constraint]);
+
+    impl VisitMut for Qualifier<'_> {
+        fn visit_type_mut(&mut self, ty: &mut Type) {
+            visit_mut::visit_type_mut(self, ty);
+            let Type::Path(TypePath { qself: None, path }) = ty else {
+                return;
+            };
+            if path.leading_colon.is_some()
+                || path.segments.len() != 2
+                || !path.segments[0].arguments.is_none()
+            {
+                return;
+            }
+            let param = &path.segments[0].ident;
+            let mut traits = self.0.iter().filter(|rule| {
+                let is_param = |typ: &Type| match typ {
+                    Type::Path(TypePath { qself: None, path }) => path.is_ident(param),
+                    _ => false,
+                };
+                rule.lifetimes.is_none() && is_param(&rule.typ)
+            });
+            let (Some(bound), None) = (traits.next(), traits.next()) else {
+                return;
+            };
+            // Built by hand rather than quoted so the projection keeps the spans of the original
+            let span = param.span();
+            let mut qualified = bound.trait_path.clone();
+            qualified.segments.push(path.segments[1].clone());
+            *ty = Type::Path(TypePath {
+                qself: Some(QSelf {
+                    lt_token: Token![<](span),
+                    ty: Box::new(bound.typ.clone()),
+                    position: bound.trait_path.segments.len(),
+                    as_token: Some(Token![as](span)),
+                    gt_token: Token![>](span),
+                }),
+                path: qualified,
+            });
+        }
+    }
+
+    let bounds = rules.to_vec();
+    for rule in rules.iter_mut() {
+        Qualifier(&bounds).visit_type_mut(&mut rule.typ);
+    }
+}
+
+/// The type parameter a projection such as `F::Apply<X>` or `<F as Functor>::Apply<X>` is taken
+/// from; what it projects to is only known once the parameter is instantiated
+fn projected_type_param<'a>(ty: &Type, generics: &'a Generics) -> Option<&'a Ident> {
+    let Type::Path(TypePath { qself, path }) = ty else {
+        return None;
+    };
+    let root = match qself {
+        Some(qself) => match crate::unwrap_type_group(qself.ty.as_ref().clone()) {
+            Type::Path(TypePath { qself: None, path }) => path.get_ident()?.clone(),
+            _ => return None,
+        },
+        None if path.leading_colon.is_none() && path.segments.len() > 1 => {
+            path.segments[0].ident.clone()
+        }
+        None => return None,
+    };
+    generics
+        .type_params()
+        .map(|type_param| &type_param.ident)
+        .find(|ident| **ident == root)
+}
+
+pub fn coinduction(module: ItemMod, mut args: CoinductionArgs) -> TokenStream {
+    // Iterate items in the module, and collect the struct/enum/unions with their generics
+    let type_generics: HashMap<Ident, Generics> = module
+        .content
+        .as_ref()
+        .map(|c| &c.1)
+        .into_iter()
+        .flatten()
+        .filter_map(|item| match item {
+            Item::Struct(item_struct) => {
+                Some((item_struct.ident.clone(), item_struct.generics.clone()))
+            }
+            Item::Enum(item_enum) => Some((item_enum.ident.clone(), item_enum.generics.clone())),
+            Item::Union(item_union) => {
+                Some((item_union.ident.clone(), item_union.generics.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    // Types of nested modules, such as `inner::Node`, are resolved by the relay of their module
+    // and are left as they are, like the module's own types which no impl rewrites
+    let nested_mods = module
+        .content
+        .iter()
+        .flat_map(|c| &c.1)
+        .filter_map(|item| match item {
+            Item::Mod(item_mod) if item_mod.content.is_some() => Some(item_mod.ident.clone()),
+            _ => None,
+        });
+    let ignore_tys: HashSet<Ident> = type_generics.keys().cloned().chain(nested_mods).collect();
+    let mut has_nested = false;
+    let (mut target_impls, mut other_contents): (Vec<ItemImpl>, Vec<Item>) = module
         .content
         .as_ref()
         .map(|c| &c.1)
@@ -34,149 +905,400 @@ pub fn coinduction(module: ItemMod, args: CoinductionArgs) -> TokenStream {
         .flatten()
         .fold(Default::default(), |(mut impls, mut others), item| {
             match item {
-                Item::Impl(item_impl) if item_impl.trait_.is_some() => {
+                Item::Impl(item_impl) if item_impl.trait_.is_some() || args.inherent => {
                     impls.push(item_impl.clone())
                 }
+                // Impls are emitted where they are written, as their paths are relative to it,
+                // so a nested module is resolved by a relay of its own
+                Item::Mod(item_mod)
+                    if item_mod.content.is_some()
+                        && !item_mod.attrs.iter().any(is_coinduction_attr) =>
+                {
+                    has_nested = true;
+                    others.push(Item::Verbatim(coinduction(item_mod.clone(), args.nested())))
+                }
                 other => others.push(other.clone()),
             }
             (impls, others)
         });
-    let working_traits: HashSet<_> = if args.paths.len() > 0 {
-        args.paths.into_iter().collect()
+    // The cycles may lie in the nested modules, whose relays are separate from this one
+    args.allow_trivial |= has_nested;
+    let aliases = type_aliases(module.content.iter().flat_map(|c| &c.1));
+    for item_impl in &mut target_impls {
+        let mut expander = AliasExpander {
+            aliases: &aliases,
+            depth: 0,
+        };
+        expander.visit_generics_mut(&mut item_impl.generics);
+        expander.visit_type_mut(&mut item_impl.self_ty);
+        if let Some((_, trait_path, _)) = &mut item_impl.trait_ {
+            expander.visit_path_mut(trait_path);
+        }
+        let mut filler = DefaultArgFiller(&type_generics);
+        filler.visit_generics_mut(&mut item_impl.generics);
+        filler.visit_type_mut(&mut item_impl.self_ty);
+    }
+    let assoc_type_rules: Vec<AssocTypeRule> = target_impls
+        .iter()
+        .filter(|item_impl| item_impl.trait_.is_some())
+        .flat_map(|item_impl| {
+            item_impl.items.iter().filter_map(move |item| match item {
+                ImplItem::Type(ImplItemType {
+                    ident,
+                    generics,
+                    ty,
+                    ..
+                }) => {
+                    let (_, ty_generics, _) = generics.split_for_impl();
+                    Some((
+                        item_impl
+                            .generics
+                            .params
+                            .iter()
+                            .chain(&generics.params)
+                            .cloned()
+                            .collect(),
+                        item_impl.self_ty.as_ref().clone(),
+                        item_impl.trait_.as_ref().unwrap().1.clone(),
+                        parse2(quote!(#ident #ty_generics)).ok()?,
+                        ty.clone(),
+                    ))
+                }
+                _ => None,
+            })
+        })
+        .collect();
+    if !assoc_type_rules.is_empty() {
+        for item_impl in &mut target_impls {
+            let mut normalizer = ProjectionNormalizer {
+                rules: &assoc_type_rules,
+                depth: 0,
+                loose_paths: args.loose_paths,
+            };
+            if let Some(where_clause) = &mut item_impl.generics.where_clause {
+                normalizer.visit_where_clause_mut(where_clause);
+            }
+        }
+    }
+    let working_traits: HashSet<_> = if !args.paths.is_empty() {
+        args.paths.iter().cloned().collect()
     } else {
         target_impls
             .iter()
             .filter_map(|ItemImpl { trait_, .. }| trait_.as_ref().map(|t| remove_path_args(&t.1)))
+            .filter(|path| !crate::is_marker_trait(&path.0))
             .collect()
     };
-    let rewrite_rules = target_impls
+    check_trait_name_collisions(&target_impls, &working_traits, args.loose_paths);
+    // Inherent impls have nothing to resolve unless their bounds mention the working traits
+    let (target_impls, plain_impls): (Vec<_>, Vec<_>) =
+        target_impls.into_iter().partition(|item_impl| {
+            item_impl.trait_.is_some()
+                || !working_bounds(item_impl, &working_traits, args.loose_paths).is_empty()
+        });
+    other_contents.extend(plain_impls.into_iter().map(Item::Impl));
+    // `cfg` is evaluated after expansion, so the impls are copied once for every combination of
+    // the predicates they are gated by, each copy gated by its combination and resolved in a
+    // scope of its own; only the predicates within a group of dependent impls are combined
+    let mut scoped_impls = Vec::new();
+    let mut scopes = Vec::new();
+    let mut scope_count = 0;
+    for group in dependent_groups(&target_impls) {
+        let group: Vec<&ItemImpl> = group.into_iter().map(|ix| &target_impls[ix]).collect();
+        let cfg_predicates = collect_cfg_predicates(&group);
+        if cfg_predicates.is_empty() {
+            scopes.extend(std::iter::repeat_n(scope_count, group.len()));
+            scoped_impls.extend(group.into_iter().cloned());
+            scope_count += 1;
+            continue;
+        }
+        for combination in 0..1usize << cfg_predicates.len() {
+            let holds = |predicate: &TokenStream| {
+                let ix = cfg_predicates
+                    .iter()
+                    .position(|p| p.to_string() == predicate.to_string())
+                    .unwrap();
+                combination & (1 << ix) != 0
+            };
+            let terms = cfg_predicates.iter().enumerate().map(|(ix, predicate)| {
+                if combination & (1 << ix) != 0 {
+                    predicate.clone()
+                } else {
+                    quote!(not(#predicate))
+                }
+            });
+            let gate = Attribute {
+                pound_token: Default::default(),
+                style: AttrStyle::Outer,
+                bracket_token: Default::default(),
+                meta: Meta::List(MetaList {
+                    path: Ident::new("cfg", proc_macro2::Span::call_site()).into(),
+                    delimiter: MacroDelimiter::Paren(Default::default()),
+                    tokens: quote!(all(#(#terms),*)),
+                }),
+            };
+            for item_impl in &group {
+                if cfg_predicates_of(item_impl).iter().all(holds) {
+                    let mut item_impl = (*item_impl).clone();
+                    item_impl.attrs.push(gate.clone());
+                    scoped_impls.push(item_impl);
+                    scopes.push(scope_count);
+                }
+            }
+            scope_count += 1;
+        }
+    }
+    let next = resolve(scoped_impls, scopes, &working_traits, &ignore_tys, &args);
+    quote! {
+        #(for attr in module.attrs.iter().filter(|a| a.style == AttrStyle::Outer)) {#attr}
+        #{ &module.vis }
+        #{ &module.unsafety }
+        #{ &module.mod_token }
+        #{ &module.ident } {
+            #(for attr in module.attrs.iter().filter(|a| a.style != AttrStyle::Outer)) {#attr}
+            #(for content in other_contents) { #content }
+            #next
+        }
+    }
+}
+
+/// A rule read from an impl of the module: its head, the params bound by matching the head,
+/// and the constraints which the head is rewritten to
+pub(crate) type RewriteRule = (HashSet<GenericParam>, Constraint, Vec<Constraint>);
+
+/// Reads the impls of working traits among `target_impls` as rewrite rules
+fn rewrite_rules(
+    target_impls: &[ItemImpl],
+    working_traits: &HashSet<NoArgPath>,
+    loose_paths: bool,
+) -> Vec<RewriteRule> {
+    target_impls
         .iter()
-        .filter_map(|item_impl| {
-            working_traits
-                .contains(&remove_path_args(&item_impl.trait_.as_ref().unwrap().1))
-                .then(|| {
-                    let mut rules = Vec::new();
-                    Constraint::map_generics(&mut item_impl.generics.clone(), |c| {
-                        rules.push(c.clone());
-                        vec![c]
-                    });
-                    (
-                        item_impl.generics.clone(),
-                        Constraint {
-                            typ: item_impl.self_ty.as_ref().clone(),
-                            trait_path: item_impl.trait_.as_ref().unwrap().1.clone(),
-                        },
-                        rules,
-                    )
-                })
+        .filter(|&item_impl| {
+            item_impl
+                .trait_
+                .as_ref()
+                .is_some_and(|(_, path, _)| names_trait(working_traits, path, loose_paths))
         })
-        .collect::<Vec<_>>();
-    let mut working_list = HashSet::new();
-    // Iterate items in the module, and generate Ident list of the struct/enum/unions
-    let ignore_tys: HashSet<Ident> = module
-        .content
-        .as_ref()
-        .map(|c| &c.1)
-        .into_iter()
-        .flatten()
-        .filter_map(|item| match item {
-            Item::Struct(item_struct) => Some(item_struct.ident.clone()),
-            Item::Enum(item_enum) => Some(item_enum.ident.clone()),
-            Item::Union(item_union) => Some(item_union.ident.clone()),
-            _ => None,
+        .map(|item_impl| {
+            let mut rules = Vec::new();
+            Constraint::map_generics(&mut item_impl.generics.clone(), |c| {
+                let mut rule = c.clone();
+                rule.replace_self(&item_impl.self_ty);
+                rules.push(rule);
+                vec![c]
+            });
+            let head = Constraint::new(
+                item_impl.self_ty.as_ref().clone(),
+                item_impl.trait_.as_ref().unwrap().1.clone(),
+            );
+            project_bound_params(&mut rules, &item_impl.generics, &head);
+            qualify_shorthand_projections(&mut rules);
+            let params =
+                head.referenced_params(&item_impl.generics.params.iter().cloned().collect());
+            let (renaming, renamed) = rename_apart(&params);
+            let params = renamed.into_keys().collect::<HashSet<_>>();
+            let mut head = head;
+            head.replace(&renaming);
+            for rule in &mut rules {
+                rule.replace(&renaming);
+            }
+            (params, head, rules)
         })
-        .collect();
+        .collect()
+}
+
+/// The rewrite rules of each scope, read from the impls of that scope only
+pub(crate) fn scoped_rewrite_rules(
+    target_impls: &[ItemImpl],
+    scopes: &[usize],
+    working_traits: &HashSet<NoArgPath>,
+    loose_paths: bool,
+) -> HashMap<usize, Vec<RewriteRule>> {
+    let mut impls_by_scope: HashMap<usize, Vec<ItemImpl>> = HashMap::new();
+    for (item_impl, scope) in target_impls.iter().zip(scopes) {
+        impls_by_scope
+            .entry(*scope)
+            .or_default()
+            .push(item_impl.clone());
+    }
+    impls_by_scope
+        .into_iter()
+        .map(|(scope, impls)| (scope, rewrite_rules(&impls, working_traits, loose_paths)))
+        .collect()
+}
+
+/// Builds the solvers for `target_impls` and starts the relay which resolves each of them with
+/// the rules of its scope
+fn resolve(
+    target_impls: Vec<ItemImpl>,
+    scopes: Vec<usize>,
+    working_traits: &HashSet<NoArgPath>,
+    ignore_tys: &HashSet<Ident>,
+    args: &CoinductionArgs,
+) -> TokenStream {
+    let rules_by_scope =
+        scoped_rewrite_rules(&target_impls, &scopes, working_traits, args.loose_paths);
+    let mut working_list = HashSet::new();
+    let mut stats = args.stats.then(Stats::default);
     let solvers = target_impls
         .iter()
-        .map(|item_impl| {
-            let constraint = Constraint {
-                typ: item_impl.self_ty.as_ref().clone(),
-                trait_path: item_impl.trait_.as_ref().unwrap().1.clone(),
-            };
-            if !working_traits.contains(&remove_path_args(&constraint.trait_path)) {
-                return None;
-            }
-            let mut solver = Solver {
-                graph: Default::default(),
-                generic_params: item_impl.generics.params.iter().cloned().collect(),
+        .zip(&scopes)
+        .map(|(item_impl, scope)| {
+            let rewrite_rules = &rules_by_scope[scope];
+            // An inherent impl has no head, so its bounds are resolved from themselves
+            let seeds = match &item_impl.trait_ {
+                Some((_, trait_path, _)) => {
+                    let constraint =
+                        Constraint::new(item_impl.self_ty.as_ref().clone(), trait_path.clone());
+                    if !names_trait(working_traits, &constraint.trait_path, args.loose_paths) {
+                        return None;
+                    }
+                    vec![constraint]
+                }
+                None => working_bounds(item_impl, working_traits, args.loose_paths),
             };
+            let mut solver = Solver::new(item_impl.generics.params.iter().cloned());
 
             solver.graph.scope_mut(|mut graph| {
-                let root_node = graph.add_node(constraint.clone());
-                let mut local_working_list = VecDeque::new();
-                local_working_list.push_back(root_node);
+                let mut index = ConstraintIndex::new(&graph);
+                let mut local_working_list = seeds
+                    .into_iter()
+                    .map(|seed| index.add(&mut graph, seed))
+                    .collect::<VecDeque<_>>();
                 let mut iteration_count = 0;
-                const MAX_ITERATIONS: usize = 1000;
+                // The constraints expanded last, reported when the limit is hit
+                const RECENT: usize = 5;
+                let mut recent = VecDeque::new();
                 while let Some(node_id) = local_working_list.pop_front() {
                     let constraint = graph.node(node_id).clone();
                     iteration_count += 1;
-                    if iteration_count > MAX_ITERATIONS {
-                        proc_macro_error::abort!(
-                            &constraint.trait_path,
-                            "Maximum iteration limit reached ({}). Possible infinite loop in coinduction resolution.",
-                            MAX_ITERATIONS
+                    if iteration_count > MAX_EXPANSIONS {
+                        let largest_cycle = gotgraph::algo::tarjan(&graph)
+                            .max_by_key(|component| component.len())
+                            .map(|component| {
+                                // Runaway rules grow their types, so the smallest members are the
+                                // ones which tell where the cycle starts
+                                let mut nodes: Vec<_> =
+                                    component.iter().map(|ix| graph.node(*ix).clone()).collect();
+                                nodes.sort_by_cached_key(|node| quote!(#node).to_string().len());
+                                describe_constraints(&nodes)
+                            })
+                            .unwrap_or_default();
+                        let recent: Vec<_> = recent.into_iter().collect();
+                        abort_runaway_expansion(
+                            &constraint,
+                            vec![
+                                format!("the largest cycle found so far: {}", largest_cycle),
+                                format!(
+                                    "the last constraints expanded: {}",
+                                    describe_constraints(&recent)
+                                ),
+                            ],
                         );
                     }
-                    if !working_traits.contains(&remove_path_args(&constraint.trait_path)) {
+                    if recent.len() == RECENT {
+                        recent.pop_front();
+                    }
+                    recent.push_back(constraint.clone());
+                    if !names_trait(working_traits, &constraint.trait_path, args.loose_paths) {
                         continue;
                     }
                     let unwrapped_typ = crate::unwrap_type_group(constraint.typ.clone());
-                    let is_module_type = matches!(&unwrapped_typ, Type::Path(p) if p.path.segments.len() == 1 && ignore_tys.contains(&p.path.segments[0].ident));
+                    if let Type::Macro(type_macro) = &unwrapped_typ {
+                        // The relay cannot expand the macro, so the constraint would be lost
+                        proc_macro_error::abort!(
+                            &type_macro.mac,
+                            "coinduction: macro types are not supported in coinductive constraints";
+                            help = "write out the type the macro expands to"
+                        );
+                    }
+                    let projected_param =
+                        projected_type_param(&unwrapped_typ, &item_impl.generics);
+                    if let Some(param) =
+                        projected_param.filter(|_| mentions_any(&unwrapped_typ, ignore_tys))
+                    {
+                        // A type-level fixpoint such as `Fix<F>` only unfolds for a concrete `F`
+                        proc_macro_error::abort!(
+                            &constraint.typ,
+                            "coinduction: cannot resolve a projection of the generic parameter `{}`", param;
+                            help = "implement the trait for each concrete instance, e.g. `Fix<ListF>` with the bound `<ListF as Functor>::Apply<Fix<ListF>>`, which is normalized through the module's impls"
+                        );
+                    }
+                    let is_module_type = matches!(&unwrapped_typ, Type::Path(p) if p.path.leading_colon.is_none() && ignore_tys.contains(&p.path.segments[0].ident));
                     let is_generic = matches!(&unwrapped_typ, Type::Path(p) if p.path.segments.len() == 1 &&
                         item_impl.generics.params.iter().any(|param|
                             matches!(param, GenericParam::Type(tp) if tp.ident == p.path.segments[0].ident)
                         )
                     );
 
-                    if !is_module_type && !is_generic {
-                        working_list.insert(constraint.clone());
+                    // Projections are not known to any trait macro, so they stay leaves of the graph
+                    // and are carried over to the where clause as they are
+                    if projected_param.is_some()
+                        || crate::is_qualified_projection(&unwrapped_typ)
+                    {
                         continue;
                     }
-
-                    for (generics, rule_constraint, rule_constraints) in &rewrite_rules {
-                        let params: HashSet<_> = generics.params.iter().cloned().collect();
-                        if let Some(substitution) = rule_constraint.matches(&constraint, &params) {
-                            for mut new_constraint in rule_constraints.clone() {
-                                new_constraint.replace(&substitution);
-                                let existing_node = graph
-                                    .node_pairs()
-                                    .find(|(_, c)| **c == new_constraint)
-                                    .map(|(id, _)| id);
-                                let new_node_id = if let Some(id) = existing_node {
-                                    id
-                                } else {
-                                    let n = graph.add_node(new_constraint.clone());
+                    if !is_module_type && !is_generic {
+                        let arguments = args
+                            .deep_args
+                            .then(|| argument_constraints(&constraint, ignore_tys))
+                            .flatten()
+                            .filter(|_| !args.facts.contains(&constraint));
+                        if let Some(arguments) = arguments {
+                            for argument in arguments {
+                                let argument_id = index.find(&argument).unwrap_or_else(|| {
+                                    let n = index.add(&mut graph, argument);
                                     local_working_list.push_back(n);
                                     n
-                                };
-                                graph.add_edge((), node_id, new_node_id);
+                                });
+                                add_dependency(&mut graph, node_id, argument_id);
                             }
-                            break;
+                            continue;
+                        }
+                        if !args.facts.contains(&constraint) {
+                            working_list.insert(constraint.clone());
                         }
+                        continue;
+                    }
+
+                    let body = apply_rewrite_rules(
+                        rewrite_rules,
+                        &constraint,
+                        &mut stats,
+                        args.loose_paths,
+                    );
+                    for new_constraint in body.into_iter().flatten() {
+                        let new_node_id = index.find(&new_constraint).unwrap_or_else(|| {
+                            let n = index.add(&mut graph, new_constraint);
+                            local_working_list.push_back(n);
+                            n
+                        });
+                        add_dependency(&mut graph, node_id, new_node_id);
                     }
                 }
             });
+            solver.debug_assert_consistent();
             Some(solver)
         })
         .collect();
     let next_step_args = NextStepArgs {
         kind: NextStepKind::None,
         working_list: working_list.into_iter().collect(),
-        coinduction: args.coinduction,
-        working_traits: working_traits.into_iter().collect(),
-        ignore_tys,
+        coinduction: args.coinduction.clone(),
+        working_traits: working_traits.iter().cloned().collect(),
+        ignore_tys: ignore_tys.clone(),
+        facts: args.facts.clone(),
         solvers,
         target_impls,
+        scopes,
+        stats,
+        relay_log: args.dump_relay.then(Vec::new),
+        show_order: args.show_order,
+        dump_graph: args.dump_graph,
+        deep_args: args.deep_args,
+        loose_paths: args.loose_paths,
+        allow_trivial: args.allow_trivial,
     };
-    let next = next_step(next_step_args);
-    quote! {
-        #(for attr in &module.attrs) {#attr}
-        #{ &module.vis }
-        #{ &module.unsafety }
-        #{ &module.mod_token }
-        #{ &module.ident } {
-            #(for content in other_contents) { #content }
-            #next
-        }
-    }
+    next_step(next_step_args)
 }
@@ -0,0 +1,43 @@
+//! Shared constructors for errors raised while parsing the `__next_step` relay protocol.
+//!
+//! Every message is prefixed with `coinduction:` so failures from any stage of the
+//! multi-stage expansion are reported consistently.
+
+use proc_macro2::Span;
+use syn::{Error, Ident, LitStr};
+
+pub use coinduction_core::error::expected_list;
+
+/// The relay was produced by a different version of the macro crate, typically because a
+/// dependency pulls in another `coinduction` than the crate expanding the relay
+pub fn version_mismatch(found: &LitStr, expected: &str) -> Error {
+    Error::new(
+        found.span(),
+        format!(
+            "coinduction: version mismatch: expected '{expected}', found '{found}'\n\
+             help: a `#[traitdef]` or `#[typedef]` macro was generated by coinduction {found}, \
+             but is expanded by coinduction {expected}; depend on a single version of \
+             `coinduction` in every crate (`cargo tree -d` lists the duplicated ones)",
+            expected = expected,
+            found = found.value()
+        ),
+    )
+}
+
+/// Either the `None` marker or the described item was expected
+pub fn expected_none_or(span: Span, what: &str) -> Error {
+    Error::new(span, format!("coinduction: expected `None` or {}", what))
+}
+
+/// The step kind is not one of `Traitdef`, `Typedef` or `None`
+pub fn unknown_step_kind(ident: &Ident) -> Error {
+    Error::new(
+        ident.span(),
+        format!("coinduction: unknown step kind `{}`", ident),
+    )
+}
+
+/// A where-predicate other than `Type: Bounds` was found
+pub fn expected_type_predicate(span: Span) -> Error {
+    Error::new(span, "coinduction: expected a type predicate")
+}
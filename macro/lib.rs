@@ -5,9 +5,27 @@ use syn::*;
 use template_quote::ToTokens;
 
 /// A wrapper for Path that ensures no path arguments are present
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+///
+/// Paths compare equal whether or not they are written with a leading `::`, so that
+/// `::std::fmt::Display` and `std::fmt::Display` name the same trait; the spelling is kept
+/// for printing.
+#[derive(Clone, Debug)]
 struct NoArgPath(pub Path);
 
+impl PartialEq for NoArgPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.segments == other.0.segments
+    }
+}
+
+impl Eq for NoArgPath {}
+
+impl core::hash::Hash for NoArgPath {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.segments.hash(state);
+    }
+}
+
 impl Parse for NoArgPath {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let path: Path = input.parse()?;
@@ -37,6 +55,23 @@ fn remove_path_args(path: &Path) -> NoArgPath {
     NoArgPath(new_path)
 }
 
+/// Whether `path`, with or without arguments, names one of `traits`; with loose paths a bare
+/// trait name also names a qualified one ending in it, e.g. `TraitA` and `super::TraitA`
+fn names_trait<'a>(
+    traits: impl IntoIterator<Item = &'a NoArgPath>,
+    path: &Path,
+    loose_paths: bool,
+) -> bool {
+    let path = remove_path_args(path);
+    traits.into_iter().any(|NoArgPath(candidate)| {
+        candidate.segments == path.0.segments
+            || (loose_paths
+                && (candidate.segments.len() == 1 || path.0.segments.len() == 1)
+                && candidate.segments.last().map(|segment| &segment.ident)
+                    == path.0.segments.last().map(|segment| &segment.ident))
+    })
+}
+
 /// Try to parse `coinduction = <path>` as the first argument
 /// Returns coinduction path, defaults to `::coinduction`
 fn try_parse_coinduction_args(input: ParseStream) -> syn::Result<NoArgPath> {
@@ -63,6 +98,15 @@ fn try_parse_coinduction_args(input: ParseStream) -> syn::Result<NoArgPath> {
     Ok(NoArgPath(default_path))
 }
 
+/// Parses the arguments of `#[check_traits]`: `coinduction = <path>` and the traits to check
+fn parse_check_traits_args(
+    input: ParseStream,
+) -> syn::Result<(NoArgPath, punctuated::Punctuated<NoArgPath, Token![,]>)> {
+    let coinduction = try_parse_coinduction_args(input)?;
+    let traits = punctuated::Punctuated::parse_terminated(input)?;
+    Ok((coinduction, traits))
+}
+
 fn get_random() -> u64 {
     use core::hash::{BuildHasher, Hasher};
     std::collections::hash_map::RandomState::new()
@@ -70,26 +114,25 @@ fn get_random() -> u64 {
         .finish()
 }
 
-/// Unwrap TypeGroup/TypeParen which may be introduced during macro expansion
-fn unwrap_type_group(typ: Type) -> Type {
-    match typ {
-        Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. }) => {
-            unwrap_type_group(*elem)
-        }
-        _ => typ,
-    }
+/// Whether `typ` is a projection `<X as Trait>::Assoc`, which no trait macro can expand
+fn is_qualified_projection(typ: &Type) -> bool {
+    matches!(typ, Type::Path(TypePath { qself: Some(_), .. }))
 }
 
+use coinduction_core::{is_marker_trait, matching, parse_list, solver, unwrap_type_group};
+
 mod coinduction;
-mod matching;
+mod error;
 mod next_step;
-mod solver;
 mod traitdef;
 mod typedef;
 
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn traitdef(attr: TokenStream, input: TokenStream) -> TokenStream {
+    if cfg!(feature = "coinduction_passthrough") {
+        return input;
+    }
     let item = parse_macro_input!(input as ItemTrait);
     let args = parse_macro_input!(attr as traitdef::TraitDefArgs);
     traitdef::traitdef(item, args).into()
@@ -98,6 +141,9 @@ pub fn traitdef(attr: TokenStream, input: TokenStream) -> TokenStream {
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn typedef(attr: TokenStream, input: TokenStream) -> TokenStream {
+    if cfg!(feature = "coinduction_passthrough") {
+        return input;
+    }
     let item = parse_macro_input!(input as ItemMod);
     let args = parse_macro_input!(attr as typedef::TypeDefArgs);
     typedef::typedef(item, args).into()
@@ -106,13 +152,39 @@ pub fn typedef(attr: TokenStream, input: TokenStream) -> TokenStream {
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn coinduction(attr: TokenStream, input: TokenStream) -> TokenStream {
+    if cfg!(feature = "coinduction_passthrough") {
+        return input;
+    }
     let item = parse_macro_input!(input as ItemMod);
     let args = parse_macro_input!(attr as coinduction::CoinductionArgs);
+    coinduction::check_named_traits(&item, &args);
     coinduction::coinduction(item, args).into()
 }
 
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn check_traits(attr: TokenStream, input: TokenStream) -> TokenStream {
+    if cfg!(feature = "coinduction_passthrough") {
+        return input;
+    }
+    let (coinduction, traits) = parse_macro_input!(attr with parse_check_traits_args);
+    let crate_version = env!("CARGO_PKG_VERSION");
+    let input = proc_macro2::TokenStream::from(input);
+    // A trait without `#[traitdef]` has no relay macro, so the invocation fails to resolve
+    template_quote::quote! {
+        #input
+        #(for path in &traits) {
+            #path ! { #crate_version, Check, {#coinduction} }
+        }
+    }
+    .into()
+}
+
+#[proc_macro_error]
 #[proc_macro]
 pub fn __next_step(input: TokenStream) -> TokenStream {
-    let args = syn::parse_macro_input!(input as next_step::NextStepArgs);
-    next_step::next_step(args).into()
+    match syn::parse_macro_input!(input as next_step::NextStepInput) {
+        next_step::NextStepInput::Step(args) => next_step::next_step(*args).into(),
+        next_step::NextStepInput::Check => TokenStream::new(),
+    }
 }
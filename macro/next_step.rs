@@ -1,28 +1,173 @@
 use gotgraph::prelude::*;
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use proc_macro_error::abort;
+use std::collections::HashSet;
 use std::collections::VecDeque;
-use std::collections::{HashMap, HashSet};
 use syn::parse::{Parse, ParseStream};
-use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit_mut::{self, VisitMut};
 use syn::*;
 use template_quote::{quote, ToTokens};
 
 use crate::{
-    matching::Matching,
-    solver::{Constraint, Solver},
+    coinduction::{scoped_rewrite_rules, RewriteRule},
+    error,
+    matching::{cyclic_binding, unify, MatchError, Matching, Substitute},
+    solver::{add_dependency, Constraint, ConstraintIndex, GroupStripper, Solver, MAX_EXPANSIONS},
     NoArgPath,
 };
 
 const PACKAGE_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Whether a relay emitted by version `found` can be read by version `expected`. Only the
+/// release numbers are compared, so pre-release and build metadata do not matter.
+fn compatible_version(found: &str, expected: &str) -> bool {
+    let release = |version: &str| {
+        semver::Version::parse(version)
+            .ok()
+            .map(|version| (version.major, version.minor, version.patch))
+    };
+    matches!((release(found), release(expected)), (Some(found), Some(expected)) if found == expected)
+}
+
+/// Environment variable which, when set, makes the expansion report its diagnostics as notes,
+/// see [`trace`]
+const TRACE_ENV: &str = "COINDUCTION_TRACE";
+
+/// Reports `lines` as the notes of a warning at the call site, which `proc_macro_error` prints
+/// on nightly and drops on stable
+fn emit_notes(title: &str, lines: impl IntoIterator<Item = String>) {
+    lines
+        .into_iter()
+        .fold(
+            proc_macro_error::Diagnostic::new(
+                proc_macro_error::Level::Warning,
+                format!("coinduction: {}", title),
+            ),
+            proc_macro_error::Diagnostic::note,
+        )
+        .emit()
+}
+
+/// Reports `lines` through [`emit_notes`] if [`TRACE_ENV`] is set while compiling
+fn trace(title: &str, lines: impl IntoIterator<Item = String>) {
+    if std::env::var_os(TRACE_ENV).is_some() {
+        emit_notes(title, lines);
+    }
+}
+
+/// How many of the rules which failed to match a constraint are reported under [`TRACE_ENV`]
+const MAX_NEAR_MISSES: usize = 3;
+
+/// Reports why the rules whose heads looked applicable to `constraint` did not match it
+pub(crate) fn trace_near_misses(constraint: &Constraint, near_misses: &[(Constraint, MatchError)]) {
+    if near_misses.is_empty() {
+        return;
+    }
+    // A param bound to two arguments tells best why an impl's bound was kept
+    let mut near_misses: Vec<_> = near_misses.iter().collect();
+    near_misses.sort_by_key(|(_, error)| error.conflict.is_none());
+    trace(
+        &format!("no rule matched `{}`", quote!(#constraint)),
+        near_misses
+            .into_iter()
+            .take(MAX_NEAR_MISSES)
+            .map(|(head, error)| format!("`{}`: {}", quote!(#head), error)),
+    );
+}
+
+/// Aborts on a match which failed as an argument carries attributes a substitution cannot
+/// drop, as the argument would fail every other rule alike
+pub(crate) fn abort_if_unsupported(error: &MatchError) {
+    if error.is_unsupported() {
+        abort!(
+            error.rhs,
+            "coinduction: {}", error.reason;
+            note = "only `doc` and lint attributes can be dropped from an argument"
+        );
+    }
+}
+
+/// Aborts at `constraint` as the rules expanded more than [`MAX_EXPANSIONS`] constraints,
+/// adding `notes` which tell where they run away
+pub(crate) fn abort_runaway_expansion(constraint: &Constraint, notes: Vec<String>) -> ! {
+    notes
+        .into_iter()
+        .fold(
+            proc_macro_error::Diagnostic::spanned(
+                constraint.trait_path.span(),
+                proc_macro_error::Level::Error,
+                format!(
+                    "Maximum iteration limit reached ({}). Possible infinite loop in coinduction resolution.",
+                    MAX_EXPANSIONS
+                ),
+            ),
+            proc_macro_error::Diagnostic::note,
+        )
+        .abort()
+}
+
+/// Rewrites `constraint` by the first of `rules` whose head unifies with it, returning the
+/// rule's body with the head's bindings applied. The rules whose heads looked applicable but
+/// did not match are traced, see [`trace_near_misses`].
+pub(crate) fn apply_rewrite_rules(
+    rules: &[RewriteRule],
+    constraint: &Constraint,
+    stats: &mut Option<Stats>,
+    loose_paths: bool,
+) -> Option<Vec<Constraint>> {
+    let mut near_misses = Vec::new();
+    for (params, head, body) in rules {
+        if !head.could_unify(constraint) {
+            continue;
+        }
+        if let Some(stats) = stats {
+            stats.rule_matches += 1;
+        }
+        // The rule's params are renamed apart, so that one sharing a name with a param of the
+        // impl does not bind that param
+        match unify(head, constraint, params, &HashSet::new(), loose_paths) {
+            Ok((substitution, _)) => {
+                return Some(
+                    body.iter()
+                        .map(|rule| {
+                            let mut rule = rule.clone();
+                            rule.replace(&substitution);
+                            rule
+                        })
+                        .collect(),
+                );
+            }
+            Err(error) => {
+                abort_if_unsupported(&error);
+                near_misses.push((head.clone(), error));
+            }
+        }
+    }
+    trace_near_misses(constraint, &near_misses);
+    None
+}
+
+/// A type which `#[typedef]` writes as a projection on its marker, as its path may not resolve
+/// where the constraint is emitted: `(projection, the type it refers to)`
+pub type ReferredType = (Type, Type);
+
+/// A typedef'd impl: its params, its head, the constraints the head is rewritten to and the
+/// types which those constraints refer to through projections
+pub type TypedefPredicate = (
+    HashSet<GenericParam>,
+    Constraint,
+    Vec<Constraint>,
+    Vec<ReferredType>,
+);
+
 #[derive(Clone, PartialEq)]
 pub enum NextStepKind {
     Traitdef {
         appending_constraints: Vec<PredicateType>,
     },
     Typedef {
-        predicates: Vec<(HashSet<GenericParam>, Constraint, Vec<Constraint>)>,
+        predicates: Vec<TypedefPredicate>,
     },
     None,
 }
@@ -43,6 +188,12 @@ impl Parse for NextStepKind {
 
                 content.parse::<kw::appending_constraints>()?;
                 content.parse::<Token![:]>()?;
+                if !content.peek(syn::token::Bracket) {
+                    return Err(error::expected_list(
+                        content.span(),
+                        "appending constraints",
+                    ));
+                }
                 let constraints_content;
                 syn::bracketed!(constraints_content in content);
                 let mut constraints = Vec::new();
@@ -51,7 +202,7 @@ impl Parse for NextStepKind {
                     if let WherePredicate::Type(pred_type) = pred {
                         constraints.push(pred_type);
                     } else {
-                        abort!(pred, "expected type predicate");
+                        return Err(error::expected_type_predicate(pred.span()));
                     }
                     if constraints_content.parse::<Token![,]>().is_err() {
                         break;
@@ -67,6 +218,9 @@ impl Parse for NextStepKind {
                 syn::braced!(content in input);
                 content.parse::<kw::predicates>()?;
                 content.parse::<Token![:]>()?;
+                if !content.peek(syn::token::Bracket) {
+                    return Err(error::expected_list(content.span(), "typedef predicates"));
+                }
                 let predicates_content;
                 syn::bracketed!(predicates_content in content);
                 let mut predicates = Vec::new();
@@ -75,10 +229,11 @@ impl Parse for NextStepKind {
                     syn::parenthesized!(tuple_content in predicates_content);
 
                     // Parse HashSet<GenericParam>
-                    let params_content;
-                    syn::bracketed!(params_content in tuple_content);
-                    let params: Punctuated<GenericParam, Token![,]> =
-                        params_content.parse_terminated(GenericParam::parse, Token![,])?;
+                    let params = crate::parse_list(
+                        &tuple_content,
+                        "generic parameters",
+                        GenericParam::parse,
+                    )?;
                     let param_set: HashSet<GenericParam> = params.into_iter().collect();
 
                     tuple_content.parse::<Token![,]>()?;
@@ -88,12 +243,25 @@ impl Parse for NextStepKind {
                     tuple_content.parse::<Token![,]>()?;
 
                     // Parse Vec<Constraint>
-                    let vec_content;
-                    syn::bracketed!(vec_content in tuple_content);
-                    let constraints: Punctuated<Constraint, Token![,]> =
-                        vec_content.parse_terminated(Constraint::parse, Token![,])?;
+                    let constraints =
+                        crate::parse_list(&tuple_content, "constraints", Constraint::parse)?;
+                    tuple_content.parse::<Token![,]>()?;
+
+                    // Parse Vec<ReferredType>
+                    let referred = crate::parse_list(&tuple_content, "referred types", |input| {
+                        let pair;
+                        syn::parenthesized!(pair in input);
+                        let projection = pair.parse::<Type>()?;
+                        pair.parse::<Token![,]>()?;
+                        Ok((projection, pair.parse::<Type>()?))
+                    })?;
 
-                    predicates.push((param_set, constraint, constraints.into_iter().collect()));
+                    predicates.push((
+                        param_set,
+                        constraint,
+                        constraints.into_iter().collect(),
+                        referred.into_iter().collect(),
+                    ));
                     if predicates_content.parse::<Token![,]>().is_err() {
                         break;
                     }
@@ -101,7 +269,18 @@ impl Parse for NextStepKind {
                 Ok(NextStepKind::Typedef { predicates })
             }
             "None" => Ok(NextStepKind::None),
-            _ => Err(syn::Error::new_spanned(ident, "Invalid NextStepKind")),
+            _ => Err(error::unknown_step_kind(&ident)),
+        }
+    }
+}
+
+impl NextStepKind {
+    /// The macro which produced a step of this kind, as the relay log names it
+    fn origin(&self) -> &'static str {
+        match self {
+            NextStepKind::Traitdef { .. } => "traitdef",
+            NextStepKind::Typedef { .. } => "typedef",
+            NextStepKind::None => "coinduction",
         }
     }
 }
@@ -121,9 +300,12 @@ impl ToTokens for NextStepKind {
             NextStepKind::Typedef { predicates } => {
                 let predicate_tokens: Vec<_> = predicates
                     .iter()
-                    .map(|(params, c, cs)| {
+                    .map(|(params, c, cs, referred)| {
                         let param_tokens: Vec<_> = params.iter().collect();
-                        quote! { ([#(#param_tokens),*], #c, [#(#cs),*]) }
+                        let referred_tokens = referred
+                            .iter()
+                            .map(|(projection, ty)| quote! { (#projection, #ty) });
+                        quote! { ([#(#param_tokens),*], #c, [#(#cs),*], [#(#referred_tokens),*]) }
                     })
                     .collect();
                 tokens.extend(quote! {
@@ -139,30 +321,134 @@ impl ToTokens for NextStepKind {
     }
 }
 
+/// Expansion counters carried through the relay when `#[coinduction(stats)]` is given.
+#[derive(Clone, Default, Debug)]
+pub struct Stats {
+    pub reentries: usize,
+    pub rule_matches: usize,
+    pub max_working_list: usize,
+    pub protocol_tokens: usize,
+}
+
+impl Stats {
+    /// Render the counters together with the final graph sizes
+    fn report(&self, solvers: &[Option<Solver>]) -> String {
+        let (nodes, edges) = solvers
+            .iter()
+            .flatten()
+            .fold((0, 0), |(nodes, edges), solver| {
+                (
+                    nodes + solver.graph.len_nodes(),
+                    edges + solver.graph.len_edges(),
+                )
+            });
+        format!(
+            "reentries={} nodes={} edges={} rule_matches={} working_list={} protocol_tokens={}",
+            self.reentries,
+            nodes,
+            edges,
+            self.rule_matches,
+            self.max_working_list,
+            self.protocol_tokens
+        )
+    }
+}
+
+impl Parse for Stats {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let counters = crate::parse_list(input, "counters", <LitInt as Parse>::parse)?;
+        let mut counters = counters.iter().map(|lit| lit.base10_parse::<usize>());
+        let mut next = || counters.next().unwrap_or(Ok(0));
+        Ok(Stats {
+            reentries: next()?,
+            rule_matches: next()?,
+            max_working_list: next()?,
+            protocol_tokens: next()?,
+        })
+    }
+}
+
+impl ToTokens for Stats {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(quote! {
+            [
+                #{self.reentries},
+                #{self.rule_matches},
+                #{self.max_working_list},
+                #{self.protocol_tokens}
+            ]
+        });
+    }
+}
+
+/// Count token trees recursively, descending into groups
+fn count_tokens(tokens: TokenStream) -> usize {
+    tokens
+        .into_iter()
+        .map(|tt| match tt {
+            proc_macro2::TokenTree::Group(group) => 1 + count_tokens(group.stream()),
+            _ => 1,
+        })
+        .sum()
+}
+
+/// Parses the version which every call of the relay starts with, rejecting another release
+fn parse_version(input: ParseStream) -> syn::Result<()> {
+    let version: LitStr = input.parse()?;
+    if !compatible_version(&version.value(), PACKAGE_VERSION) {
+        return Err(error::version_mismatch(&version, PACKAGE_VERSION));
+    }
+    input.parse::<Token![,]>()?;
+    Ok(())
+}
+
+/// The input of `__next_step`: a step of the relay, or the `Check` of `#[check_traits]`, which
+/// only needs the trait macro to be generated by this release
+pub enum NextStepInput {
+    Step(Box<NextStepArgs>),
+    Check,
+}
+
+impl Parse for NextStepInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        mod kw {
+            syn::custom_keyword!(Check);
+        }
+
+        if input.peek3(kw::Check) {
+            parse_version(input)?;
+            input.parse::<kw::Check>()?;
+            Ok(NextStepInput::Check)
+        } else {
+            input.parse().map(Box::new).map(NextStepInput::Step)
+        }
+    }
+}
+
 pub struct NextStepArgs {
     pub kind: NextStepKind,
     pub working_list: VecDeque<Constraint>,
     pub coinduction: NoArgPath,
     pub working_traits: Vec<NoArgPath>,
     pub ignore_tys: HashSet<Ident>,
+    pub facts: Vec<Constraint>,
     pub solvers: Vec<Option<Solver>>,
     pub target_impls: Vec<ItemImpl>,
+    /// The scope of each of `target_impls`: an impl is rewritten only by the impls of its own
+    /// scope, such as the copies which are gated by the same combination of `cfg` predicates
+    pub scopes: Vec<usize>,
+    pub stats: Option<Stats>,
+    pub relay_log: Option<Vec<LitStr>>,
+    pub show_order: bool,
+    pub dump_graph: bool,
+    pub deep_args: bool,
+    pub loose_paths: bool,
+    pub allow_trivial: bool,
 }
 
 impl Parse for NextStepArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let version: LitStr = input.parse()?;
-
-        if version.value() != PACKAGE_VERSION {
-            abort!(
-                version,
-                "version mismatch: expected '{}', found '{}'",
-                PACKAGE_VERSION,
-                version.value()
-            );
-        }
-
-        input.parse::<Token![,]>()?;
+        parse_version(input)?;
 
         // Parse kind
         let kind: NextStepKind = input.parse()?;
@@ -170,10 +456,7 @@ impl Parse for NextStepArgs {
         input.parse::<Token![,]>()?;
 
         // Parse working_list
-        let working_list_content;
-        syn::bracketed!(working_list_content in input);
-        let working_list_vec: Punctuated<Constraint, Token![,]> =
-            working_list_content.parse_terminated(Constraint::parse, Token![,])?;
+        let working_list_vec = crate::parse_list(input, "constraints", Constraint::parse)?;
         let working_list: VecDeque<Constraint> = working_list_vec.into_iter().collect();
 
         input.parse::<Token![,]>()?;
@@ -186,24 +469,27 @@ impl Parse for NextStepArgs {
         input.parse::<Token![,]>()?;
 
         // Parse working_traits
-        let working_traits_content;
-        syn::bracketed!(working_traits_content in input);
-        let working_traits_vec: Punctuated<NoArgPath, Token![,]> =
-            working_traits_content.parse_terminated(NoArgPath::parse, Token![,])?;
+        let working_traits_vec = crate::parse_list(input, "trait paths", NoArgPath::parse)?;
         let working_traits: Vec<NoArgPath> = working_traits_vec.into_iter().collect();
 
         input.parse::<Token![,]>()?;
 
         // Parse ignore_tys
-        let ignore_tys_content;
-        syn::bracketed!(ignore_tys_content in input);
-        let ignore_tys_vec: Punctuated<Ident, Token![,]> =
-            ignore_tys_content.parse_terminated(Ident::parse, Token![,])?;
+        let ignore_tys_vec = crate::parse_list(input, "type identifiers", Ident::parse)?;
         let ignore_tys: HashSet<Ident> = ignore_tys_vec.into_iter().collect();
 
         input.parse::<Token![,]>()?;
 
+        // Parse facts
+        let facts = crate::parse_list(input, "facts", Constraint::parse)?;
+        let facts: Vec<Constraint> = facts.into_iter().collect();
+
+        input.parse::<Token![,]>()?;
+
         // Parse solvers
+        if !input.peek(syn::token::Bracket) {
+            return Err(error::expected_list(input.span(), "solvers"));
+        }
         let solvers_content;
         syn::bracketed!(solvers_content in input);
         let mut solvers = Vec::new();
@@ -218,13 +504,10 @@ impl Parse for NextStepArgs {
                 if ident == "None" {
                     solvers.push(None);
                 } else {
-                    return Err(syn::Error::new_spanned(
-                        ident,
-                        "Expected 'None' or a Solver",
-                    ));
+                    return Err(error::expected_none_or(ident.span(), "a solver"));
                 }
             } else {
-                return Err(solvers_content.error("Expected Solver or None"));
+                return Err(error::expected_none_or(solvers_content.span(), "a solver"));
             }
 
             if solvers_content.peek(Token![,]) {
@@ -235,20 +518,86 @@ impl Parse for NextStepArgs {
         input.parse::<Token![,]>()?;
 
         // Parse target_impls
-        let target_impls_bracket;
-        syn::bracketed!(target_impls_bracket in input);
-        let target_impls: Punctuated<ItemImpl, Token![,]> =
-            target_impls_bracket.parse_terminated(ItemImpl::parse, Token![,])?;
+        let target_impls = crate::parse_list(input, "impls", ItemImpl::parse)?;
         let target_impls: Vec<ItemImpl> = target_impls.into_iter().collect();
 
+        input.parse::<Token![,]>()?;
+
+        // Parse scopes
+        let scopes = crate::parse_list(input, "scopes", <LitInt as Parse>::parse)?
+            .iter()
+            .map(LitInt::base10_parse::<usize>)
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        input.parse::<Token![,]>()?;
+
+        // Parse stats
+        let stats = if input.peek(syn::token::Bracket) {
+            Some(input.parse::<Stats>()?)
+        } else {
+            let ident: syn::Ident = input.parse()?;
+            if ident != "None" {
+                return Err(error::expected_none_or(ident.span(), "stats"));
+            }
+            None
+        };
+
+        input.parse::<Token![,]>()?;
+
+        // Parse relay_log
+        let relay_log = if input.peek(syn::token::Bracket) {
+            let relay_log = crate::parse_list(input, "relay calls", <LitStr as Parse>::parse)?;
+            Some(relay_log.into_iter().collect())
+        } else {
+            let ident: syn::Ident = input.parse()?;
+            if ident != "None" {
+                return Err(error::expected_none_or(ident.span(), "a relay log"));
+            }
+            None
+        };
+
+        input.parse::<Token![,]>()?;
+
+        // Parse show_order
+        let show_order = input.parse::<LitBool>()?.value;
+
+        input.parse::<Token![,]>()?;
+
+        // Parse dump_graph
+        let dump_graph = input.parse::<LitBool>()?.value;
+
+        input.parse::<Token![,]>()?;
+
+        // Parse deep_args
+        let deep_args = input.parse::<LitBool>()?.value;
+
+        input.parse::<Token![,]>()?;
+
+        // Parse loose_paths
+        let loose_paths = input.parse::<LitBool>()?.value;
+
+        input.parse::<Token![,]>()?;
+
+        // Parse allow_trivial
+        let allow_trivial = input.parse::<LitBool>()?.value;
+
         Ok(NextStepArgs {
             kind,
             working_list,
             coinduction,
             working_traits,
             ignore_tys,
+            facts,
             solvers,
             target_impls,
+            scopes,
+            stats,
+            relay_log,
+            show_order,
+            dump_graph,
+            deep_args,
+            loose_paths,
+            allow_trivial,
         })
     }
 }
@@ -259,6 +608,7 @@ impl ToTokens for NextStepArgs {
         let working_list: Vec<_> = self.working_list.iter().collect();
         let working_traits: Vec<_> = self.working_traits.iter().collect();
         let ignore_tys: Vec<_> = self.ignore_tys.iter().collect();
+        let facts = &self.facts;
         let solver_tokens: Vec<_> = self
             .solvers
             .iter()
@@ -269,6 +619,15 @@ impl ToTokens for NextStepArgs {
             .collect();
         let coinduction = &self.coinduction;
         let target_impls = &self.target_impls;
+        let scopes = &self.scopes;
+        let stats = match &self.stats {
+            Some(stats) => quote! { #stats },
+            None => quote! { None },
+        };
+        let relay_log = match &self.relay_log {
+            Some(relay_log) => quote! { [#(#relay_log),*] },
+            None => quote! { None },
+        };
 
         tokens.extend(quote! {
             #PACKAGE_VERSION,
@@ -277,27 +636,134 @@ impl ToTokens for NextStepArgs {
             {#coinduction},
             [#(#working_traits),*],
             [#(#ignore_tys),*],
+            [#(#facts),*],
             [#(#solver_tokens),*],
-            [#(#target_impls),*]
+            [#(#target_impls),*],
+            [#(#scopes),*],
+            #stats,
+            #relay_log,
+            #{self.show_order},
+            #{self.dump_graph},
+            #{self.deep_args},
+            #{self.loose_paths},
+            #{self.allow_trivial}
         });
     }
 }
 
+/// The entry of the `dump_relay` log for a step of `kind` which relays `target` to
+/// `macro_path`; the arguments of the call are left out, as they repeat the whole module
+fn relay_entry(kind: &NextStepKind, macro_path: &NoArgPath, target: &Constraint) -> String {
+    format!(
+        "after the {} step: `{}!` for `{}`",
+        kind.origin(),
+        quote!(#macro_path).to_string().replace(' ', ""),
+        quote!(#target)
+    )
+}
+
+/// The `cfg` attributes shared by all target impls; the relay and the items emitted next to the
+/// impls are gated by them, so that a resolution for an inactive configuration is dropped entirely
+fn shared_cfgs(target_impls: &[ItemImpl]) -> Vec<Attribute> {
+    let Some((first, rest)) = target_impls.split_first() else {
+        return Vec::new();
+    };
+    first
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .filter(|attr| rest.iter().all(|item_impl| item_impl.attrs.contains(attr)))
+        .cloned()
+        .collect()
+}
+
+/// Warns that none of the impls lies on a cycle, so that the attribute changes nothing, which is
+/// often a bound naming another trait or type than intended; the warning points at the first
+/// impl's trait
+fn warn_if_trivial(args: &NextStepArgs) {
+    if args.allow_trivial || args.solvers.iter().flatten().any(Solver::has_cycle) {
+        return;
+    }
+    let traits = args
+        .working_traits
+        .iter()
+        .map(|path| format!("`{}`", quote!(#path).to_string().replace(' ', "")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let span = args
+        .target_impls
+        .iter()
+        .find_map(|item_impl| item_impl.trait_.as_ref().map(|(_, path, _)| path.span()))
+        .unwrap_or_else(Span::call_site);
+    proc_macro_error::emit_warning!(
+        span,
+        "coinduction: no impl of {} in this module lies on a cycle, so `#[coinduction]` has nothing to resolve",
+        if traits.is_empty() { "the traits" } else { &traits };
+        help = "check the bounds, or add `allow_trivial` if this is intended"
+    );
+}
+
+/// Replaces the projections of `referred` in `constraint` by the types they refer to, with
+/// `substitute` applied to both as it has been to `constraint`
+fn refer_types(
+    constraint: &Constraint,
+    referred: &[ReferredType],
+    substitute: &Substitute,
+) -> Constraint {
+    struct Referrer(Vec<ReferredType>);
+
+    impl VisitMut for Referrer {
+        fn visit_type_mut(&mut self, ty: &mut Type) {
+            let mut structural = ty.clone();
+            GroupStripper.visit_type_mut(&mut structural);
+            match self
+                .0
+                .iter()
+                .find(|(projection, _)| *projection == structural)
+            {
+                Some((_, referred)) => *ty = referred.clone(),
+                None => visit_mut::visit_type_mut(self, ty),
+            }
+        }
+    }
+
+    let mut referrer = Referrer(
+        referred
+            .iter()
+            .map(|(projection, ty)| {
+                let (mut projection, mut ty) = (projection.clone(), ty.clone());
+                projection.replace(substitute);
+                GroupStripper.visit_type_mut(&mut projection);
+                ty.replace(substitute);
+                (projection, ty)
+            })
+            .collect(),
+    );
+    let mut constraint = constraint.clone();
+    referrer.visit_type_mut(&mut constraint.typ);
+    referrer.visit_path_mut(&mut constraint.trait_path);
+    constraint
+}
+
 pub fn next_step(mut args: NextStepArgs) -> TokenStream {
     if let Some(Some(target)) =
-        (&args.kind != &NextStepKind::None).then(|| args.working_list.pop_front())
+        (args.kind != NextStepKind::None).then(|| args.working_list.pop_front())
     {
-        for solver in args.solvers.iter_mut().filter_map(Option::as_mut) {
+        if let Some(stats) = &mut args.stats {
+            stats.reentries += 1;
+        }
+        let rules_by_scope = scoped_rewrite_rules(
+            &args.target_impls,
+            &args.scopes,
+            &args.working_traits.iter().cloned().collect(),
+            args.loose_paths,
+        );
+        for (solver, scope) in args.solvers.iter_mut().zip(&args.scopes) {
+            let Some(solver) = solver else { continue };
+            let rewrite_rules = &rules_by_scope[scope];
             solver.graph.scope_mut(|mut graph| {
-                let root_ix_opt = graph
-                    .node_pairs()
-                    .find(|(_, node)| {
-                        template_quote::quote!(#node).to_string()
-                            == template_quote::quote!(#target).to_string()
-                    })
-                    .map(|(ix, _)| ix);
-
-                if let Some(root_ix) = root_ix_opt {
+                let mut index = ConstraintIndex::new(&graph);
+                if let Some(root_ix) = index.find(&target) {
                     let dep_constraints = match &args.kind {
                         NextStepKind::Traitdef {
                             appending_constraints,
@@ -310,6 +776,10 @@ pub fn next_step(mut args: NextStepArgs) -> TokenStream {
                                             Constraint {
                                                 typ: pred.bounded_ty.clone(),
                                                 trait_path: trait_bound.path.clone(),
+                                                lifetimes: trait_bound
+                                                    .lifetimes
+                                                    .clone()
+                                                    .or_else(|| pred.lifetimes.clone()),
                                             },
                                             HashSet::new(),
                                         )
@@ -319,44 +789,111 @@ pub fn next_step(mut args: NextStepArgs) -> TokenStream {
                                 })
                             })
                             .collect::<Vec<_>>(),
-                        NextStepKind::Typedef { predicates } => predicates
-                            .iter()
-                            .map(|(params, replacing, new_constraints)| {
-                                let mut replacing = replacing.clone();
-                                match (&mut replacing.typ, &target.typ) {
-                                    (
-                                        Type::Path(TypePath { qself: None, path: Path { leading_colon: None, segments } }),
-                                        Type::Path(TypePath{ path: Path { segments: target_segments, ..}, ..})
-                                    ) if segments.len() == 1 => {
-                                        let mut new_segments = target_segments.clone();
-                                        new_segments.last_mut().unwrap().arguments = segments.last().unwrap().arguments.clone();
-                                        *segments = new_segments
-                                    },
-                                    _ => unreachable!(),
-                                }
-                                if &replacing.trait_path.segments.last().unwrap().ident == &target.trait_path.segments.last().unwrap().ident {
-                                    let mut new_path = target.trait_path.clone();
-                                    new_path.segments.last_mut().unwrap().arguments = replacing.trait_path.segments.last().unwrap().arguments.clone();
-                                }
-                                replacing.matches(&target, &params).map(|substitute| {
-                                    new_constraints.iter().map(move |new_constraint0| {
-                                        let mut new_constraint = new_constraint0.clone();
-                                        new_constraint.replace(&substitute);
-                                        (new_constraint, params.clone())
+                        NextStepKind::Typedef { predicates } => {
+                            let target_params = solver.generic_params.iter().cloned().collect();
+                            let mut near_misses = Vec::new();
+                            let mut applied = false;
+                            let dep_constraints = predicates
+                                .iter()
+                                .filter_map(|(params, replacing, new_constraints, referred)| {
+                                    let replacing = match (&replacing.typ, &target.typ) {
+                                        // The head may be qualified differently from the target, e.g.
+                                        // `self::Node`, so only its last segment's arguments are kept
+                                        (
+                                            Type::Path(TypePath { qself: None, path: Path { segments, .. } }),
+                                            Type::Path(TypePath{ path: Path { segments: target_segments, ..}, ..})
+                                        ) => {
+                                            let mut new_segments = target_segments.clone();
+                                            new_segments.last_mut()?.arguments = segments.last()?.arguments.clone();
+                                            replacing.clone().with_type(Type::Path(TypePath {
+                                                qself: None,
+                                                path: Path { leading_colon: None, segments: new_segments },
+                                            }))
+                                        },
+                                        // A typedef only relays constraints on its own path types
+                                        _ => return None,
+                                    };
+                                    if let Some(stats) = &mut args.stats {
+                                        stats.rule_matches += 1;
+                                    }
+                                    // The rule's params may share names with the impl's params
+                                    // which the target still holds, and must not specialize them
+                                    let substitute = match unify(&replacing, &target, params, &target_params, args.loose_paths) {
+                                        Ok((substitute, target_bindings)) if target_bindings.is_empty() => {
+                                            applied = true;
+                                            Some(substitute)
+                                        }
+                                        Ok(_) => {
+                                            let error = MatchError::new(
+                                                &replacing,
+                                                &target,
+                                                "the rule only applies to instances of the constraint",
+                                            );
+                                            near_misses.push((replacing.clone(), error));
+                                            None
+                                        }
+                                        Err(error) => {
+                                            abort_if_unsupported(&error);
+                                            near_misses.push((replacing.clone(), error));
+                                            None
+                                        }
+                                    };
+                                    if substitute.is_none() {
+                                        if let Some((param, arg)) = cyclic_binding(&replacing, &target, params, args.loose_paths) {
+                                            abort!(
+                                                replacing,
+                                                "the rule for `{}` would bind `{}` to `{}`, which contains it",
+                                                template_quote::quote!(#replacing),
+                                                template_quote::quote!(#param),
+                                                template_quote::quote!(#arg);
+                                                note = "applying it to `{}` would grow the constraint on every step", template_quote::quote!(#target)
+                                            );
+                                        }
+                                    }
+                                    let index = &index;
+                                    substitute.map(|substitute| {
+                                        new_constraints.iter().map(move |new_constraint0| {
+                                            let mut new_constraint = new_constraint0.clone();
+                                            new_constraint.replace(&substitute);
+                                            // Spelled with the types it refers to, the constraint
+                                            // may be one the module already has, e.g. when the
+                                            // trait argument is a composite like `Wrapper<T>`
+                                            let referring =
+                                                refer_types(&new_constraint, referred, &substitute);
+                                            if index.find(&referring).is_some() {
+                                                new_constraint = referring;
+                                            }
+                                            (new_constraint, params.clone())
+                                        })
                                     })
                                 })
-                            })
-                            .flatten()
-                            .flatten()
-                            .collect::<Vec<_>>(),
-                        NextStepKind::None => unreachable!(),
+                                .flatten()
+                                .collect::<Vec<_>>();
+                            if !applied {
+                                trace_near_misses(&target, &near_misses);
+                            }
+                            dep_constraints
+                        }
+                        // Excluded before the target is popped
+                        NextStepKind::None => unreachable!("no relay kind to expand with"),
                     };
 
-                    for (new_constraint, additional_params) in dep_constraints {
-                        let existing_ix_opt = graph
-                            .node_pairs()
-                            .find(|(_, c)| *c == &new_constraint)
-                            .map(|(ix, _)| ix);
+                    // Module types reached through the relay are rewritten by the module's own
+                    // impls, so that cycles running through foreign types close here as well
+                    let mut pending = dep_constraints
+                        .into_iter()
+                        .map(|(new_constraint, additional_params)| {
+                            (root_ix, new_constraint, additional_params)
+                        })
+                        .collect::<VecDeque<_>>();
+                    let mut expansions = 0;
+                    while let Some((parent_ix, new_constraint, additional_params)) =
+                        pending.pop_front()
+                    {
+                        let existing_ix_opt = index.find(&new_constraint);
+                        let target_ix = existing_ix_opt
+                            .unwrap_or_else(|| index.add(&mut graph, new_constraint.clone()));
+                        add_dependency(&mut graph, parent_ix, target_ix);
                         if existing_ix_opt.is_none() {
                             let not_in_working_list = !args.working_list.contains(&new_constraint);
 
@@ -368,48 +905,97 @@ pub fn next_step(mut args: NextStepArgs) -> TokenStream {
                                     |param| {
                                         if let GenericParam::Type(tp) = param {
                                             let param_str = template_quote::quote!(#tp).to_string();
-                                            &typ_str == &param_str
+                                            typ_str == param_str
                                         } else {
                                             false
                                         }
                                     },
                                 );
 
-                            let trait_in_working_traits = args.working_traits.iter().any(|wt| {
-                                wt == &crate::remove_path_args(&new_constraint.trait_path)
-                            });
+                            let trait_in_working_traits =
+                                crate::names_trait(&args.working_traits, &new_constraint.trait_path, args.loose_paths);
 
                             let is_ignored = matches!(
                                 crate::unwrap_type_group(new_constraint.typ.clone()),
                                 Type::Path(TypePath { qself: None, path })
-                                if path.segments.len() == 1 && args.ignore_tys.contains(&path.segments[0].ident)
+                                if path.leading_colon.is_none() && path.segments.first().is_some_and(|segment| args.ignore_tys.contains(&segment.ident))
+                            );
+
+                            let is_fact = args.facts.contains(&new_constraint);
+
+                            let is_projection = crate::is_qualified_projection(
+                                &crate::unwrap_type_group(new_constraint.typ.clone()),
                             );
 
-                            if not_in_working_list
+                            let arguments = (args.deep_args
+                                && trait_in_working_traits
+                                && !is_fact
+                                && !is_projection
+                                && !is_generic)
+                                .then(|| {
+                                    crate::coinduction::argument_constraints(
+                                        &new_constraint,
+                                        &args.ignore_tys,
+                                    )
+                                })
+                                .flatten();
+                            if let Some(arguments) = arguments {
+                                pending.extend(arguments.into_iter().map(|argument| {
+                                    (target_ix, argument, additional_params.clone())
+                                }));
+                            } else if not_in_working_list
+                                && !is_fact
+                                && !is_projection
                                 && !is_generic
                                 && trait_in_working_traits
                                 && !is_ignored
                             {
                                 args.working_list.push_back(new_constraint.clone());
                             }
-                        }
-                        let target_ix =
-                            existing_ix_opt.unwrap_or_else(|| graph.add_node(new_constraint));
-                        let edge_exists = graph
-                            .outgoing_edge_indices(root_ix)
-                            .any(|edge_ix| graph.endpoints(edge_ix)[1] == target_ix);
-                        if !edge_exists {
-                            graph.add_edge((), root_ix, target_ix);
+
+                            if is_ignored && trait_in_working_traits && !is_fact {
+                                expansions += 1;
+                                if expansions > MAX_EXPANSIONS {
+                                    abort_runaway_expansion(&new_constraint, Vec::new());
+                                }
+                                if let Some(body) = apply_rewrite_rules(
+                                    rewrite_rules,
+                                    &new_constraint,
+                                    &mut args.stats,
+                                    args.loose_paths,
+                                ) {
+                                    pending.extend(
+                                        body.into_iter()
+                                            .map(|rule| (target_ix, rule, HashSet::new())),
+                                    );
+                                }
+                            }
                         }
                     }
                 }
             });
+            solver.debug_assert_consistent();
         }
     }
+    if let Some(stats) = &mut args.stats {
+        stats.max_working_list = stats.max_working_list.max(args.working_list.len());
+    }
+    let kind = std::mem::replace(&mut args.kind, NextStepKind::None);
     if let Some(target) = args.working_list.front() {
-        args.kind = NextStepKind::None;
         let macro_path = crate::remove_path_args(&target.trait_path);
+        if args.stats.is_some() {
+            let emitted = count_tokens(quote!(#args));
+            if let Some(stats) = &mut args.stats {
+                stats.protocol_tokens += emitted;
+            }
+        }
+        if let Some(relay_log) = &mut args.relay_log {
+            let entry = relay_entry(&kind, &macro_path, target);
+            relay_log.push(LitStr::new(&entry, Span::call_site()));
+        }
+        let cfgs = shared_cfgs(&args.target_impls);
         quote! {
+            #(#cfgs)*
             #macro_path ! { #args }
         }
     } else {
@@ -419,41 +1005,139 @@ pub fn next_step(mut args: NextStepArgs) -> TokenStream {
             .zip(&args.solvers)
             .filter_map(|(item_impl, solver)| solver.as_ref().map(|solver| (item_impl, solver)))
         {
-            solver.graph.scope(|graph| {
-                let loops = gotgraph::algo::tarjan(graph)
-                    .filter_map(|lp| {
-                        (lp.len() > 1).then_some(
-                            lp.iter()
-                                .map(|ix| (graph.node(*ix), *ix))
-                                .collect::<HashMap<_, _>>(),
-                        )
-                    })
-                    .collect::<Vec<_>>();
-                Constraint::map_generics(&mut impl_item.generics, |constraint| {
-                    if let Some(the_loop) = loops.iter().find(|lp| lp.contains_key(&constraint)) {
-                        let dependencies = the_loop
-                            .values()
-                            .map(|ix| {
-                                graph
-                                    .outgoing_edge_indices(*ix)
-                                    .map(|eix| graph.endpoints(eix)[1])
-                            })
-                            .flatten()
-                            .collect::<HashSet<_>>();
-                        dependencies
-                            .difference(&the_loop.values().cloned().collect())
-                            .map(|ix| graph.node(*ix).clone())
-                            .collect()
+            solver.break_cycles(&mut impl_item.generics, &impl_item.self_ty);
+        }
+        let cfgs = shared_cfgs(&target_impls);
+        warn_if_trivial(&args);
+        let stats = args.stats.as_ref().map(|stats| {
+            let report = stats.report(&args.solvers);
+            trace("stats", [report.clone()]);
+            report
+        });
+        let order = args.show_order.then(|| {
+            let order: Vec<String> = args
+                .solvers
+                .iter()
+                .flatten()
+                .flat_map(Solver::condensation_order)
+                .map(|component| {
+                    let members = component
+                        .iter()
+                        .map(|constraint| quote!(#constraint).to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    if component.len() > 1 {
+                        format!("cycle({})", members)
                     } else {
-                        vec![constraint]
+                        members
                     }
-                });
-            });
+                })
+                .collect();
+            trace("resolution order", order.clone());
+            order
+        });
+        let graph = args.dump_graph.then(|| {
+            let graph = args
+                .solvers
+                .iter()
+                .zip(&args.target_impls)
+                .enumerate()
+                .filter_map(|(i, (solver, item_impl))| {
+                    let solver = solver.as_ref()?;
+                    let self_ty = &item_impl.self_ty;
+                    Some(format!(
+                        "// {}\n{}",
+                        quote!(#self_ty),
+                        solver.to_dot(&format!("solver{}", i))
+                    ))
+                })
+                .collect::<String>();
+            trace("constraint graph", [graph.clone()]);
+            graph
+        });
+        if let Some(relay_log) = &args.relay_log {
+            emit_notes("relay calls", relay_log.iter().map(LitStr::value));
         }
         quote! {
             #(for content in target_impls) {
                 #content
             }
+            #(if let Some(report) = &stats) {
+                #(#cfgs)*
+                #[doc(hidden)]
+                #[allow(dead_code)]
+                pub const __COINDUCTION_STATS: &str = #report;
+            }
+            #(if let Some(order) = &order) {
+                #(#cfgs)*
+                #[doc(hidden)]
+                #[allow(dead_code)]
+                pub const __COINDUCTION_ORDER: &[&str] = &[#(#order),*];
+            }
+            #(if let Some(graph) = &graph) {
+                #(#cfgs)*
+                #[doc(hidden)]
+                #[allow(dead_code)]
+                pub const __COINDUCTION_GRAPH: &str = #graph;
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compatible_versions() {
+        assert!(compatible_version("0.1.0", "0.1.0"));
+        assert!(compatible_version("0.1.0+ci", "0.1.0"));
+        assert!(compatible_version("0.1.0-dev", "0.1.0"));
+        assert!(compatible_version("0.1.0", "0.1.0-dev+build.5"));
+    }
+
+    #[test]
+    fn test_relay_log_names_the_trait_macros() {
+        let args = NextStepArgs {
+            kind: NextStepKind::None,
+            working_list: [
+                parse_quote!((Leaf, Leaf): traitdef::LocalTrait),
+                parse_quote!(Leaf: TestTrait),
+            ]
+            .into(),
+            coinduction: crate::remove_path_args(&parse_quote!(::coinduction)),
+            working_traits: vec![
+                crate::remove_path_args(&parse_quote!(traitdef::LocalTrait)),
+                crate::remove_path_args(&parse_quote!(TestTrait)),
+            ],
+            ignore_tys: HashSet::new(),
+            facts: Vec::new(),
+            solvers: Vec::new(),
+            target_impls: Vec::new(),
+            scopes: Vec::new(),
+            stats: None,
+            relay_log: Some(Vec::new()),
+            show_order: false,
+            dump_graph: false,
+            deep_args: false,
+            loose_paths: false,
+            allow_trivial: false,
+        };
+        let relayed: ItemMacro = parse2(next_step(args)).unwrap();
+        assert_eq!(relayed.mac.path, parse_quote!(traitdef::LocalTrait));
+        let args: NextStepArgs = relayed.mac.parse_body().unwrap();
+        let relay_log: Vec<_> = args.relay_log.unwrap().iter().map(LitStr::value).collect();
+        assert_eq!(
+            relay_log,
+            ["after the coinduction step: `traitdef::LocalTrait!` for `(Leaf , Leaf) : traitdef :: LocalTrait`"]
+        );
+    }
+
+    #[test]
+    fn test_incompatible_versions() {
+        assert!(!compatible_version("0.1.0", "0.2.0"));
+        assert!(!compatible_version("1.1.0", "0.1.0"));
+        assert!(!compatible_version("0.1.1", "0.1.0"));
+        assert!(!compatible_version("0.1", "0.1.0"));
+    }
+}
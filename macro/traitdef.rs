@@ -1,4 +1,5 @@
 use proc_macro2::TokenStream;
+use syn::ext::IdentExt;
 use syn::*;
 use syn::{parse::Parse, parse::ParseStream, ItemTrait};
 use template_quote::quote;
@@ -89,10 +90,48 @@ fn remove_matcher_kinds(input: TokenStream) -> TokenStream {
     result
 }
 
+fn name_wildcards(input: TokenStream, counter: &mut usize) -> TokenStream {
+    // Replace each `_` in a rule pattern with a fresh `$__wildcard_N:ty`, so it accepts any type;
+    // the `_` of an anonymous lifetime `'_` is kept
+    use proc_macro2::{Punct, Spacing, TokenTree};
+
+    let mut result = TokenStream::new();
+    let mut after_dollar = false;
+    let mut after_quote = false;
+
+    for token in input {
+        let is_dollar = matches!(&token, TokenTree::Punct(p) if p.as_char() == '$');
+        let is_quote = matches!(&token, TokenTree::Punct(p) if p.as_char() == '\'');
+        match token {
+            TokenTree::Ident(ref ident) if ident == "_" && !after_dollar && !after_quote => {
+                let name = syn::Ident::new(&format!("__wildcard_{}", counter), ident.span());
+                *counter += 1;
+                result.extend([
+                    TokenTree::Punct(Punct::new('$', Spacing::Alone)),
+                    TokenTree::Ident(name),
+                    TokenTree::Punct(Punct::new(':', Spacing::Alone)),
+                    TokenTree::Ident(syn::Ident::new("ty", ident.span())),
+                ]);
+            }
+            TokenTree::Group(group) => {
+                let stream = name_wildcards(group.stream(), counter);
+                let mut new_group = proc_macro2::Group::new(group.delimiter(), stream);
+                new_group.set_span(group.span());
+                result.extend(Some(TokenTree::Group(new_group)));
+            }
+            _ => result.extend(Some(token)),
+        }
+        after_dollar = is_dollar;
+        after_quote = is_quote;
+    }
+
+    result
+}
+
 pub fn traitdef(item: ItemTrait, args: TraitDefArgs) -> TokenStream {
     let random_suffix = crate::get_random();
     let temporal_mac_name = syn::Ident::new(
-        &format!("__{}_temporal_{}", &item.ident, random_suffix),
+        &format!("__{}_temporal_{}", item.ident.unraw(), random_suffix),
         item.ident.span(),
     );
     let crate_version = env!("CARGO_PKG_VERSION");
@@ -103,7 +142,12 @@ pub fn traitdef(item: ItemTrait, args: TraitDefArgs) -> TokenStream {
         #[doc(hidden)]
         #[macro_export]
         macro_rules! #temporal_mac_name {
-            #(for (pattern, pattern_converted, constraints) in args.rules.iter().map(|(pattern, constraints)| (pattern.clone(), remove_matcher_kinds(pattern.clone()), constraints))) {
+            // Answers `#[check_traits]` of any release, whose `__next_step` checks that it is the
+            // release which generated this macro
+            ($version:literal, Check, {$($coinduction:tt)+}) => {
+                $($coinduction)+::__next_step! { #crate_version, Check }
+            };
+            #(for (pattern, pattern_converted, constraints) in args.rules.iter().map(|(pattern, constraints)| (name_wildcards(pattern.clone(), &mut 0), constraints)).map(|(pattern, constraints)| (pattern.clone(), remove_matcher_kinds(pattern), constraints))) {
                 (#crate_version, None, [#pattern  :$($wt:tt)*], {$($coinduction:tt)+}, $($t:tt)*) => {
                     $($coinduction)+::__next_step ! {
                         #crate_version, Traitdef {
@@ -1,6 +1,7 @@
 use proc_macro2::{Span, TokenStream};
 use proc_macro_error::*;
 use std::collections::{HashMap, HashSet};
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
@@ -8,6 +9,7 @@ use syn::*;
 use template_quote::quote;
 use type_leak::{Leaker, NotInternableError};
 
+use crate::coinduction::{type_aliases, AliasExpander};
 use crate::remove_path_args;
 use crate::solver::Constraint;
 use crate::NoArgPath;
@@ -25,35 +27,24 @@ fn randomize_generic_params(
     let rename_map: HashMap<Ident, Ident> = generics
         .params
         .iter_mut()
-        .filter_map(|param| {
-            match param {
-                GenericParam::Type(tp) => {
-                    let old = tp.ident.clone();
-                    let new = Ident::new(
-                        &format!("__{}_{}_{}", old, ix0, random_suffix),
-                        old.span(),
-                    );
-                    tp.ident = new.clone();
-                    Some((old, new))
-                }
-                GenericParam::Lifetime(lp) => {
-                    let old = lp.lifetime.ident.clone();
-                    let new = Ident::new(
-                        &format!("__{}_{}_{}", old, ix0, random_suffix),
-                        old.span(),
-                    );
-                    lp.lifetime.ident = new.clone();
-                    Some((old, new))
-                }
-                GenericParam::Const(cp) => {
-                    let old = cp.ident.clone();
-                    let new = Ident::new(
-                        &format!("__{}_{}_{}", old, ix0, random_suffix),
-                        old.span(),
-                    );
-                    cp.ident = new.clone();
-                    Some((old, new))
-                }
+        .map(|param| match param {
+            GenericParam::Type(tp) => {
+                let old = tp.ident.clone();
+                let new = Ident::new(&format!("__{}_{}_{}", old, ix0, random_suffix), old.span());
+                tp.ident = new.clone();
+                (old, new)
+            }
+            GenericParam::Lifetime(lp) => {
+                let old = lp.lifetime.ident.clone();
+                let new = Ident::new(&format!("__{}_{}_{}", old, ix0, random_suffix), old.span());
+                lp.lifetime.ident = new.clone();
+                (old, new)
+            }
+            GenericParam::Const(cp) => {
+                let old = cp.ident.clone();
+                let new = Ident::new(&format!("__{}_{}_{}", old, ix0, random_suffix), old.span());
+                cp.ident = new.clone();
+                (old, new)
             }
         })
         .collect();
@@ -85,7 +76,10 @@ fn randomize_generic_params(
 
         fn visit_expr_mut(&mut self, expr: &mut Expr) {
             syn::visit_mut::visit_expr_mut(self, expr);
-            if let Expr::Path(ExprPath { qself: None, path, .. }) = expr {
+            if let Expr::Path(ExprPath {
+                qself: None, path, ..
+            }) = expr
+            {
                 if path.leading_colon.is_none()
                     && path.segments.len() == 1
                     && matches!(path.segments[0].arguments, PathArguments::None)
@@ -131,6 +125,32 @@ fn randomize_generic_params(
     rename_map
 }
 
+/// Replaces `crate` with `$crate`, as the macro the predicates are emitted in is exported and may
+/// be invoked from other crates
+fn dollar_crate(tokens: TokenStream) -> TokenStream {
+    use proc_macro2::{Group, Punct, Spacing, TokenTree};
+    tokens
+        .into_iter()
+        .flat_map(|token| match token {
+            TokenTree::Ident(ident) if ident == "crate" => {
+                let mut dollar = Punct::new('$', Spacing::Alone);
+                dollar.set_span(ident.span());
+                vec![TokenTree::Punct(dollar), TokenTree::Ident(ident)]
+            }
+            TokenTree::Group(group) => {
+                let mut new_group = Group::new(group.delimiter(), dollar_crate(group.stream()));
+                new_group.set_span(group.span());
+                vec![TokenTree::Group(new_group)]
+            }
+            token => vec![token],
+        })
+        .collect()
+}
+
+/// Rewrite rules collected per type identifier: `(generics, head, children)`
+type TypeImplTable =
+    HashMap<Ident, Vec<(Generics, Constraint, Vec<Constraint>, Vec<(Type, Type)>)>>;
+
 mod kw {
     syn::custom_keyword!(marker);
     syn::custom_keyword!(coinduction);
@@ -192,7 +212,7 @@ pub fn typedef(module: ItemMod, args: TypeDefArgs) -> TokenStream {
         .into_iter()
         .flatten()
         .collect::<Vec<_>>();
-    let working_traits: HashSet<_> = if args.paths.len() > 0 {
+    let working_traits: HashSet<_> = if !args.paths.is_empty() {
         args.paths.into_iter().collect()
     } else {
         content
@@ -213,9 +233,10 @@ pub fn typedef(module: ItemMod, args: TypeDefArgs) -> TokenStream {
             _ => None,
         })
         .collect::<HashMap<_, _>>();
+    let aliases = type_aliases(content.iter().copied());
     let (_typeref_impl, type_impl_table) = content.iter().enumerate().fold(
         Default::default(),
-        |(mut typeref_impl, mut acc): (TokenStream, HashMap<Ident, Vec<(Generics, Constraint, Vec<Constraint>)>>),
+        |(mut typeref_impl, mut acc): (TokenStream, TypeImplTable),
          (ix0, item)| {
             if let Item::Impl(ItemImpl {
                 trait_: Some((_, trait_path, _)),
@@ -225,23 +246,28 @@ pub fn typedef(module: ItemMod, args: TypeDefArgs) -> TokenStream {
             }) = item
             {
                 match self_ty.as_ref() {
-                    Type::Path(TypePath {
-                        qself: None,
-                        path:
-                            Path {
-                                leading_colon: None,
-                                segments,
-                            },
-                    }) if segments.len() == 1
-                        && working_traits.contains(&remove_path_args(trait_path)) =>
+                    Type::Path(TypePath { qself: None, path })
+                        if crate::names_trait(&working_traits, trait_path, false) =>
                     {
-                        // Extract type identifier before modifications
-                        let type_ident = segments[0].ident.clone();
+                        // The macro is named after the type, however its path is spelled, e.g.
+                        // `self::Node` or `crate::nodes::Node`
+                        let type_ident = path.segments.last().unwrap().ident.clone();
 
                         // Clone and randomize generic parameter names to avoid collisions
                         let mut renamed_generics = generics.clone();
                         let mut renamed_self_ty = self_ty.as_ref().clone();
                         let mut renamed_trait_path = trait_path.clone();
+                        // The macro stays named after the self type, so only the bounds and the
+                        // trait arguments are written in terms of the aliased types
+                        {
+                            use syn::visit_mut::VisitMut;
+                            let mut expander = AliasExpander {
+                                aliases: &aliases,
+                                depth: 0,
+                            };
+                            expander.visit_generics_mut(&mut renamed_generics);
+                            expander.visit_path_mut(&mut renamed_trait_path);
+                        }
                         let _rename_map = randomize_generic_params(
                             &mut renamed_generics,
                             &mut renamed_self_ty,
@@ -270,13 +296,16 @@ pub fn typedef(module: ItemMod, args: TypeDefArgs) -> TokenStream {
                         let referrer = leaker.finish();
                         let typeref_arg = type_leak::encode_generics_params_to_ty(&renamed_generics.params);
 
-                        let mut constraint = Constraint {
-                            typ: renamed_self_ty.clone(),
-                            trait_path: renamed_trait_path.clone()
-                        };
+                        // The head is only matched against, never emitted, so it keeps the types
+                        // which the children refer to through the marker
+                        let constraint =
+                            Constraint::new(renamed_self_ty.clone(), renamed_trait_path.clone());
                         let mut children = Vec::new();
+                        let mut referred = Vec::new();
                         Constraint::map_generics(&mut renamed_generics.clone(), |c| {
-                            children.push(c.clone());
+                            let mut child = c.clone();
+                            child.replace_self(&renamed_self_ty);
+                            children.push(child);
                             vec![c]
                         });
 
@@ -288,14 +317,18 @@ pub fn typedef(module: ItemMod, args: TypeDefArgs) -> TokenStream {
                                     hint = first.span() => "or make this path absolute";
                                 );
                             });
-                            let mut visitor = referrer.clone().into_visitor(|_ty, ix| {
+                            let projection = |ix: usize| -> Type {
                                 parse2(quote!(
                                         <#marker as #{&args.coinduction}::TypeRef<#random_suffix, #ix0, #ix, #typeref_arg>>::Type
                                 )).unwrap()
-                            });
+                            };
+                            referred = referrer
+                                .iter()
+                                .enumerate()
+                                .map(|(ix, ty)| (projection(ix), ty.clone()))
+                                .collect();
+                            let mut visitor = referrer.clone().into_visitor(|_ty, ix| projection(ix));
                             use syn::visit_mut::VisitMut;
-                            visitor.visit_type_mut(&mut constraint.typ);
-                            visitor.visit_path_mut(&mut constraint.trait_path);
                             typeref_impl = quote!(
                                 #typeref_impl
                                 #(for (ix, ty) in referrer.iter().enumerate()) {
@@ -314,7 +347,8 @@ pub fn typedef(module: ItemMod, args: TypeDefArgs) -> TokenStream {
                         acc.entry(type_ident).or_default().push((
                             renamed_generics.clone(),
                             constraint,
-                            children
+                            children,
+                            referred,
                         ));
                     }
                     _ => (),
@@ -327,11 +361,11 @@ pub fn typedef(module: ItemMod, args: TypeDefArgs) -> TokenStream {
         .iter()
         .fold(TokenStream::new(), |acc, (ty_ident, impls)| {
             let temporal_mac_name = syn::Ident::new(
-                &format!("__{}_temporal_{}", &ty_ident, random_suffix),
+                &format!("__{}_temporal_{}", ty_ident.unraw(), random_suffix),
                 ty_ident.span(),
             );
             let vis = type_idents
-                .get(&ty_ident)
+                .get(ty_ident)
                 .cloned()
                 .unwrap_or(Visibility::Public(Default::default()));
             quote! {
@@ -345,11 +379,14 @@ pub fn typedef(module: ItemMod, args: TypeDefArgs) -> TokenStream {
                         $($coinduction)+::__next_step! {
                             #crate_version, Typedef {
                                 predicates: [
-                                    #(for (generics, constraint, children) in impls), {
+                                    #(for (generics, constraint, children, referred) in impls), {
                                         (
                                             [ #(for p in &generics.params), {#p} ],
-                                            #constraint,
-                                            [ #(for c in children), { #c } ]
+                                            #{dollar_crate(quote!(#constraint))},
+                                            [ #(for c in children), { #{dollar_crate(quote!(#c))} } ],
+                                            [ #(for (projection, ty) in referred), {
+                                                (#{dollar_crate(quote!(#projection))}, #{dollar_crate(quote!(#ty))})
+                                            } ]
                                         )
                                     }
                                 ]
@@ -364,8 +401,9 @@ pub fn typedef(module: ItemMod, args: TypeDefArgs) -> TokenStream {
             }
         });
     quote! {
-        #(for attr in &module.attrs) { #attr }
+        #(for attr in module.attrs.iter().filter(|a| a.style == AttrStyle::Outer)) { #attr }
         #{&module.vis} #{&module.unsafety} #{&module.mod_token} #{&module.ident} {
+            #(for attr in module.attrs.iter().filter(|a| a.style != AttrStyle::Outer)) { #attr }
             #(for item in &content) {
                 #item
             }
@@ -0,0 +1,46 @@
+use coinduction::*;
+
+#[traitdef]
+trait Total {
+    fn total(&self) -> usize;
+}
+
+#[coinduction(Total)]
+mod totals {
+    use super::Total;
+
+    pub struct Even<I>(pub I);
+    pub struct Odd<I>(pub I);
+
+    impl<I, U> Total for Even<I>
+    where
+        I: Iterator<Item = U> + Clone,
+        U: Into<usize>,
+        Odd<I>: Total,
+    {
+        fn total(&self) -> usize {
+            self.0.clone().map(Into::into).sum()
+        }
+    }
+
+    impl<I, V> Total for Odd<I>
+    where
+        I: Clone + Iterator<Item = V>,
+        V: Copy,
+        Even<I>: Total,
+    {
+        fn total(&self) -> usize {
+            self.0.clone().count()
+        }
+    }
+}
+
+// `U` and `V` are only fixed by their `Item = ..` bindings; when the bounds of one impl are carried
+// into the other, they must be expressed as `<I as Iterator>::Item` rather than leak as unbound names
+#[test]
+fn test_assoc_type_bindings_pass_through() {
+    use totals::*;
+    let items = vec![1u8, 2, 3];
+    assert_eq!(Even(items.clone().into_iter()).total(), 6);
+    assert_eq!(Odd(items.into_iter()).total(), 3);
+}
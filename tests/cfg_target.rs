@@ -0,0 +1,265 @@
+use coinduction::*;
+
+#[traitdef]
+trait Count {
+    fn count(&self) -> usize;
+}
+
+// Target-conditional: `Head` closes its cycle through `Native` on Linux and through `Portable`
+// elsewhere. Each combination of the `cfg` predicates is resolved on its own, so only the cycle
+// of the active target shapes the impls that survive `cfg` evaluation.
+#[coinduction(Count)]
+mod gated {
+    use super::Count;
+
+    pub struct Head;
+    pub struct Native;
+    pub struct Portable;
+
+    #[cfg(not(target_os = "linux"))]
+    impl Count for Head
+    where
+        Portable: Count,
+    {
+        fn count(&self) -> usize {
+            2
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    impl Count for Head
+    where
+        Native: Count,
+    {
+        fn count(&self) -> usize {
+            1
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    impl Count for Native
+    where
+        Head: Count,
+    {
+        fn count(&self) -> usize {
+            Head.count() + 10
+        }
+    }
+
+    impl Count for Portable
+    where
+        Head: Count,
+    {
+        fn count(&self) -> usize {
+            Head.count() + 20
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_target_gated_cycle() {
+    use gated::*;
+    assert_eq!(Head.count(), 1);
+    assert_eq!(Native.count(), 11);
+    assert_eq!(Portable.count(), 21);
+}
+
+#[cfg(not(target_os = "linux"))]
+#[test]
+fn test_target_gated_cycle() {
+    use gated::*;
+    assert_eq!(Head.count(), 2);
+    assert_eq!(Portable.count(), 22);
+}
+
+// Five independent cycles, each gated by predicates of its own. Only the predicates of a cycle
+// are combined, so a module is not limited in the number of predicates it uses.
+#[coinduction(Count)]
+mod many_gates {
+    use super::Count;
+
+    pub struct UnixPing;
+    pub struct UnixPong;
+
+    #[cfg(unix)]
+    impl Count for UnixPing
+    where
+        UnixPong: Count,
+    {
+        fn count(&self) -> usize {
+            1
+        }
+    }
+
+    #[cfg(not(unix))]
+    impl Count for UnixPing
+    where
+        UnixPong: Count,
+    {
+        fn count(&self) -> usize {
+            2
+        }
+    }
+
+    impl Count for UnixPong
+    where
+        UnixPing: Count,
+    {
+        fn count(&self) -> usize {
+            UnixPing.count() + 10
+        }
+    }
+
+    pub struct WindowsPing;
+    pub struct WindowsPong;
+
+    #[cfg(windows)]
+    impl Count for WindowsPing
+    where
+        WindowsPong: Count,
+    {
+        fn count(&self) -> usize {
+            1
+        }
+    }
+
+    #[cfg(not(windows))]
+    impl Count for WindowsPing
+    where
+        WindowsPong: Count,
+    {
+        fn count(&self) -> usize {
+            2
+        }
+    }
+
+    impl Count for WindowsPong
+    where
+        WindowsPing: Count,
+    {
+        fn count(&self) -> usize {
+            WindowsPing.count() + 10
+        }
+    }
+
+    pub struct DebugPing;
+    pub struct DebugPong;
+
+    #[cfg(debug_assertions)]
+    impl Count for DebugPing
+    where
+        DebugPong: Count,
+    {
+        fn count(&self) -> usize {
+            1
+        }
+    }
+
+    #[cfg(not(debug_assertions))]
+    impl Count for DebugPing
+    where
+        DebugPong: Count,
+    {
+        fn count(&self) -> usize {
+            2
+        }
+    }
+
+    impl Count for DebugPong
+    where
+        DebugPing: Count,
+    {
+        fn count(&self) -> usize {
+            DebugPing.count() + 10
+        }
+    }
+
+    pub struct WidePing;
+    pub struct WidePong;
+
+    #[cfg(target_pointer_width = "64")]
+    impl Count for WidePing
+    where
+        WidePong: Count,
+    {
+        fn count(&self) -> usize {
+            1
+        }
+    }
+
+    #[cfg(not(target_pointer_width = "64"))]
+    impl Count for WidePing
+    where
+        WidePong: Count,
+    {
+        fn count(&self) -> usize {
+            2
+        }
+    }
+
+    impl Count for WidePong
+    where
+        WidePing: Count,
+    {
+        fn count(&self) -> usize {
+            WidePing.count() + 10
+        }
+    }
+
+    pub struct LittlePing;
+    pub struct LittlePong;
+
+    #[cfg(target_endian = "little")]
+    impl Count for LittlePing
+    where
+        LittlePong: Count,
+    {
+        fn count(&self) -> usize {
+            1
+        }
+    }
+
+    #[cfg(not(target_endian = "little"))]
+    impl Count for LittlePing
+    where
+        LittlePong: Count,
+    {
+        fn count(&self) -> usize {
+            2
+        }
+    }
+
+    impl Count for LittlePong
+    where
+        LittlePing: Count,
+    {
+        fn count(&self) -> usize {
+            LittlePing.count() + 10
+        }
+    }
+}
+
+#[test]
+fn test_many_gated_cycles() {
+    use many_gates::*;
+    let ping = if cfg!(unix) { 1 } else { 2 };
+    assert_eq!(UnixPing.count(), ping);
+    assert_eq!(UnixPong.count(), ping + 10);
+    let ping = if cfg!(windows) { 1 } else { 2 };
+    assert_eq!(WindowsPing.count(), ping);
+    assert_eq!(WindowsPong.count(), ping + 10);
+    let ping = if cfg!(debug_assertions) { 1 } else { 2 };
+    assert_eq!(DebugPing.count(), ping);
+    assert_eq!(DebugPong.count(), ping + 10);
+    let ping = if cfg!(target_pointer_width = "64") {
+        1
+    } else {
+        2
+    };
+    assert_eq!(WidePing.count(), ping);
+    assert_eq!(WidePong.count(), ping + 10);
+    let ping = if cfg!(target_endian = "little") { 1 } else { 2 };
+    assert_eq!(LittlePing.count(), ping);
+    assert_eq!(LittlePong.count(), ping + 10);
+}
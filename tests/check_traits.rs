@@ -0,0 +1,34 @@
+use coinduction::*;
+
+#[traitdef]
+trait Show {
+    fn show(&self) -> String;
+}
+
+pub mod nested {
+    use coinduction::traitdef;
+
+    #[traitdef]
+    pub trait Measure {
+        fn measure(&self) -> usize;
+    }
+}
+
+// Local traits, a trait behind a path and one from another crate are all registered
+#[check_traits(Show, nested::Measure, traitdef::LocalTrait)]
+pub struct Checked;
+
+impl Show for Checked {
+    fn show(&self) -> String {
+        "checked".to_string()
+    }
+}
+
+// The path the trait macros reach the relay through can be given as for `#[coinduction]`
+#[check_traits(coinduction = ::coinduction, Show)]
+pub struct CheckedThroughPath;
+
+#[test]
+fn test_item_is_kept() {
+    assert_eq!(Checked.show(), "checked");
+}
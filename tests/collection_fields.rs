@@ -0,0 +1,115 @@
+use coinduction::*;
+use std::collections::HashMap;
+
+#[traitdef]
+trait Count {
+    fn count(&self) -> usize;
+}
+
+impl Count for u8 {
+    fn count(&self) -> usize {
+        1
+    }
+}
+
+impl<T: Count> Count for Vec<T> {
+    fn count(&self) -> usize {
+        self.iter().map(Count::count).sum()
+    }
+}
+
+impl<K, V: Count> Count for HashMap<K, V> {
+    fn count(&self) -> usize {
+        self.values().map(Count::count).sum()
+    }
+}
+
+#[coinduction(deep_args, Count)]
+mod tree {
+    use super::Count;
+    use std::collections::HashMap;
+
+    pub struct Node<T> {
+        pub value: T,
+        pub children: Vec<Node<T>>,
+        pub named: HashMap<String, Node<T>>,
+    }
+
+    impl<T> Count for Node<T>
+    where
+        T: Count,
+        Vec<Node<T>>: Count,
+        HashMap<String, Node<T>>: Count,
+    {
+        fn count(&self) -> usize {
+            self.value.count() + self.children.count() + self.named.count()
+        }
+    }
+}
+
+#[test]
+fn test_recursion_through_collections() {
+    use tree::*;
+    let leaf = |value| Node {
+        value,
+        children: vec![],
+        named: HashMap::new(),
+    };
+    let node = Node {
+        value: 1u8,
+        children: vec![leaf(2), leaf(3)],
+        named: [("four".to_string(), leaf(4))].into_iter().collect(),
+    };
+    assert_eq!(node.count(), 4);
+}
+
+#[traitdef((Box<$T:ty>) => { $T: Size })]
+trait Size {
+    fn size(&self) -> usize;
+}
+
+impl<T: Size> Size for Box<T> {
+    fn size(&self) -> usize {
+        self.as_ref().size()
+    }
+}
+
+impl<T: Size> Size for Vec<T> {
+    fn size(&self) -> usize {
+        self.iter().map(Size::size).sum()
+    }
+}
+
+#[coinduction(deep_args, Size)]
+mod boxed {
+    use super::Size;
+
+    pub struct Node(pub Box<Branch>);
+    pub struct Branch(pub Vec<Node>);
+
+    impl Size for Node
+    where
+        Box<Branch>: Size,
+    {
+        fn size(&self) -> usize {
+            1 + self.0.size()
+        }
+    }
+
+    // `Vec` has no rule, so this bound is only reached after the relay through `Box`
+    impl Size for Branch
+    where
+        Vec<Node>: Size,
+    {
+        fn size(&self) -> usize {
+            self.0.size()
+        }
+    }
+}
+
+#[test]
+fn test_collection_reached_through_relay() {
+    use boxed::*;
+    let leaf = || Node(Box::new(Branch(vec![])));
+    assert_eq!(Node(Box::new(Branch(vec![leaf(), leaf()]))).size(), 3);
+}
@@ -1,4 +1,4 @@
-#![allow(dead_code)]
+#![allow(dead_code, clippy::type_complexity)]
 
 use coinduction::*;
 use std::fmt::{Display, UpperHex};
@@ -133,7 +133,7 @@ mod coinduction_mod {
             if let Some(b) = &self.0 {
                 format!("{:X} {}", T::default(), <RecB<T> as TraitB<S>>::get_b(b))
             } else {
-                format!("None")
+                "None".to_string()
             }
         }
     }
@@ -153,7 +153,7 @@ mod coinduction_mod {
                     <RecA<T> as TraitA<S>>::get_a(a.as_ref())
                 )
             } else {
-                format!("None")
+                "None".to_string()
             }
         }
     }
@@ -193,7 +193,7 @@ mod complex_recursive {
             if let Some(ref rec_c) = self.0 {
                 format!("RecD {}", <RecC<T1, T2, T3, T4> as TraitA<S>>::get_a(rec_c))
             } else {
-                format!("RecD None")
+                "RecD None".to_string()
             }
         }
     }
@@ -0,0 +1,66 @@
+use coinduction::*;
+
+#[traitdef]
+trait Size {
+    fn size(&self) -> usize;
+}
+
+#[coinduction(Size)]
+mod sizes {
+    use super::Size;
+
+    pub struct Pair<const M: usize, const N: usize = M>;
+    pub struct Single<const M: usize>;
+    pub struct Wrapper<T, U = T>(pub T, pub U);
+    pub struct Other<T>(pub T);
+
+    impl<const M: usize, const N: usize> Size for Pair<M, N>
+    where
+        Single<M>: Size,
+    {
+        fn size(&self) -> usize {
+            M + N
+        }
+    }
+
+    impl<const M: usize> Size for Single<M>
+    where
+        Pair<M>: Size,
+    {
+        fn size(&self) -> usize {
+            Pair::<M>.size()
+        }
+    }
+
+    impl<T, U> Size for Wrapper<T, U>
+    where
+        Other<T>: Size,
+    {
+        fn size(&self) -> usize {
+            2
+        }
+    }
+
+    impl<T> Size for Other<T>
+    where
+        Wrapper<T>: Size,
+    {
+        fn size(&self) -> usize {
+            1
+        }
+    }
+}
+
+#[test]
+fn test_const_default_referencing_param() {
+    assert_eq!(sizes::Pair::<2>.size(), 4);
+    assert_eq!(sizes::Pair::<2, 3>.size(), 5);
+    assert_eq!(sizes::Single::<3>.size(), 6);
+}
+
+#[test]
+fn test_type_default_referencing_param() {
+    let wrapper: sizes::Wrapper<u8> = sizes::Wrapper(1, 2);
+    assert_eq!(wrapper.size(), 2);
+    assert_eq!(sizes::Other(1u8).size(), 1);
+}
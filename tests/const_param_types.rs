@@ -0,0 +1,128 @@
+use coinduction::*;
+
+#[traitdef]
+trait Width {
+    fn width(&self) -> usize;
+}
+
+#[coinduction(Width)]
+mod widths {
+    use super::Width;
+
+    pub struct Bits<const N: u8, const SIGNED: bool>;
+    pub struct Field<T, const N: u8>(pub T);
+    pub struct Cell<const N: u8>;
+
+    impl<const N: u8, const SIGNED: bool> Width for Bits<N, SIGNED>
+    where
+        Cell<N>: Width,
+    {
+        fn width(&self) -> usize {
+            N as usize + SIGNED as usize
+        }
+    }
+
+    impl<const N: u8> Width for Cell<N>
+    where
+        Bits<N, true>: Width,
+        Field<[u8; 2], N>: Width,
+    {
+        fn width(&self) -> usize {
+            Bits::<N, true>.width()
+        }
+    }
+
+    impl<T, const N: u8> Width for Field<T, N>
+    where
+        Cell<N>: Width,
+    {
+        fn width(&self) -> usize {
+            N as usize
+        }
+    }
+}
+
+#[test]
+fn test_const_params_of_non_usize_types() {
+    assert_eq!(widths::Bits::<7, false>.width(), 7);
+    assert_eq!(widths::Cell::<7>.width(), 8);
+    assert_eq!(widths::Field::<_, 3>([0u8; 2]).width(), 3);
+}
+
+#[coinduction(Width)]
+mod gates {
+    use super::Width;
+
+    pub struct Gate<const OPEN: bool>;
+    pub struct Latch<const OPEN: bool, const C: char>;
+
+    impl Width for Gate<true>
+    where
+        Gate<false>: Width,
+    {
+        fn width(&self) -> usize {
+            1
+        }
+    }
+
+    impl Width for Gate<false>
+    where
+        Latch<true, 'x'>: Width,
+    {
+        fn width(&self) -> usize {
+            0
+        }
+    }
+
+    impl<const OPEN: bool, const C: char> Width for Latch<OPEN, C>
+    where
+        Gate<OPEN>: Width,
+    {
+        fn width(&self) -> usize {
+            C as usize
+        }
+    }
+}
+
+// The rules over `Gate<true>` and `Gate<false>` are matched against concrete literals
+#[test]
+fn test_bool_and_char_literals_in_rules() {
+    use gates::*;
+    assert_eq!(Gate::<true>.width(), 1);
+    assert_eq!(Gate::<false>.width(), 0);
+    assert_eq!(Latch::<true, 'x'>.width(), 'x' as usize);
+}
+
+#[coinduction(Width)]
+mod registers {
+    use super::Width;
+
+    pub struct Reg<const N: u8>;
+    pub struct Glyph<const C: char>;
+
+    impl Width for Reg<16>
+    where
+        Glyph<'\x41'>: Width,
+    {
+        fn width(&self) -> usize {
+            16
+        }
+    }
+
+    impl Width for Glyph<'A'>
+    where
+        Reg<0x10>: Width,
+    {
+        fn width(&self) -> usize {
+            1
+        }
+    }
+}
+
+// `Reg<0x10>` and `Glyph<'\x41'>` are the heads of the impls above, written differently
+#[test]
+fn test_literals_written_differently() {
+    use registers::*;
+    assert_eq!(Reg::<16>.width(), 16);
+    assert_eq!(Glyph::<'A'>.width(), 1);
+}
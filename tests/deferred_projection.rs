@@ -0,0 +1,60 @@
+use coinduction::*;
+
+#[traitdef]
+trait Show {
+    fn show(&self) -> String;
+}
+
+pub trait Produce {
+    type Output;
+}
+
+pub struct Unit;
+
+impl Produce for Unit {
+    type Output = u8;
+}
+
+impl Show for u8 {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[coinduction(Show)]
+mod projected {
+    use super::{Produce, Show};
+    use std::marker::PhantomData;
+
+    pub struct Top;
+    pub struct Branch<T>(pub PhantomData<T>);
+
+    impl Show for Top
+    where
+        Branch<super::Unit>: Show,
+    {
+        fn show(&self) -> String {
+            "top".to_string()
+        }
+    }
+
+    impl<T> Show for Branch<T>
+    where
+        T: Produce,
+        T::Output: Show + Default,
+        Top: Show,
+    {
+        fn show(&self) -> String {
+            format!("branch {}", T::Output::default().show())
+        }
+    }
+}
+
+// `T::Output: Show` becomes `<Unit as Produce>::Output: Show` for `Branch<Unit>`; the projection
+// cannot be relayed to a trait macro, so it is kept as a bound instead
+#[test]
+fn test_projections_are_deferred() {
+    use projected::*;
+    assert_eq!(Top.show(), "top");
+    assert_eq!(Branch::<Unit>(Default::default()).show(), "branch 0");
+}
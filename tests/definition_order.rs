@@ -0,0 +1,55 @@
+use coinduction::*;
+
+#[coinduction(Later)]
+mod early {
+    use super::later_types::Leaf;
+    use super::Later;
+
+    pub struct A;
+    pub struct B;
+
+    impl Later for A
+    where
+        B: Later,
+        Leaf: Later,
+    {
+        fn later(&self) -> usize {
+            B.later() + Leaf.later()
+        }
+    }
+
+    impl Later for B
+    where
+        A: Later,
+    {
+        fn later(&self) -> usize {
+            1
+        }
+    }
+}
+
+#[traitdef]
+trait Later {
+    fn later(&self) -> usize;
+}
+
+#[typedef(Later)]
+mod later_types {
+    use super::Later;
+
+    pub struct Leaf;
+
+    impl Later for Leaf {
+        fn later(&self) -> usize {
+            10
+        }
+    }
+}
+
+// The generated macros are resolved by path, so the relay finds them even though both the
+// trait and the typedef module come after the coinduction module in this file.
+#[test]
+fn test_trait_and_types_defined_after_coinduction_module() {
+    assert_eq!(early::A.later(), 11);
+    assert_eq!(early::B.later(), 1);
+}
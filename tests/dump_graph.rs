@@ -0,0 +1,59 @@
+use coinduction::*;
+
+#[traitdef]
+trait Evaluate {
+    fn evaluate(&self) -> i32;
+}
+
+#[coinduction(Evaluate, dump_graph)]
+mod graphed {
+    use super::Evaluate;
+
+    pub struct Expr;
+    pub struct Term;
+    pub struct Lit;
+
+    impl Evaluate for Lit {
+        fn evaluate(&self) -> i32 {
+            2
+        }
+    }
+
+    impl Evaluate for Expr
+    where
+        Term: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            Term.evaluate()
+        }
+    }
+
+    impl Evaluate for Term
+    where
+        Expr: Evaluate,
+        Lit: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            Lit.evaluate()
+        }
+    }
+}
+
+#[test]
+fn test_graph_lists_constraints_and_cycles() {
+    let graph = graphed::__COINDUCTION_GRAPH;
+    // One graph per impl of the module
+    assert_eq!(graph.matches("digraph solver").count(), 3);
+    assert!(graph.contains("[label=\"Expr : Evaluate\"]"));
+    assert!(graph.contains("[label=\"Term : Evaluate\"]"));
+    assert!(graph.contains("[label=\"Lit : Evaluate\"]"));
+    assert!(graph.contains(" -> "));
+    // The solvers of `Expr` and `Term` see their cycle; `Lit` is a leaf outside of it
+    assert_eq!(graph.matches("subgraph cluster_0").count(), 2);
+    assert!(!graph.contains("cluster_1"));
+}
+
+#[test]
+fn test_graph_does_not_affect_impls() {
+    assert_eq!(graphed::Expr.evaluate(), 2);
+}
@@ -0,0 +1,71 @@
+use coinduction::*;
+
+#[coinduction(dump_relay, allow_trivial, LocalTrait, TestTrait)]
+mod relayed {
+    use traitdef::{LocalTrait, TestTrait};
+
+    #[derive(Clone, Debug, Default)]
+    pub struct Leaf;
+
+    impl LocalTrait for Leaf
+    where
+        (Leaf, Leaf): LocalTrait,
+        (Leaf, Leaf): TestTrait,
+    {
+        fn local_method(&self) -> usize {
+            (Leaf, Leaf).local_method() + (Leaf, Leaf).test_method().len()
+        }
+    }
+
+    impl TestTrait for Leaf
+    where
+        (Leaf, Leaf): LocalTrait,
+    {
+        fn test_method(&self) -> String {
+            "leaf".to_string()
+        }
+    }
+}
+
+#[coinduction(dump_relay, Evaluate)]
+mod local_only {
+    use super::Evaluate;
+
+    pub struct Expr;
+    pub struct Term;
+
+    impl Evaluate for Expr
+    where
+        Term: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            Term.evaluate()
+        }
+    }
+
+    impl Evaluate for Term
+    where
+        Expr: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            1
+        }
+    }
+}
+
+#[traitdef]
+trait Evaluate {
+    fn evaluate(&self) -> i32;
+}
+
+// The dump is reported as a note and adds no item to the module
+#[test]
+fn test_dump_without_relay() {
+    assert_eq!(local_only::Expr.evaluate(), 1);
+}
+
+#[test]
+fn test_dump_does_not_affect_impls() {
+    use traitdef::LocalTrait;
+    assert!(relayed::Leaf.local_method() > 2);
+}
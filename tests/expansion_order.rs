@@ -0,0 +1,123 @@
+use coinduction::*;
+
+#[traitdef((Option<$T:ty>) => { $T: Walk })]
+trait Walk {
+    fn walk(&self) -> usize;
+}
+
+impl<T: Walk> Walk for Option<T> {
+    fn walk(&self) -> usize {
+        self.as_ref().map_or(0, Walk::walk)
+    }
+}
+
+// Several impls reach the same relayed constraint, whose expansion revisits their nodes
+#[coinduction(show_order, Walk)]
+mod city {
+    use super::Walk;
+
+    pub struct Hub;
+    pub struct A;
+    pub struct B;
+    pub struct C;
+    pub struct D;
+    pub struct E;
+    pub struct F;
+
+    impl Walk for Hub
+    where
+        E: Walk,
+        A: Walk,
+    {
+        fn walk(&self) -> usize {
+            1
+        }
+    }
+
+    impl Walk for A
+    where
+        B: Walk,
+        Option<Hub>: Walk,
+    {
+        fn walk(&self) -> usize {
+            1
+        }
+    }
+
+    impl Walk for B
+    where
+        C: Walk,
+    {
+        fn walk(&self) -> usize {
+            1
+        }
+    }
+
+    impl Walk for C
+    where
+        A: Walk,
+        D: Walk,
+    {
+        fn walk(&self) -> usize {
+            1
+        }
+    }
+
+    impl Walk for D
+    where
+        E: Walk,
+    {
+        fn walk(&self) -> usize {
+            1
+        }
+    }
+
+    impl Walk for E
+    where
+        D: Walk,
+        F: Walk,
+    {
+        fn walk(&self) -> usize {
+            1
+        }
+    }
+
+    impl Walk for F {
+        fn walk(&self) -> usize {
+            1
+        }
+    }
+}
+
+// The nodes are looked up through an index, which must not change the order they are added in
+#[test]
+fn test_expansion_order_is_stable() {
+    let expected = [
+        "F : Walk",
+        "cycle(E : Walk, D : Walk)",
+        "cycle(C : Walk, B : Walk, Option < Hub > : Walk, A : Walk, Hub : Walk)",
+        "F : Walk",
+        "cycle(D : Walk, E : Walk)",
+        "cycle(C : Walk, B : Walk, Hub : Walk, Option < Hub > : Walk, A : Walk)",
+        "F : Walk",
+        "cycle(E : Walk, D : Walk)",
+        "cycle(Hub : Walk, Option < Hub > : Walk, A : Walk, C : Walk, B : Walk)",
+        "F : Walk",
+        "cycle(E : Walk, D : Walk)",
+        "cycle(B : Walk, Hub : Walk, Option < Hub > : Walk, A : Walk, C : Walk)",
+        "F : Walk",
+        "cycle(E : Walk, D : Walk)",
+        "F : Walk",
+        "cycle(D : Walk, E : Walk)",
+        "F : Walk",
+    ];
+    assert_eq!(city::__COINDUCTION_ORDER, expected);
+}
+
+#[test]
+fn test_expansion_does_not_affect_impls() {
+    use city::*;
+    let walked = [A.walk(), B.walk(), C.walk(), D.walk(), E.walk(), F.walk()];
+    assert_eq!(walked, [1; 6]);
+    assert_eq!(Some(Hub).walk(), 1);
+}
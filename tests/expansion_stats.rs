@@ -0,0 +1,117 @@
+use coinduction::*;
+
+#[traitdef]
+trait Evaluate {
+    fn evaluate(&self, input: &[&'static str], index: &mut usize) -> i32;
+}
+
+#[coinduction(stats, Evaluate)]
+mod calculator {
+    use super::Evaluate;
+
+    pub struct Expr;
+    pub struct Term;
+
+    impl Evaluate for Expr
+    where
+        Term: Evaluate,
+    {
+        fn evaluate(&self, input: &[&'static str], index: &mut usize) -> i32 {
+            let left_val = Term.evaluate(input, index);
+            *index += 1;
+            left_val + Term.evaluate(input, index)
+        }
+    }
+
+    impl Evaluate for Term
+    where
+        Expr: Evaluate,
+    {
+        fn evaluate(&self, input: &[&'static str], index: &mut usize) -> i32 {
+            let token = input[*index];
+            *index += 1;
+            if token == "(" {
+                let result = Expr.evaluate(input, index);
+                *index += 1;
+                result
+            } else {
+                token.parse::<i32>().unwrap()
+            }
+        }
+    }
+}
+
+#[coinduction(stats, allow_trivial, LocalTrait)]
+mod relayed {
+    use traitdef::LocalTrait;
+
+    #[derive(Clone, Default)]
+    pub struct Leaf;
+
+    impl LocalTrait for Leaf
+    where
+        (Leaf, Leaf): LocalTrait,
+    {
+        fn local_method(&self) -> usize {
+            (Leaf, Leaf).local_method()
+        }
+    }
+}
+
+fn stat_of(stats: &str, name: &str) -> usize {
+    stats
+        .split_whitespace()
+        .find_map(|entry| entry.strip_prefix(name)?.strip_prefix('='))
+        .unwrap_or_else(|| panic!("missing `{}` in stats", name))
+        .parse()
+        .unwrap()
+}
+
+fn stat(name: &str) -> usize {
+    stat_of(calculator::__COINDUCTION_STATS, name)
+}
+
+#[test]
+fn test_stats_are_emitted() {
+    for name in [
+        "reentries",
+        "nodes",
+        "edges",
+        "rule_matches",
+        "working_list",
+        "protocol_tokens",
+    ] {
+        stat(name);
+    }
+}
+
+#[test]
+fn test_stats_upper_bounds() {
+    // Two impls in a single cycle resolve without any relay through trait macros
+    assert_eq!(stat("reentries"), 0);
+    assert_eq!(stat("working_list"), 0);
+    assert!(stat("nodes") <= 4);
+    assert!(stat("edges") <= 4);
+    assert!(stat("rule_matches") <= 8);
+    assert!(stat("protocol_tokens") <= 1000);
+}
+
+#[test]
+fn test_stats_count_relay() {
+    use traitdef::LocalTrait;
+    assert_eq!(relayed::Leaf.local_method(), 2);
+
+    let stats = relayed::__COINDUCTION_STATS;
+    assert_eq!(stat_of(stats, "reentries"), 1);
+    assert_eq!(stat_of(stats, "working_list"), 1);
+    assert!(stat_of(stats, "protocol_tokens") > 0);
+    assert!(stat_of(stats, "protocol_tokens") <= 2000);
+    assert!(stat_of(stats, "nodes") <= 8);
+}
+
+#[test]
+fn test_stats_evaluation_unaffected() {
+    let input = vec!["2", "+", "3"];
+    let mut index = 0;
+    assert_eq!(calculator::Expr.evaluate(&input, &mut index), 5);
+}
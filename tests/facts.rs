@@ -0,0 +1,88 @@
+use coinduction::*;
+
+#[traitdef((($T:ty, $U:ty)) => { $T: Describe, $U: Describe })]
+trait Describe {
+    fn describe(&self) -> String;
+}
+
+impl<T: Describe, U: Describe> Describe for (T, U) {
+    fn describe(&self) -> String {
+        format!("({}, {})", self.0.describe(), self.1.describe())
+    }
+}
+
+// A foreign type with a hand-written impl and no `#[typedef]` relay macro
+pub struct Foreign;
+
+impl Describe for Foreign {
+    fn describe(&self) -> String {
+        "foreign".to_string()
+    }
+}
+
+#[coinduction(facts(Foreign: Describe), Describe)]
+mod described {
+    use super::{Describe, Foreign};
+
+    pub struct Outer;
+    pub struct Inner;
+
+    impl Describe for Outer
+    where
+        Inner: Describe,
+        Foreign: Describe,
+    {
+        fn describe(&self) -> String {
+            format!("outer({})", Foreign.describe())
+        }
+    }
+
+    impl Describe for Inner
+    where
+        Outer: Describe,
+    {
+        fn describe(&self) -> String {
+            "inner".to_string()
+        }
+    }
+}
+
+#[coinduction(facts(Foreign: Describe), Describe)]
+mod relayed {
+    use super::{Describe, Foreign};
+
+    pub struct Outer;
+    pub struct Inner;
+
+    impl Describe for Outer
+    where
+        (Foreign, Inner): Describe,
+    {
+        fn describe(&self) -> String {
+            "outer".to_string()
+        }
+    }
+
+    impl Describe for Inner
+    where
+        Outer: Describe,
+    {
+        fn describe(&self) -> String {
+            "inner".to_string()
+        }
+    }
+}
+
+// Without the fact, `Foreign: Describe` would be relayed to a nonexistent `Foreign!` macro
+#[test]
+fn test_fact_is_not_relayed() {
+    assert_eq!(described::Outer.describe(), "outer(foreign)");
+    assert_eq!(described::Inner.describe(), "inner");
+}
+
+// Here `Foreign: Describe` only appears after the tuple rule has been relayed
+#[test]
+fn test_fact_reached_through_relay() {
+    assert_eq!(relayed::Outer.describe(), "outer");
+    assert_eq!((Foreign, relayed::Inner).describe(), "(foreign, inner)");
+}
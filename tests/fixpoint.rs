@@ -0,0 +1,49 @@
+use coinduction::*;
+
+#[traitdef]
+trait Weigh {
+    fn weigh(&self) -> usize;
+}
+
+pub trait Functor {
+    type Apply<X>;
+}
+
+#[coinduction(Weigh)]
+mod fixpoint {
+    use super::{Functor, Weigh};
+
+    pub struct Fix<F: Functor>(pub Box<F::Apply<Fix<F>>>);
+    pub struct ListF;
+    pub struct Cons<X>(pub u8, pub Option<X>);
+
+    impl Functor for ListF {
+        type Apply<X> = Cons<X>;
+    }
+
+    impl Weigh for Fix<ListF>
+    where
+        <ListF as Functor>::Apply<Fix<ListF>>: Weigh,
+    {
+        fn weigh(&self) -> usize {
+            self.0.weigh()
+        }
+    }
+
+    impl<X> Weigh for Cons<X>
+    where
+        X: Weigh,
+    {
+        fn weigh(&self) -> usize {
+            self.0 as usize + self.1.as_ref().map_or(0, |x| x.weigh())
+        }
+    }
+}
+
+// `Fix<ListF>` unfolds to `Cons<Fix<ListF>>` through the generic associated type, closing the cycle
+#[test]
+fn test_type_level_fixpoint() {
+    use fixpoint::*;
+    let list: Fix<ListF> = Fix(Box::new(Cons(1, Some(Fix(Box::new(Cons(2, None)))))));
+    assert_eq!(list.weigh(), 3);
+}
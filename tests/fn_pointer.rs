@@ -0,0 +1,52 @@
+use coinduction::*;
+
+#[traitdef]
+trait Weight {
+    fn weight(&self) -> usize;
+}
+
+#[coinduction(Weight)]
+mod fn_pointer {
+    use super::Weight;
+    use std::marker::PhantomData;
+
+    pub struct Callback<F>(pub PhantomData<F>);
+
+    impl<A, R> Weight for Callback<fn(A) -> R>
+    where
+        A: Weight,
+        R: Weight,
+    {
+        fn weight(&self) -> usize {
+            1
+        }
+    }
+
+    pub struct Source;
+    pub struct Sink;
+
+    impl Weight for Source
+    where
+        Callback<fn(Sink) -> Source>: Weight,
+    {
+        fn weight(&self) -> usize {
+            Callback::<fn(Sink) -> Source>(PhantomData).weight() + 1
+        }
+    }
+
+    impl Weight for Sink
+    where
+        Source: Weight,
+    {
+        fn weight(&self) -> usize {
+            10
+        }
+    }
+}
+
+#[test]
+fn test_fn_pointer_bound_in_cycle() {
+    use fn_pointer::*;
+    assert_eq!(Source.weight(), 2);
+    assert_eq!(Sink.weight(), 10);
+}
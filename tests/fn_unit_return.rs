@@ -0,0 +1,66 @@
+use coinduction::*;
+
+#[traitdef]
+trait Weight {
+    fn weight(&self) -> usize;
+}
+
+#[coinduction(show_order, Weight)]
+mod handlers {
+    use super::Weight;
+    use std::marker::PhantomData;
+
+    pub struct Handler<F: ?Sized>(pub PhantomData<Box<F>>);
+
+    impl<T> Weight for Handler<dyn Fn(T)>
+    where
+        T: Weight,
+    {
+        fn weight(&self) -> usize {
+            1
+        }
+    }
+
+    pub struct Source;
+    pub struct Sink;
+
+    // The rule leaves the return type out, which is the same as returning `()`
+    impl Weight for Source
+    where
+        Handler<dyn Fn(Sink) -> ()>: Weight,
+    {
+        fn weight(&self) -> usize {
+            Handler::<dyn Fn(Sink)>(PhantomData).weight() + 1
+        }
+    }
+
+    impl Weight for Sink
+    where
+        Source: Weight,
+    {
+        fn weight(&self) -> usize {
+            10
+        }
+    }
+}
+
+#[test]
+fn test_unit_return_matches_default() {
+    use handlers::*;
+    assert_eq!(Source.weight(), 2);
+    assert_eq!(Sink.weight(), 10);
+}
+
+#[test]
+fn test_unit_return_closes_the_cycle() {
+    let order = handlers::__COINDUCTION_ORDER;
+    let cycle = order
+        .iter()
+        .find(|step| step.starts_with("cycle("))
+        .expect("the cycle is listed");
+    assert!(
+        cycle.contains("Handler < dyn Fn(Sink) -> () > : Weight"),
+        "{:?}",
+        order
+    );
+}
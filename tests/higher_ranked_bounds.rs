@@ -0,0 +1,46 @@
+use coinduction::*;
+
+#[traitdef]
+trait Render {
+    fn render(&self) -> String;
+}
+
+#[coinduction(Render)]
+mod rendered {
+    use super::Render;
+
+    pub struct Doc<F>(pub F, pub Vec<Para<F>>);
+    pub struct Para<F>(pub F, pub Vec<Doc<F>>);
+
+    impl<F> Render for Doc<F>
+    where
+        F: for<'a> Fn(&'a str) -> &'a str,
+        Para<F>: Render,
+    {
+        fn render(&self) -> String {
+            let inner: String = self.1.iter().map(|para| para.render()).collect();
+            (self.0)(&inner).to_string()
+        }
+    }
+
+    impl<F: for<'a> Fn(&'a str) -> &'a str> Render for Para<F>
+    where
+        Doc<F>: Render,
+    {
+        fn render(&self) -> String {
+            let inner: String = self.1.iter().map(|doc| doc.render()).collect();
+            format!("{}{}", (self.0)("para"), inner)
+        }
+    }
+}
+
+// The `for<'a>` bounds are not part of the cycle and must be passed through with their binder
+#[test]
+fn test_higher_ranked_bounds_pass_through() {
+    use rendered::*;
+    fn trim(s: &str) -> &str {
+        &s[..2]
+    }
+    let doc = Doc(trim, vec![Para(trim, vec![])]);
+    assert_eq!(doc.render(), "pa");
+}
@@ -0,0 +1,57 @@
+use coinduction::*;
+
+#[traitdef]
+trait Weigh {
+    fn weigh(&self) -> usize;
+}
+
+pub trait Peek<'a> {
+    fn peek(&'a self) -> &'a str;
+}
+
+#[coinduction(Weigh)]
+mod forest {
+    use super::{Peek, Weigh};
+
+    pub struct Tree(pub &'static str, pub Forest);
+    pub struct Forest(pub Vec<Tree>);
+
+    impl<'a> Peek<'a> for Tree {
+        fn peek(&'a self) -> &'a str {
+            self.0
+        }
+    }
+
+    impl Weigh for Tree
+    where
+        for<'a> Forest: Weigh + Peek<'a>,
+    {
+        fn weigh(&self) -> usize {
+            1 + self.1.weigh()
+        }
+    }
+
+    impl<'a> Peek<'a> for Forest {
+        fn peek(&'a self) -> &'a str {
+            self.0.first().map_or("", |tree| tree.peek())
+        }
+    }
+
+    impl Weigh for Forest
+    where
+        for<'a> Tree: Weigh + Peek<'a>,
+    {
+        fn weigh(&self) -> usize {
+            self.0.iter().map(|tree| tree.0.len()).sum()
+        }
+    }
+}
+
+// The cyclic `Weigh` bounds are dropped, while `Peek<'a>` only compiles if the `for<'a>` binder is kept
+#[test]
+fn test_higher_ranked_where_predicates() {
+    use forest::*;
+    let tree = Tree("root", Forest(vec![Tree("ab", Forest(vec![]))]));
+    assert_eq!(tree.weigh(), 3);
+    assert_eq!(tree.1.peek(), "ab");
+}
@@ -0,0 +1,128 @@
+use coinduction::*;
+
+#[traitdef]
+trait Show {
+    fn show(&self) -> String;
+}
+
+impl Show for u8 {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[coinduction(inherent, dump_graph, Show)]
+mod forest {
+    use super::Show;
+
+    pub struct Tree<T>(pub T, pub Vec<Forest<T>>);
+    pub struct Forest<T>(pub Vec<Tree<T>>);
+
+    impl<T> Show for Tree<T>
+    where
+        T: Show,
+        Forest<T>: Show,
+    {
+        fn show(&self) -> String {
+            let children: Vec<_> = self.1.iter().map(Show::show).collect();
+            format!("{}{}", self.0.show(), children.join(""))
+        }
+    }
+
+    impl<T> Show for Forest<T>
+    where
+        Tree<T>: Show,
+    {
+        fn show(&self) -> String {
+            let trees: Vec<_> = self.0.iter().map(Show::show).collect();
+            format!("[{}]", trees.join(","))
+        }
+    }
+
+    // The bound runs into the cycle above, and is rewritten to `T: Show` like those of the trait impls
+    impl<T> Tree<T>
+    where
+        Tree<T>: Show,
+    {
+        pub fn describe(&self) -> String {
+            format!("tree {}", self.show())
+        }
+    }
+
+    // Without bounds on the working traits there is nothing to resolve
+    impl<T> Forest<T> {
+        pub fn len(&self) -> usize {
+            self.0.len()
+        }
+    }
+}
+
+#[coinduction(dump_graph, allow_trivial, Show)]
+mod without_flag {
+    use super::Show;
+
+    pub struct Leaf;
+
+    impl Show for Leaf {
+        fn show(&self) -> String {
+            "leaf".to_string()
+        }
+    }
+
+    impl Leaf
+    where
+        Leaf: Show,
+    {
+        pub fn describe(&self) -> String {
+            format!("a {}", self.show())
+        }
+    }
+}
+
+// `Show` bounds the inherent impl only, which is enough to name it with the flag
+#[coinduction(inherent, allow_trivial, Show)]
+mod bounded_only {
+    use super::Show;
+
+    pub struct Labelled<T>(pub T);
+
+    impl<T> Labelled<T>
+    where
+        T: Show,
+    {
+        pub fn describe(&self) -> String {
+            format!("labelled {}", self.0.show())
+        }
+    }
+}
+
+#[test]
+fn test_inherent_impl_bounds_are_resolved() {
+    use forest::*;
+    let tree = Tree(1u8, vec![Forest(vec![Tree(2u8, vec![])])]);
+    assert_eq!(tree.describe(), "tree 1[2]");
+    assert_eq!(Forest(vec![tree]).len(), 1);
+
+    let graph = forest::__COINDUCTION_GRAPH;
+    // The two trait impls and the inherent impl with bounds
+    assert_eq!(graph.matches("digraph solver").count(), 3, "{}", graph);
+    assert_eq!(graph.matches("// Tree < T >").count(), 2, "{}", graph);
+    // Every one of them sees the cycle of `Tree<T>` and `Forest<T>`
+    assert_eq!(graph.matches("subgraph cluster_0").count(), 3, "{}", graph);
+}
+
+#[test]
+fn test_traits_bounding_inherent_impls_can_be_named() {
+    assert_eq!(bounded_only::Labelled(3u8).describe(), "labelled 3");
+}
+
+#[test]
+fn test_inherent_impls_need_the_flag() {
+    assert_eq!(without_flag::Leaf.describe(), "a leaf");
+    assert_eq!(
+        without_flag::__COINDUCTION_GRAPH
+            .matches("digraph solver")
+            .count(),
+        1
+    );
+}
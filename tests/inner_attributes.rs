@@ -0,0 +1,61 @@
+use coinduction::*;
+
+#[traitdef]
+trait Count {
+    fn count(&self) -> usize;
+}
+
+#[coinduction(Count)]
+mod counted {
+    #![allow(dead_code)]
+    #![deny(unused_mut)]
+
+    use super::Count;
+
+    pub struct Even;
+    pub struct Odd;
+
+    // Never constructed: would warn without the inner `allow(dead_code)`
+    struct Unused;
+
+    impl Count for Even
+    where
+        Odd: Count,
+    {
+        fn count(&self) -> usize {
+            Odd.count() + 1
+        }
+    }
+
+    impl Count for Odd
+    where
+        Even: Count,
+    {
+        fn count(&self) -> usize {
+            1
+        }
+    }
+}
+
+#[typedef(Count)]
+mod typed {
+    #![allow(dead_code)]
+
+    use super::Count;
+
+    pub struct Leaf;
+
+    struct Unused;
+
+    impl Count for Leaf {
+        fn count(&self) -> usize {
+            0
+        }
+    }
+}
+
+#[test]
+fn test_inner_attributes_preserved() {
+    assert_eq!(counted::Even.count(), 2);
+    assert_eq!(typed::Leaf.count(), 0);
+}
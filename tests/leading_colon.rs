@@ -0,0 +1,36 @@
+use coinduction::*;
+
+// The trait is named without a leading `::` here, while the impls and bounds mix both spellings
+#[coinduction(core::fmt::Debug)]
+mod shapes {
+    pub struct Even(pub Option<Box<Odd>>);
+    pub struct Odd(pub Box<Even>);
+
+    impl ::core::fmt::Debug for Even
+    where
+        Odd: core::fmt::Debug,
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match &self.0 {
+                Some(odd) => write!(f, "E({:?})", odd),
+                None => write!(f, "E"),
+            }
+        }
+    }
+
+    impl core::fmt::Debug for Odd
+    where
+        Even: ::core::fmt::Debug,
+    {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "O({:?})", self.0)
+        }
+    }
+}
+
+#[test]
+fn test_leading_colon_is_ignored() {
+    use shapes::*;
+    let value = Even(Some(Box::new(Odd(Box::new(Even(None))))));
+    assert_eq!(format!("{:?}", value), "E(O(E))");
+}
@@ -0,0 +1,102 @@
+use coinduction::*;
+use std::any::TypeId;
+
+#[traitdef]
+trait Identify {
+    fn identify(&self) -> TypeId;
+}
+
+#[coinduction(Identify)]
+mod identified {
+    use super::Identify;
+    use std::any::TypeId;
+    use std::marker::PhantomData;
+
+    pub struct NodeA<T>(pub PhantomData<T>);
+    pub struct NodeB<T>(pub PhantomData<T>);
+
+    // `TypeId::of` needs `T: 'static`, so dropping the lifetime bound fails to compile
+    impl<T: 'static> Identify for NodeA<T>
+    where
+        NodeB<T>: Identify,
+    {
+        fn identify(&self) -> TypeId {
+            TypeId::of::<T>()
+        }
+    }
+
+    impl<T> Identify for NodeB<T>
+    where
+        Self: 'static,
+        T: 'static + Clone,
+        NodeA<T>: Identify,
+    {
+        fn identify(&self) -> TypeId {
+            TypeId::of::<Self>()
+        }
+    }
+}
+
+#[traitdef]
+trait Label {
+    fn label(&self) -> String;
+}
+
+impl Label for u8 {
+    fn label(&self) -> String {
+        format!("u8:{}", self)
+    }
+}
+
+// The params are declared with bounds, which must not stop them from being substituted
+#[coinduction(Label)]
+mod labelled {
+    use super::Label;
+
+    pub struct Tree<'a, T>(pub &'a T, pub Option<&'a Forest<'a, T>>);
+    pub struct Forest<'a, T>(pub &'a [Tree<'a, T>]);
+
+    impl<'a: 'static, T: Clone> Label for Tree<'a, T>
+    where
+        T: Label,
+        Forest<'a, T>: Label,
+    {
+        fn label(&self) -> String {
+            match self.1 {
+                Some(forest) => format!("{}{}", self.0.label(), forest.label()),
+                None => self.0.label(),
+            }
+        }
+    }
+
+    impl<'b: 'static, U: Clone> Label for Forest<'b, U>
+    where
+        Tree<'b, U>: Label,
+    {
+        fn label(&self) -> String {
+            let labels: Vec<_> = self.0.iter().map(Label::label).collect();
+            format!("[{}]", labels.join(","))
+        }
+    }
+}
+
+#[test]
+fn test_static_bounds_survive_coinduction() {
+    use std::marker::PhantomData;
+    assert_eq!(
+        identified::NodeA::<u8>(PhantomData).identify(),
+        TypeId::of::<u8>()
+    );
+    assert_eq!(
+        identified::NodeB::<u8>(PhantomData).identify(),
+        TypeId::of::<identified::NodeB<u8>>()
+    );
+}
+
+#[test]
+fn test_bounded_lifetime_params_are_substituted() {
+    use labelled::*;
+    static LEAVES: [Tree<'static, u8>; 2] = [Tree(&1, None), Tree(&2, None)];
+    static FOREST: Forest<'static, u8> = Forest(&LEAVES);
+    assert_eq!(Tree(&0, Some(&FOREST)).label(), "u8:0[u8:1,u8:2]");
+}
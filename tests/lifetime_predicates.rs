@@ -0,0 +1,45 @@
+use coinduction::*;
+
+#[traitdef]
+trait Shorten {
+    fn shorten(&self) -> usize;
+}
+
+#[coinduction(Shorten)]
+mod shortened {
+    use super::Shorten;
+
+    pub struct Outer<'a, 'b>(pub &'a str, pub &'b str);
+    pub struct Inner<'a, 'b>(pub &'a str, pub &'b str);
+
+    // Returning `self.0` as `&'b str` only compiles if `'a: 'b` is kept
+    impl<'a, 'b> Shorten for Outer<'a, 'b>
+    where
+        'a: 'b,
+        Inner<'a, 'b>: Shorten,
+    {
+        fn shorten(&self) -> usize {
+            let shorter: &'b str = self.0;
+            shorter.len().min(self.1.len())
+        }
+    }
+
+    impl<'a, 'b> Shorten for Inner<'a, 'b>
+    where
+        'a: 'b,
+        Outer<'a, 'b>: Shorten,
+    {
+        fn shorten(&self) -> usize {
+            let shorter: &'b str = self.0;
+            shorter.len() + self.1.len()
+        }
+    }
+}
+
+#[test]
+fn test_lifetime_predicates_survive_coinduction() {
+    use shortened::*;
+    let long = String::from("abc");
+    assert_eq!(Outer(&long, "de").shorten(), 2);
+    assert_eq!(Inner(&long, "de").shorten(), 5);
+}
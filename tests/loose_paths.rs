@@ -0,0 +1,61 @@
+use coinduction::*;
+
+#[traitdef]
+trait Count {
+    fn count(&self) -> usize;
+}
+
+impl Count for u8 {
+    fn count(&self) -> usize {
+        1
+    }
+}
+
+// The impls spell `Vec` and the trait as imported, while the bounds qualify them
+#[coinduction(loose_paths, show_order, Count)]
+mod lists {
+    use super::Count;
+
+    pub struct Chain<T>(pub T);
+
+    impl<T: Count> Count for Chain<Vec<T>> {
+        fn count(&self) -> usize {
+            self.0.iter().map(Count::count).sum()
+        }
+    }
+
+    pub struct Node(pub u8, pub Option<Box<Chain<std::vec::Vec<Node>>>>);
+
+    impl Count for Node
+    where
+        Chain<std::vec::Vec<Node>>: super::Count,
+    {
+        fn count(&self) -> usize {
+            self.0.count() + self.1.as_ref().map_or(0, |chain| chain.count())
+        }
+    }
+}
+
+#[test]
+fn test_qualified_paths_match_loosely() {
+    use lists::*;
+    let leaf = Node(1, None);
+    let chain = Chain(vec![leaf, Node(2, None)]);
+    let root = Node(3, Some(Box::new(chain)));
+    assert_eq!(root.count(), 3);
+}
+
+#[test]
+fn test_qualified_paths_close_the_cycle() {
+    let order = lists::__COINDUCTION_ORDER;
+    let cycle = order
+        .iter()
+        .find(|step| step.starts_with("cycle("))
+        .expect("the cycle is listed");
+    assert!(cycle.contains("Node : Count"), "{:?}", order);
+    assert!(
+        cycle.contains("Chain < std :: vec :: Vec < Node > > : super :: Count"),
+        "{:?}",
+        order
+    );
+}
@@ -0,0 +1,93 @@
+use coinduction::*;
+
+#[traitdef]
+trait Show {
+    fn show(&self) -> String;
+}
+
+#[coinduction]
+mod marked {
+    use super::Show;
+
+    pub struct Leaf;
+    pub struct Pair<T>(pub T, pub T);
+
+    impl Clone for Leaf {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
+
+    impl Copy for Leaf {}
+
+    impl<T> Show for Pair<T>
+    where
+        T: Show + Sized + Copy,
+    {
+        fn show(&self) -> String {
+            let first = self.0;
+            format!("({} {})", first.show(), self.1.show())
+        }
+    }
+
+    impl Show for Leaf
+    where
+        Self: Sized,
+        Pair<Leaf>: Show,
+    {
+        fn show(&self) -> String {
+            "leaf".to_string()
+        }
+    }
+}
+
+// `Copy` is implemented in the module, so it would be picked up as a working trait; the explicit
+// `Sized` and `Copy` bounds must survive, as `Pair::show` copies out of `&self`
+#[test]
+fn test_marker_bounds_survive() {
+    use marked::*;
+    assert_eq!(Leaf.show(), "leaf");
+    assert_eq!(Pair(Leaf, Leaf).show(), "(leaf leaf)");
+}
+
+pub mod user {
+    use coinduction::traitdef;
+
+    // Shares its name with the marker trait, but is reached through a path of its own
+    #[traitdef]
+    pub trait Send {
+        fn hops(&self) -> usize;
+    }
+}
+
+#[coinduction]
+mod shadowing {
+    use super::user;
+
+    pub struct Ping;
+    pub struct Pong;
+
+    impl user::Send for Ping
+    where
+        Pong: user::Send,
+    {
+        fn hops(&self) -> usize {
+            Pong.hops() + 1
+        }
+    }
+
+    impl user::Send for Pong
+    where
+        Ping: user::Send,
+    {
+        fn hops(&self) -> usize {
+            1
+        }
+    }
+}
+
+#[test]
+fn test_user_trait_named_like_a_marker_is_resolved() {
+    use user::Send;
+    assert_eq!(shadowing::Ping.hops(), 2);
+}
@@ -0,0 +1,56 @@
+use coinduction::*;
+
+#[traitdef]
+trait Weigh {
+    fn weigh(&self) -> usize;
+}
+
+impl Weigh for str {
+    fn weigh(&self) -> usize {
+        self.len()
+    }
+}
+
+#[coinduction(Weigh)]
+mod unsized_params {
+    use super::Weigh;
+
+    pub struct Ptr<T: ?Sized>(pub Box<T>);
+    pub struct Slot<T: ?Sized>(pub Box<T>);
+    pub struct Node;
+
+    impl<T: ?Sized + Weigh> Weigh for Ptr<T> {
+        fn weigh(&self) -> usize {
+            self.0.weigh()
+        }
+    }
+
+    impl<T> Weigh for Slot<T>
+    where
+        T: Weigh + ?Sized,
+    {
+        fn weigh(&self) -> usize {
+            self.0.weigh() + 1
+        }
+    }
+
+    impl Weigh for Node
+    where
+        Slot<Node>: Weigh,
+    {
+        fn weigh(&self) -> usize {
+            1
+        }
+    }
+}
+
+// `Node` closes its cycle through `Slot`; `str` is unsized, so these only compile if the `?Sized` relaxations survived the rewrite
+#[test]
+fn test_maybe_sized_bounds_are_kept() {
+    use unsized_params::*;
+    let ptr: Ptr<str> = Ptr(Box::from("abc"));
+    assert_eq!(ptr.weigh(), 3);
+    let slot: Slot<str> = Slot(Box::from("ab"));
+    assert_eq!(slot.weigh(), 3);
+    assert_eq!(Node.weigh(), 1);
+}
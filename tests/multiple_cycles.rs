@@ -0,0 +1,132 @@
+use coinduction::*;
+
+#[traitdef]
+trait TraitX {
+    fn x(&self) -> usize;
+}
+
+#[traitdef]
+trait TraitY {
+    fn y(&self) -> usize;
+}
+
+#[coinduction(TraitX, TraitY)]
+mod shared {
+    use super::{TraitX, TraitY};
+
+    pub struct NodeA;
+    pub struct NodeB;
+    pub struct NodeC;
+
+    impl TraitX for NodeA
+    where
+        NodeB: TraitX,
+    {
+        fn x(&self) -> usize {
+            1
+        }
+    }
+
+    impl TraitX for NodeB
+    where
+        NodeA: TraitX,
+    {
+        fn x(&self) -> usize {
+            NodeA.x() + 1
+        }
+    }
+
+    impl TraitY for NodeA
+    where
+        NodeC: TraitY,
+    {
+        fn y(&self) -> usize {
+            10
+        }
+    }
+
+    impl TraitY for NodeC
+    where
+        NodeA: TraitY,
+    {
+        fn y(&self) -> usize {
+            NodeA.y() + 10
+        }
+    }
+}
+
+#[coinduction(TraitX, TraitY)]
+mod mixed {
+    use super::{TraitX, TraitY};
+
+    pub struct NodeA;
+    pub struct NodeB;
+    pub struct NodeC;
+
+    impl TraitX for NodeA
+    where
+        NodeB: TraitX,
+        NodeC: TraitY,
+    {
+        fn x(&self) -> usize {
+            1
+        }
+    }
+
+    impl TraitX for NodeB
+    where
+        NodeA: TraitX,
+    {
+        fn x(&self) -> usize {
+            2
+        }
+    }
+
+    impl TraitY for NodeA
+    where
+        NodeC: TraitY,
+        NodeB: TraitX,
+    {
+        fn y(&self) -> usize {
+            3
+        }
+    }
+
+    impl TraitY for NodeC
+    where
+        NodeA: TraitY,
+        NodeA: TraitX,
+    {
+        fn y(&self) -> usize {
+            4
+        }
+    }
+}
+
+// `NodeA` sits on a `TraitX` cycle with `NodeB` and on a separate `TraitY` cycle with `NodeC`;
+// both cyclic bounds have to be discharged without one cycle affecting the other.
+#[test]
+fn test_type_in_two_independent_cycles() {
+    use shared::*;
+    assert_eq!(NodeA.x(), 1);
+    assert_eq!(NodeB.x(), 2);
+    assert_eq!(NodeA.y(), 10);
+    assert_eq!(NodeC.y(), 20);
+}
+
+fn requires_both<T: TraitX + TraitY>(value: &T) -> usize {
+    value.x() + value.y()
+}
+
+#[test]
+fn test_type_in_two_cycles_satisfies_both_traits() {
+    assert_eq!(requires_both(&shared::NodeA), 11);
+}
+
+#[test]
+fn test_bounds_from_both_cycles_on_one_impl() {
+    use mixed::*;
+    assert_eq!(requires_both(&NodeA), 4);
+    assert_eq!(NodeB.x(), 2);
+    assert_eq!(NodeC.y(), 4);
+}
@@ -0,0 +1,76 @@
+use coinduction::*;
+
+#[traitdef((Option<$T:ty>) => { $T: Pong })]
+trait Ping {
+    fn ping(&self) -> usize;
+}
+
+impl<T: Pong> Ping for Option<T> {
+    fn ping(&self) -> usize {
+        self.as_ref().map_or(0, Pong::pong)
+    }
+}
+
+#[traitdef((Option<$T:ty>) => { $T: Ping })]
+trait Pong {
+    fn pong(&self) -> usize;
+}
+
+impl<T: Ping> Pong for Option<T> {
+    fn pong(&self) -> usize {
+        self.as_ref().map_or(0, Ping::ping)
+    }
+}
+
+#[coinduction(show_order, Ping, Pong)]
+mod rally {
+    use super::{Ping, Pong};
+
+    pub struct Serve(pub Option<Box<Volley>>);
+    pub struct Volley(pub Option<Box<Serve>>);
+
+    // Each impl only holds through the other trait, so the cycle crosses both of them
+    impl Ping for Serve
+    where
+        Option<Volley>: Ping,
+    {
+        fn ping(&self) -> usize {
+            self.0.as_ref().map_or(1, |volley| volley.pong() + 1)
+        }
+    }
+
+    impl Pong for Volley
+    where
+        Option<Serve>: Pong,
+    {
+        fn pong(&self) -> usize {
+            self.0.as_ref().map_or(1, |serve| serve.ping() + 1)
+        }
+    }
+}
+
+#[test]
+fn test_mutually_dependent_traits() {
+    use rally::*;
+    let serve = Serve(Some(Box::new(Volley(Some(Box::new(Serve(None)))))));
+    assert_eq!(serve.ping(), 3);
+    assert_eq!(Volley(None).pong(), 1);
+    assert_eq!(Some(Volley(None)).ping(), 1);
+}
+
+#[test]
+fn test_cycle_spans_both_traits() {
+    let order = rally::__COINDUCTION_ORDER;
+    let cycles: Vec<_> = order
+        .iter()
+        .filter(|step| step.starts_with("cycle("))
+        .collect();
+    // Both impls see the same cycle through `Ping` and `Pong`
+    assert_eq!(cycles.len(), 2, "{:?}", order);
+    for cycle in cycles {
+        assert!(cycle.contains("Serve : Ping"), "{:?}", order);
+        assert!(cycle.contains("Volley : Pong"), "{:?}", order);
+        assert!(cycle.contains("Option < Volley > : Ping"), "{:?}", order);
+        assert!(cycle.contains("Option < Serve > : Pong"), "{:?}", order);
+    }
+}
@@ -0,0 +1,124 @@
+use coinduction::*;
+
+#[traitdef]
+trait Evaluate {
+    fn evaluate(&self) -> i32;
+}
+
+#[coinduction(Evaluate)]
+mod outer {
+    use super::Evaluate;
+
+    pub struct Top;
+
+    impl Evaluate for Top
+    where
+        inner::Even: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            inner::Even(2).evaluate()
+        }
+    }
+
+    pub mod inner {
+        use super::super::Evaluate;
+
+        pub struct Even(pub i32);
+        pub struct Odd(pub i32);
+
+        impl Evaluate for Even
+        where
+            Odd: Evaluate,
+        {
+            fn evaluate(&self) -> i32 {
+                if self.0 == 0 {
+                    1
+                } else {
+                    Odd(self.0 - 1).evaluate()
+                }
+            }
+        }
+
+        impl Evaluate for Odd
+        where
+            Even: Evaluate,
+        {
+            fn evaluate(&self) -> i32 {
+                if self.0 == 0 {
+                    0
+                } else {
+                    Even(self.0 - 1).evaluate()
+                }
+            }
+        }
+
+        // Deeper modules are resolved as well, and may name the trait by a path
+        pub mod deeper {
+            pub struct Ping;
+            pub struct Pong;
+
+            impl super::super::Evaluate for Ping
+            where
+                Pong: super::super::Evaluate,
+            {
+                fn evaluate(&self) -> i32 {
+                    1
+                }
+            }
+
+            impl super::super::Evaluate for Pong
+            where
+                Ping: super::super::Evaluate,
+            {
+                fn evaluate(&self) -> i32 {
+                    2
+                }
+            }
+        }
+    }
+}
+
+#[coinduction(super::Evaluate)]
+mod relative {
+    pub mod inner {
+        pub struct A;
+        pub struct B;
+
+        impl super::super::Evaluate for A
+        where
+            B: super::super::Evaluate,
+        {
+            fn evaluate(&self) -> i32 {
+                3
+            }
+        }
+
+        impl super::super::Evaluate for B
+        where
+            A: super::super::Evaluate,
+        {
+            fn evaluate(&self) -> i32 {
+                4
+            }
+        }
+    }
+}
+
+#[test]
+fn test_nested_module_cycle() {
+    assert_eq!(outer::inner::Even(4).evaluate(), 1);
+    assert_eq!(outer::inner::Odd(4).evaluate(), 0);
+    assert_eq!(outer::Top.evaluate(), 1);
+}
+
+#[test]
+fn test_deeper_module_cycle() {
+    use outer::inner::deeper::*;
+    assert_eq!(Ping.evaluate() + Pong.evaluate(), 3);
+}
+
+#[test]
+fn test_nested_module_with_relative_trait_path() {
+    assert_eq!(relative::inner::A.evaluate(), 3);
+    assert_eq!(relative::inner::B.evaluate(), 4);
+}
@@ -0,0 +1,51 @@
+use coinduction::*;
+
+#[traitdef]
+trait Describe {
+    fn describe(&self) -> String;
+}
+
+// The never type takes part in the cycle through the return type of a function pointer
+#[coinduction(Describe)]
+mod steps {
+    use super::Describe;
+
+    pub struct Abort<F>(pub F);
+
+    pub enum Step {
+        Done,
+        Next(Box<Abort<fn() -> !>>, Box<Step>),
+    }
+
+    impl Describe for Abort<fn() -> !>
+    where
+        Step: Describe,
+    {
+        fn describe(&self) -> String {
+            "abort".to_string()
+        }
+    }
+
+    impl Describe for Step
+    where
+        Abort<fn() -> !>: Describe,
+    {
+        fn describe(&self) -> String {
+            match self {
+                Step::Done => "done".to_string(),
+                Step::Next(abort, next) => format!("{} {}", abort.describe(), next.describe()),
+            }
+        }
+    }
+}
+
+fn diverge() -> ! {
+    panic!("never called")
+}
+
+#[test]
+fn test_never_type_in_cycle() {
+    use steps::*;
+    let step = Step::Next(Box::new(Abort(diverge)), Box::new(Step::Done));
+    assert_eq!(step.describe(), "abort done");
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "coinduction_passthrough")]
+
+use coinduction::*;
+
+#[traitdef]
+trait Describe {
+    fn describe(&self) -> String;
+}
+
+#[typedef(Describe)]
+mod types {
+    use super::Describe;
+
+    pub struct Leaf;
+
+    impl Describe for Leaf {
+        fn describe(&self) -> String {
+            "Leaf".to_string()
+        }
+    }
+}
+
+#[coinduction(stats, Describe)]
+mod passed {
+    use super::Describe;
+
+    // Would collide with the constant emitted by the `stats` flag if the module were processed
+    pub const __COINDUCTION_STATS: &str = "untouched";
+
+    pub struct Node;
+
+    impl Describe for Node
+    where
+        super::types::Leaf: Describe,
+    {
+        fn describe(&self) -> String {
+            "Node".to_string()
+        }
+    }
+}
+
+#[test]
+fn test_passthrough_keeps_input() {
+    assert_eq!(passed::__COINDUCTION_STATS, "untouched");
+    assert_eq!(passed::Node.describe(), "Node");
+    assert_eq!(types::Leaf.describe(), "Leaf");
+}
@@ -0,0 +1,58 @@
+use coinduction::*;
+
+#[traitdef]
+trait Describe {
+    fn describe(&self) -> String;
+}
+
+pub trait Project {
+    type Assoc;
+}
+
+#[coinduction(Describe)]
+mod projected {
+    use super::{Describe, Project};
+    use std::marker::PhantomData;
+
+    pub struct NodeA<T>(pub PhantomData<T>);
+    pub struct NodeB<T>(pub PhantomData<T>);
+
+    impl<T> Project for NodeA<T> {
+        type Assoc = NodeB<T>;
+    }
+
+    impl<T> Project for NodeB<T> {
+        type Assoc = NodeA<T>;
+    }
+
+    impl<T> Describe for NodeA<T>
+    where
+        <NodeA<T> as Project>::Assoc: Describe,
+    {
+        fn describe(&self) -> String {
+            "NodeA".to_string()
+        }
+    }
+
+    impl<T> Describe for NodeB<T>
+    where
+        <NodeB<T> as super::Project>::Assoc: Describe,
+    {
+        fn describe(&self) -> String {
+            format!("NodeB -> {}", NodeA::<T>(PhantomData).describe())
+        }
+    }
+}
+
+#[test]
+fn test_fully_qualified_cyclic_bound() {
+    use std::marker::PhantomData;
+    assert_eq!(
+        projected::NodeA::<u8>(PhantomData).describe(),
+        "NodeA".to_string()
+    );
+    assert_eq!(
+        projected::NodeB::<u8>(PhantomData).describe(),
+        "NodeB -> NodeA".to_string()
+    );
+}
@@ -0,0 +1,62 @@
+#![allow(non_camel_case_types)]
+
+use coinduction::*;
+
+#[traitdef((($T:ty, $U:ty)) => { $T: r#match, $U: r#match })]
+pub trait r#match {
+    fn matched(&self) -> usize;
+}
+
+impl<T: r#match, U: r#match> r#match for (T, U) {
+    fn matched(&self) -> usize {
+        self.0.matched() + self.1.matched()
+    }
+}
+
+#[coinduction(r#match)]
+mod raw {
+    use super::r#match;
+
+    pub struct A;
+    pub struct B;
+
+    impl r#match for A
+    where
+        (B, B): r#match,
+    {
+        fn matched(&self) -> usize {
+            (B, B).matched()
+        }
+    }
+
+    impl r#match for B
+    where
+        A: r#match,
+    {
+        fn matched(&self) -> usize {
+            1
+        }
+    }
+}
+
+#[typedef(r#match)]
+mod raw_types {
+    use super::r#match;
+
+    pub struct r#type;
+
+    impl r#match for r#type {
+        fn matched(&self) -> usize {
+            3
+        }
+    }
+}
+
+// The relay invokes `r#match! { ... }`, so the raw form of the trait name must be kept
+// intact, while the generated helper macro is named from the unraw identifier.
+#[test]
+fn test_raw_ident_trait_resolves_through_relay() {
+    assert_eq!(raw::A.matched(), 2);
+    assert_eq!(raw::B.matched(), 1);
+    assert_eq!(raw_types::r#type.matched(), 3);
+}
@@ -0,0 +1,88 @@
+use coinduction::*;
+
+#[traitdef(
+    ([$T:ty; $N:expr]) => { $T: Depth },
+    (Box<$T:ty>) => { $T: Depth }
+)]
+trait Depth {
+    fn depth(&self) -> usize;
+}
+
+impl<T: Depth, const N: usize> Depth for [T; N] {
+    fn depth(&self) -> usize {
+        self.iter().map(Depth::depth).max().unwrap_or(0)
+    }
+}
+
+impl<T: Depth> Depth for Box<T> {
+    fn depth(&self) -> usize {
+        self.as_ref().depth()
+    }
+}
+
+#[coinduction(Depth)]
+mod octree {
+    use super::Depth;
+
+    pub struct Vec3<T>(pub [T; 3]);
+    pub struct Grid<T, const N: usize>(pub [T; N]);
+
+    pub enum Node {
+        Leaf,
+        Branch(Box<Vec3<Node>>),
+        Wide(Box<Grid<Node, 2>>),
+    }
+
+    impl<T> Depth for Vec3<T>
+    where
+        [T; 3]: Depth,
+    {
+        fn depth(&self) -> usize {
+            self.0.depth()
+        }
+    }
+
+    impl<T, const N: usize> Depth for Grid<T, N>
+    where
+        [T; N]: Depth,
+    {
+        fn depth(&self) -> usize {
+            self.0.depth()
+        }
+    }
+
+    // The cycle back to `Node` only shows up once `[Node; 3]` is peeled into its element
+    impl Depth for Node
+    where
+        Box<Vec3<Node>>: Depth,
+        Box<Grid<Node, 2>>: Depth,
+    {
+        fn depth(&self) -> usize {
+            match self {
+                Node::Leaf => 0,
+                Node::Branch(children) => children.depth() + 1,
+                Node::Wide(children) => children.depth() + 1,
+            }
+        }
+    }
+}
+
+#[test]
+fn test_array_of_recursive_elements() {
+    use octree::*;
+    let leaves = || Vec3([Node::Leaf, Node::Leaf, Node::Leaf]);
+    let tree = Node::Branch(Box::new(Vec3([
+        Node::Leaf,
+        Node::Branch(Box::new(leaves())),
+        Node::Wide(Box::new(Grid([
+            Node::Leaf,
+            Node::Branch(Box::new(Vec3([
+                Node::Branch(Box::new(leaves())),
+                Node::Leaf,
+                Node::Leaf,
+            ]))),
+        ]))),
+    ])));
+    assert_eq!(tree.depth(), 4);
+    assert_eq!(Vec3([Node::Leaf, Node::Leaf, Node::Leaf]).depth(), 0);
+}
@@ -0,0 +1,56 @@
+#[test]
+fn test_relay_parse_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/relay_*.rs");
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn test_solver_invariant_assertions() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/invariant_*.rs");
+}
+
+#[test]
+fn test_unsupported_constraint_types() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/macro_type_*.rs");
+    t.compile_fail("tests/ui/generic_projection_*.rs");
+}
+
+#[test]
+fn test_ambiguous_trait_names() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/trait_name_collision.rs");
+}
+
+#[test]
+fn test_runaway_rules_are_reported() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/runaway_*.rs");
+}
+
+#[test]
+fn test_unregistered_traits_are_rejected() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/unregistered_*.rs");
+}
+
+#[test]
+fn test_modules_without_cycles_only_warn() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/trivial_*.rs");
+}
+
+#[test]
+fn test_misspelled_traits_are_rejected() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/misspelled_*.rs");
+    t.compile_fail("tests/ui/bound_only_trait.rs");
+}
+
+#[test]
+fn test_unsupported_attributes_are_reported() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/unsupported_attribute.rs");
+}
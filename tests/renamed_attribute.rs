@@ -0,0 +1,72 @@
+extern crate coinduction as coind;
+
+use coind::traitdef;
+
+#[traitdef]
+trait Parity {
+    fn is_even(&self, n: usize) -> bool;
+}
+
+#[coind::coinductive(Parity)]
+mod aliased {
+    use super::Parity;
+
+    pub struct Even;
+    pub struct Odd;
+
+    impl Parity for Even
+    where
+        Odd: Parity,
+    {
+        fn is_even(&self, n: usize) -> bool {
+            n == 0 || Odd.is_even(n - 1)
+        }
+    }
+
+    impl Parity for Odd
+    where
+        Even: Parity,
+    {
+        fn is_even(&self, n: usize) -> bool {
+            n != 0 && Even.is_even(n - 1)
+        }
+    }
+}
+
+#[coind::coinductive(coinduction = ::coind, Parity)]
+mod renamed_facade {
+    use super::Parity;
+
+    pub struct Even;
+    pub struct Odd;
+
+    impl Parity for Even
+    where
+        Odd: Parity,
+    {
+        fn is_even(&self, n: usize) -> bool {
+            n == 0 || Odd.is_even(n - 1)
+        }
+    }
+
+    impl Parity for Odd
+    where
+        Even: Parity,
+    {
+        fn is_even(&self, n: usize) -> bool {
+            n != 0 && Even.is_even(n - 1)
+        }
+    }
+}
+
+#[test]
+fn test_coinductive_alias() {
+    assert!(aliased::Even.is_even(4));
+    assert!(!aliased::Even.is_even(3));
+}
+
+#[test]
+fn test_coinductive_alias_with_renamed_facade() {
+    assert!(renamed_facade::Even.is_even(2));
+    assert!(!renamed_facade::Odd.is_even(2));
+}
@@ -0,0 +1,44 @@
+use coinduction::*;
+
+#[traitdef]
+trait Show {
+    fn show(&self) -> String;
+}
+
+#[coinduction(Show)]
+mod self_bounded {
+    use super::Show;
+
+    pub struct Slot<T>(pub Option<Box<T>>);
+    pub struct Tree(pub Slot<Tree>);
+
+    impl<T> Show for Slot<T>
+    where
+        T: Show,
+    {
+        fn show(&self) -> String {
+            match &self.0 {
+                Some(inner) => format!("({})", inner.show()),
+                None => "()".to_string(),
+            }
+        }
+    }
+
+    impl Show for Tree
+    where
+        Self: Sized,
+        Slot<Self>: Show,
+    {
+        fn show(&self) -> String {
+            format!("tree{}", self.0.show())
+        }
+    }
+}
+
+// `Slot<Self>: Show` only closes the cycle once `Self` is read as `Tree`
+#[test]
+fn test_self_in_where_clause() {
+    use self_bounded::*;
+    let tree = Tree(Slot(Some(Box::new(Tree(Slot(None))))));
+    assert_eq!(tree.show(), "tree(tree())");
+}
@@ -0,0 +1,56 @@
+use coinduction::*;
+
+#[traitdef]
+trait Show {
+    fn show(&self) -> String;
+}
+
+impl Show for u8 {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[coinduction(show_order, allow_trivial, Show)]
+mod nested {
+    use super::Show;
+
+    pub struct Outer<T>(pub Wrap<Wrap<T>>);
+    pub struct Wrap<T>(pub T);
+
+    impl<T> Show for Outer<T>
+    where
+        Wrap<Wrap<T>>: Show,
+    {
+        fn show(&self) -> String {
+            format!("outer{}", self.0.show())
+        }
+    }
+
+    impl<T> Show for Wrap<T>
+    where
+        T: Show,
+    {
+        fn show(&self) -> String {
+            format!("[{}]", self.0.show())
+        }
+    }
+}
+
+// The `T` of `Wrap<T>` is bound to `Wrap<T>` of `Outer<T>`, which is a different `T`
+#[test]
+fn test_rule_params_shadowing_impl_params() {
+    use nested::*;
+    assert_eq!(Outer(Wrap(Wrap(3u8))).show(), "outer[[3]]");
+}
+
+#[test]
+fn test_rule_expands_within_outer() {
+    let order = nested::__COINDUCTION_ORDER;
+    let outer = order
+        .iter()
+        .position(|step| *step == "Outer < T > : Show")
+        .expect("the impl is listed");
+    assert!(order[..outer].contains(&"Wrap < T > : Show"), "{:?}", order);
+    assert!(order[..outer].contains(&"T : Show"), "{:?}", order);
+}
@@ -0,0 +1,61 @@
+use coinduction::*;
+
+#[traitdef]
+trait Evaluate {
+    fn evaluate(&self) -> i32;
+}
+
+#[coinduction(show_order, Evaluate)]
+mod ordered {
+    use super::Evaluate;
+
+    pub struct Expr;
+    pub struct Term;
+    pub struct Lit;
+
+    impl Evaluate for Lit {
+        fn evaluate(&self) -> i32 {
+            1
+        }
+    }
+
+    impl Evaluate for Expr
+    where
+        Term: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            Term.evaluate() + 1
+        }
+    }
+
+    impl Evaluate for Term
+    where
+        Expr: Evaluate,
+        Lit: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            Lit.evaluate()
+        }
+    }
+}
+
+#[test]
+fn test_order_lists_leaves_before_cycles() {
+    let order = ordered::__COINDUCTION_ORDER;
+    let leaf = order
+        .iter()
+        .position(|step| *step == "Lit : Evaluate")
+        .expect("the leaf is listed");
+    let cycle = order
+        .iter()
+        .position(|step| step.starts_with("cycle("))
+        .expect("the cycle is listed");
+    assert!(leaf < cycle, "{:?}", order);
+    assert!(order[cycle].contains("Expr : Evaluate"));
+    assert!(order[cycle].contains("Term : Evaluate"));
+}
+
+#[test]
+fn test_order_does_not_affect_impls() {
+    assert_eq!(ordered::Expr.evaluate(), 2);
+}
@@ -0,0 +1,120 @@
+use coinduction::*;
+
+#[traitdef]
+trait Fold<Acc> {
+    fn fold(&self, acc: Acc) -> Acc;
+}
+
+pub struct Wrapper<T>(pub Vec<T>);
+
+pub struct BagMarker;
+
+#[typedef(Fold, marker = BagMarker)]
+pub mod bags {
+    use super::{Fold, Wrapper};
+
+    pub struct Bag<T>(pub Vec<T>);
+
+    // `U` is only bound through the trait argument, and `Wrapper` is a relative path which the
+    // relayed constraints refer to through a projection on the marker
+    impl<T, U> Fold<Wrapper<U>> for Bag<T>
+    where
+        T: Fold<Wrapper<U>>,
+    {
+        fn fold(&self, acc: Wrapper<U>) -> Wrapper<U> {
+            self.0.iter().fold(acc, |acc, item| item.fold(acc))
+        }
+    }
+}
+
+#[coinduction(show_order, Fold)]
+mod forest {
+    use super::bags::Bag;
+    use super::{Fold, Wrapper};
+
+    pub enum Tree<T> {
+        Leaf(T),
+        Node(Box<Forest<T>>),
+        Bag(Bag<Forest<T>>),
+    }
+
+    pub struct Forest<T>(pub Vec<Tree<T>>);
+
+    // The trait argument is a composite of the impl's param, so matching binds through it
+    impl<T: Clone> Fold<Wrapper<T>> for Tree<T>
+    where
+        Forest<T>: Fold<Wrapper<T>>,
+        Bag<Forest<T>>: Fold<Wrapper<T>>,
+    {
+        fn fold(&self, mut acc: Wrapper<T>) -> Wrapper<T> {
+            match self {
+                Tree::Leaf(value) => {
+                    acc.0.push(value.clone());
+                    acc
+                }
+                Tree::Node(forest) => forest.fold(acc),
+                Tree::Bag(bag) => bag.fold(acc),
+            }
+        }
+    }
+
+    impl<T: Clone> Fold<Wrapper<T>> for Forest<T>
+    where
+        Tree<T>: Fold<Wrapper<T>>,
+    {
+        fn fold(&self, acc: Wrapper<T>) -> Wrapper<T> {
+            self.0.iter().fold(acc, |acc, tree| tree.fold(acc))
+        }
+    }
+
+    // A differently specialized impl of the same trait is not part of the cycle
+    impl<T> Fold<usize> for Forest<T>
+    where
+        Tree<T>: Fold<Wrapper<T>>,
+    {
+        fn fold(&self, acc: usize) -> usize {
+            acc + self.0.len()
+        }
+    }
+}
+
+#[test]
+fn test_specialized_trait_argument() {
+    use bags::Bag;
+    use forest::*;
+    let forest = Forest(vec![
+        Tree::Leaf(1),
+        Tree::Node(Box::new(Forest(vec![Tree::Leaf(2), Tree::Leaf(3)]))),
+        Tree::Bag(Bag(vec![Forest(vec![Tree::Leaf(4)])])),
+    ]);
+    assert_eq!(forest.fold(Wrapper(vec![0])).0, vec![0, 1, 2, 3, 4]);
+    assert_eq!(forest.fold(10usize), 13);
+}
+
+#[test]
+fn test_specialized_cycle_is_resolved() {
+    let order = forest::__COINDUCTION_ORDER;
+    let cycles: Vec<_> = order
+        .iter()
+        .filter(|step| step.starts_with("cycle("))
+        .collect();
+    assert_eq!(cycles.len(), 3, "{:?}", order);
+    for cycle in cycles {
+        assert!(
+            cycle.contains("Tree < T > : Fold < Wrapper < T > >"),
+            "{:?}",
+            order
+        );
+        assert!(
+            cycle.contains("Forest < T > : Fold < Wrapper < T > >"),
+            "{:?}",
+            order
+        );
+        assert!(
+            cycle.contains("Bag < Forest < T > > : Fold < Wrapper < T > >"),
+            "{:?}",
+            order
+        );
+        assert!(!cycle.contains("Fold < usize >"), "{:?}", order);
+    }
+}
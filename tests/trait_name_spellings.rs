@@ -0,0 +1,87 @@
+use coinduction::*;
+
+#[traitdef]
+trait Show {
+    fn show(&self) -> String;
+}
+
+// Traits which are not resolved are not checked for ambiguous names
+#[coinduction(Show)]
+mod debugged {
+    use super::Show;
+
+    pub struct Node;
+    pub struct Leaf;
+
+    impl Show for Node
+    where
+        Leaf: Show,
+    {
+        fn show(&self) -> String {
+            "node".to_string()
+        }
+    }
+
+    impl Show for Leaf
+    where
+        Node: Show,
+    {
+        fn show(&self) -> String {
+            "leaf".to_string()
+        }
+    }
+
+    impl core::fmt::Debug for Node {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "Node")
+        }
+    }
+
+    impl std::fmt::Debug for Leaf {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Leaf")
+        }
+    }
+}
+
+// An imported name and a qualified path ending in it are the same trait, which loose paths
+// resolve as one
+#[coinduction(loose_paths)]
+mod spelled {
+    use super::Show;
+
+    pub struct Node;
+    pub struct Leaf;
+
+    impl Show for Node
+    where
+        Leaf: Show,
+    {
+        fn show(&self) -> String {
+            "node".to_string()
+        }
+    }
+
+    impl super::Show for Leaf
+    where
+        Node: super::Show,
+    {
+        fn show(&self) -> String {
+            "leaf".to_string()
+        }
+    }
+}
+
+#[test]
+fn test_unresolved_traits_may_share_names() {
+    use debugged::*;
+    assert_eq!(Node.show(), "node");
+    assert_eq!(format!("{:?} {:?}", Node, Leaf), "Node Leaf");
+}
+
+#[test]
+fn test_one_trait_spelled_two_ways() {
+    use spelled::*;
+    assert_eq!(Node.show(), "node");
+    assert_eq!(Leaf.show(), "leaf");
+}
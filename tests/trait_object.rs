@@ -0,0 +1,77 @@
+use coinduction::*;
+
+#[traitdef]
+trait Accept {
+    fn accept(&self) -> usize;
+}
+
+pub trait Visitor<T> {}
+
+#[coinduction(Accept)]
+mod visiting {
+    use super::{Accept, Visitor};
+    use std::marker::PhantomData;
+
+    pub struct Boxed<V: ?Sized>(pub Box<V>);
+    pub struct Scoped<V: ?Sized>(pub Box<V>);
+    pub struct Node<T>(pub PhantomData<T>);
+    pub struct Leaf<'a, T>(pub PhantomData<&'a T>);
+
+    impl<T> Accept for Boxed<dyn Visitor<T>>
+    where
+        Node<T>: Accept,
+    {
+        fn accept(&self) -> usize {
+            1
+        }
+    }
+
+    impl<T> Accept for Node<T>
+    where
+        Boxed<dyn Visitor<T>>: Accept,
+    {
+        fn accept(&self) -> usize {
+            2
+        }
+    }
+
+    impl<'a, T> Accept for Scoped<dyn Visitor<T> + 'a>
+    where
+        Leaf<'a, T>: Accept,
+    {
+        fn accept(&self) -> usize {
+            3
+        }
+    }
+
+    impl<'a, T> Accept for Leaf<'a, T>
+    where
+        Scoped<dyn Visitor<T> + 'a>: Accept,
+    {
+        fn accept(&self) -> usize {
+            4
+        }
+    }
+}
+
+struct Printer;
+impl Visitor<String> for Printer {}
+
+#[test]
+fn test_trait_object_bound_in_cycle() {
+    use std::marker::PhantomData;
+    use visiting::*;
+    let visitor: Boxed<dyn Visitor<String>> = Boxed(Box::new(Printer));
+    assert_eq!(visitor.accept(), 1);
+    assert_eq!(Node::<String>(PhantomData).accept(), 2);
+}
+
+#[test]
+fn test_trait_object_lifetime_bound_in_cycle() {
+    use std::marker::PhantomData;
+    use visiting::*;
+    fn scoped<'a>(visitor: Scoped<dyn Visitor<String> + 'a>) -> usize {
+        visitor.accept() + Leaf::<'a, String>(PhantomData).accept()
+    }
+    assert_eq!(scoped(Scoped(Box::new(Printer))), 7);
+}
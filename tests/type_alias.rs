@@ -0,0 +1,118 @@
+use coinduction::*;
+
+#[traitdef(
+    (Option<$T:ty>) => { $T: Count },
+    (Box<$T:ty>) => { $T: Count }
+)]
+trait Count {
+    fn count(&self) -> usize;
+}
+
+impl<T: Count> Count for Option<T> {
+    fn count(&self) -> usize {
+        self.as_ref().map_or(0, Count::count)
+    }
+}
+
+impl<T: Count> Count for Box<T> {
+    fn count(&self) -> usize {
+        self.as_ref().count()
+    }
+}
+
+impl Count for u8 {
+    fn count(&self) -> usize {
+        1
+    }
+}
+
+#[typedef(Count)]
+pub mod cells {
+    use super::Count;
+
+    pub type Shared<T> = Box<T>;
+
+    pub struct Cell<T>(pub Shared<T>);
+
+    impl<T> Count for Cell<T>
+    where
+        Shared<T>: Count,
+    {
+        fn count(&self) -> usize {
+            self.0.count()
+        }
+    }
+}
+
+#[coinduction(show_order, Count)]
+mod list {
+    use super::cells::Cell;
+    use super::Count;
+
+    type Boxed<T> = Box<T>;
+    pub type NodePtr<T> = Option<Boxed<Node<T>>>;
+    pub type Pair<A, B> = (A, B);
+
+    pub struct Node<T> {
+        pub value: Cell<T>,
+        pub next: NodePtr<T>,
+    }
+
+    impl<T> Count for Node<T>
+    where
+        Cell<T>: Count,
+        NodePtr<T>: Count,
+    {
+        fn count(&self) -> usize {
+            self.value.count() + self.next.count()
+        }
+    }
+
+    pub struct Labelled<T>(pub Pair<Node<T>, u8>);
+
+    impl<T> Count for Labelled<T>
+    where
+        Node<T>: Count,
+    {
+        fn count(&self) -> usize {
+            self.0 .0.count() + 1
+        }
+    }
+}
+
+#[test]
+fn test_aliases_are_expanded() {
+    use cells::Cell;
+    use list::*;
+    let tail = Node {
+        value: Cell(Box::new(1u8)),
+        next: None,
+    };
+    let head = Node {
+        value: Cell(Box::new(2u8)),
+        next: Some(Box::new(tail)),
+    };
+    assert_eq!(head.count(), 2);
+    let labelled: Pair<_, _> = (head, 0);
+    assert_eq!(Labelled(labelled).count(), 3);
+}
+
+#[test]
+fn test_aliased_constraints_close_the_cycle() {
+    let order = list::__COINDUCTION_ORDER;
+    let cycle = order
+        .iter()
+        .find(|step| step.starts_with("cycle("))
+        .expect("the cycle is listed");
+    assert!(cycle.contains("Node < T > : Count"), "{:?}", order);
+    assert!(
+        cycle.contains("Option < Box < Node < T > > > : Count"),
+        "{:?}",
+        order
+    );
+    assert!(
+        !order.iter().any(|step| step.contains("NodePtr")),
+        "{:?}",
+        order
+    );
+}
@@ -0,0 +1,98 @@
+use coinduction::*;
+use std::mem::ManuallyDrop;
+
+#[traitdef]
+trait Weight {
+    fn weight(&self) -> u32;
+}
+
+#[typedef(Weight)]
+pub mod containers {
+    use super::Weight;
+    use std::mem::ManuallyDrop;
+
+    pub struct Unit(pub u32);
+
+    impl Weight for Unit {
+        fn weight(&self) -> u32 {
+            self.0
+        }
+    }
+
+    pub enum Either<L, R> {
+        Left(L),
+        Right(R),
+    }
+
+    impl<L, R> Weight for Either<L, R>
+    where
+        L: Weight,
+        R: Weight,
+    {
+        fn weight(&self) -> u32 {
+            match self {
+                Either::Left(left) => left.weight(),
+                Either::Right(right) => right.weight(),
+            }
+        }
+    }
+
+    /// Always holds a value; the union only exists to be resolved through
+    pub union Slot<T> {
+        pub value: ManuallyDrop<T>,
+    }
+
+    impl<T> Weight for Slot<T>
+    where
+        T: Weight,
+    {
+        fn weight(&self) -> u32 {
+            unsafe { self.value.weight() }
+        }
+    }
+}
+
+#[coinduction(show_order, Weight)]
+mod tree {
+    use super::containers::{Either, Slot, Unit};
+    use super::Weight;
+
+    pub struct Node(pub Box<Either<Unit, Slot<Node>>>);
+
+    impl Weight for Node
+    where
+        Either<Unit, Slot<Node>>: Weight,
+    {
+        fn weight(&self) -> u32 {
+            1 + self.0.weight()
+        }
+    }
+}
+
+#[test]
+fn test_enum_and_union_self_types() {
+    use containers::{Either, Slot, Unit};
+    use tree::Node;
+    let leaf = Node(Box::new(Either::Left(Unit(5))));
+    let node = Node(Box::new(Either::Right(Slot {
+        value: ManuallyDrop::new(leaf),
+    })));
+    assert_eq!(node.weight(), 7);
+}
+
+// The rules of both the enum and the union are applied on the way back to `Node`
+#[test]
+fn test_enum_and_union_rules_close_the_cycle() {
+    let order = tree::__COINDUCTION_ORDER;
+    let cycle = order
+        .iter()
+        .find(|step| step.starts_with("cycle("))
+        .expect("the cycle is listed");
+    assert!(
+        cycle.contains("Either < Unit, Slot < Node > > : Weight"),
+        "{:?}",
+        order
+    );
+    assert!(cycle.contains("Slot < Node > : Weight"), "{:?}", order);
+    assert!(cycle.contains("Node : Weight"), "{:?}", order);
+}
@@ -0,0 +1,125 @@
+use coinduction::*;
+#[traitdef]
+trait Describe<S> {
+    fn describe(&self) -> String;
+}
+
+#[typedef(Describe)]
+pub mod cells {
+    use super::Describe;
+
+    pub struct Hex<U>(pub U);
+
+    impl<S, U> Describe<S> for Hex<U>
+    where
+        U: ::core::fmt::UpperHex,
+    {
+        fn describe(&self) -> String {
+            format!("{:X}", self.0)
+        }
+    }
+
+    pub struct Tagged<T, U>(pub T, pub U);
+
+    impl<T, S, U> Describe<S> for Tagged<T, U>
+    where
+        T: Describe<S>,
+        U: ::core::fmt::Display,
+    {
+        fn describe(&self) -> String {
+            format!("{}:{}", self.1, self.0.describe())
+        }
+    }
+
+    pub struct Boxed<T>(pub Option<Box<T>>);
+
+    // Params bounded inline are bound as well
+    impl<S: Default, T: Describe<S>> Describe<S> for Boxed<T> {
+        fn describe(&self) -> String {
+            self.0
+                .as_ref()
+                .map_or("-".to_string(), |inner| inner.describe())
+        }
+    }
+}
+
+#[coinduction(show_order, Describe)]
+mod chain {
+    use super::cells::{Boxed, Hex, Tagged};
+    use super::Describe;
+
+    pub struct Left(pub Hex<u8>, pub Option<Box<Right>>);
+
+    pub struct Right(pub Tagged<Boxed<Left>, u16>);
+
+    impl<S> Describe<S> for Left
+    where
+        Hex<u8>: Describe<S>,
+        Tagged<Boxed<Left>, u16>: Describe<S>,
+    {
+        fn describe(&self) -> String {
+            let right = self.1.as_ref().map_or("-".to_string(), |right| {
+                <Tagged<Boxed<Left>, u16> as Describe<S>>::describe(&right.0)
+            });
+            format!("{} {}", <Hex<u8> as Describe<S>>::describe(&self.0), right)
+        }
+    }
+
+    impl<S> Describe<S> for Right
+    where
+        Tagged<Boxed<Left>, u16>: Describe<S>,
+    {
+        fn describe(&self) -> String {
+            <Tagged<Boxed<Left>, u16> as Describe<S>>::describe(&self.0)
+        }
+    }
+}
+
+#[test]
+fn test_concrete_arguments_resolve() {
+    use cells::*;
+    use chain::*;
+    let inner = Left(Hex(0xab), None);
+    let right = Right(Tagged(Boxed(Some(Box::new(inner))), 7));
+    assert_eq!(<Right as Describe<()>>::describe(&right), "7:AB -");
+    let left = Left(Hex(0x1f), Some(Box::new(right)));
+    assert_eq!(<Left as Describe<()>>::describe(&left), "1F 7:AB -");
+}
+
+// `U` of `Hex<U>` and `Tagged<T, U>` is bound to the concrete arguments, so the impls' bounds
+// on it are relayed for `u8` and `u16`, and the cycle closes through `Boxed<T>`
+#[test]
+fn test_typedef_params_are_bound() {
+    let order = chain::__COINDUCTION_ORDER;
+    assert!(
+        order.contains(&"u8 : :: core :: fmt :: UpperHex"),
+        "{:?}",
+        order
+    );
+    assert!(
+        order.contains(&"u16 : :: core :: fmt :: Display"),
+        "{:?}",
+        order
+    );
+    assert!(
+        !order
+            .iter()
+            .any(|step| step.starts_with("U :") || step.starts_with("T :")),
+        "{:?}",
+        order
+    );
+    let cycles: Vec<_> = order
+        .iter()
+        .filter(|step| step.starts_with("cycle("))
+        .collect();
+    assert!(!cycles.is_empty(), "{:?}", order);
+    for cycle in cycles {
+        for member in [
+            "Left : Describe < S >",
+            "Boxed < Left > : Describe < S >",
+            "Tagged < Boxed < Left > , u16 > : Describe < S >",
+        ] {
+            assert!(cycle.contains(member), "{:?}", order);
+        }
+    }
+}
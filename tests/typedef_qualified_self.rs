@@ -0,0 +1,86 @@
+use coinduction::*;
+
+#[traitdef]
+trait Area {
+    fn area(&self) -> u32;
+}
+
+impl Area for u32 {
+    fn area(&self) -> u32 {
+        *self
+    }
+}
+
+#[typedef(Area)]
+pub mod shapes {
+    use super::Area;
+
+    pub struct Scaled<T>(pub u32, pub T);
+    pub struct Framed<T>(pub T);
+
+    impl<T> Area for self::Scaled<T>
+    where
+        T: Area,
+    {
+        fn area(&self) -> u32 {
+            self.0 * self.1.area()
+        }
+    }
+
+    impl<T> Area for crate::shapes::Framed<T>
+    where
+        T: Area,
+    {
+        fn area(&self) -> u32 {
+            self.0.area() + 1
+        }
+    }
+}
+
+#[coinduction(show_order, Area)]
+mod figure {
+    use super::shapes::{Framed, Scaled};
+    use super::Area;
+
+    pub enum Figure {
+        Unit,
+        Scaled(Box<Scaled<Figure>>),
+        Framed(Box<Framed<Figure>>),
+    }
+
+    impl Area for Figure
+    where
+        Scaled<Figure>: Area,
+        Framed<Figure>: Area,
+    {
+        fn area(&self) -> u32 {
+            match self {
+                Figure::Unit => 1,
+                Figure::Scaled(scaled) => scaled.area(),
+                Figure::Framed(framed) => framed.area(),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_qualified_self_types() {
+    use figure::Figure;
+    use shapes::{Framed, Scaled};
+    let figure = Figure::Scaled(Box::new(Scaled(
+        3,
+        Figure::Framed(Box::new(Framed(Figure::Unit))),
+    )));
+    assert_eq!(figure.area(), 6);
+    assert_eq!(Scaled(2, Framed(4u32)).area(), 10);
+}
+
+// Both bounds are rewritten by the rules of the typedef module, which close the cycle
+#[test]
+fn test_qualified_self_rules_are_applied() {
+    let order = figure::__COINDUCTION_ORDER;
+    assert_eq!(order.len(), 1, "{:?}", order);
+    assert!(order[0].starts_with("cycle("), "{:?}", order);
+    assert!(order[0].contains("Scaled < Figure > : Area"));
+    assert!(order[0].contains("Framed < Figure > : Area"));
+}
@@ -0,0 +1,84 @@
+use coinduction::*;
+
+#[traitdef]
+trait Area {
+    fn area(&self) -> u32;
+}
+
+#[typedef(Area)]
+pub mod shapes {
+    use super::Area;
+
+    pub struct Square(pub u32);
+
+    impl Area for Square {
+        fn area(&self) -> u32 {
+            self.0 * self.0
+        }
+    }
+
+    pub struct Stack<T>(pub Vec<T>);
+
+    impl<T> Area for Stack<T>
+    where
+        T: Area,
+    {
+        fn area(&self) -> u32 {
+            self.0.iter().map(Area::area).sum()
+        }
+    }
+}
+
+// Every flag travels in the tokens trailing the coinduction path, which the typedef macro forwards
+#[coinduction(dump_relay, show_order, stats, Area)]
+mod layout {
+    use super::shapes::{Square, Stack};
+    use super::Area;
+
+    pub enum Panel {
+        Tile(Square),
+        Column(Stack<Panel>),
+    }
+
+    impl Area for Panel
+    where
+        Square: Area,
+        Stack<Panel>: Area,
+    {
+        fn area(&self) -> u32 {
+            match self {
+                Panel::Tile(square) => square.area(),
+                Panel::Column(stack) => stack.area(),
+            }
+        }
+    }
+}
+
+// The trait macro hands each constraint to the typedef macro of its type, which resumes the
+// resolution with the rest of the working list
+#[test]
+fn test_typedef_macros_resume_the_relay() {
+    let stats = layout::__COINDUCTION_STATS;
+    assert!(stats.contains("reentries=2 "), "{}", stats);
+    assert!(stats.contains("working_list=2 "), "{}", stats);
+}
+
+#[test]
+fn test_typedef_predicates_reach_the_final_step() {
+    let order = layout::__COINDUCTION_ORDER;
+    let cycle = order
+        .iter()
+        .find(|step| step.starts_with("cycle("))
+        .expect("the cycle is listed");
+    assert!(cycle.contains("Panel : Area"), "{:?}", order);
+    assert!(cycle.contains("Stack < Panel > : Area"), "{:?}", order);
+    assert!(layout::__COINDUCTION_STATS.contains("rule"));
+}
+
+#[test]
+fn test_typedef_relay_does_not_affect_impls() {
+    use layout::Panel;
+    use shapes::{Square, Stack};
+    let panel = Panel::Column(Stack(vec![Panel::Tile(Square(2)), Panel::Tile(Square(3))]));
+    assert_eq!(panel.area(), 13);
+}
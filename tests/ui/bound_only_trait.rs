@@ -0,0 +1,38 @@
+use coinduction::*;
+
+#[traitdef]
+trait Evaluate {
+    fn evaluate(&self) -> i32;
+}
+
+#[traitdef]
+trait Describe {}
+
+// `Describe` only bounds the impls, so there is nothing of it to resolve
+#[coinduction(Evaluate, Describe)]
+mod bound_only {
+    use super::{Describe, Evaluate};
+
+    pub struct Expr;
+    pub struct Term;
+
+    impl Evaluate for Expr
+    where
+        Term: Evaluate + Describe,
+    {
+        fn evaluate(&self) -> i32 {
+            Term.evaluate()
+        }
+    }
+
+    impl Evaluate for Term
+    where
+        Expr: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            1
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,38 @@
+use coinduction::*;
+
+#[traitdef]
+trait Weigh {
+    fn weigh(&self) -> usize;
+}
+
+pub trait Functor {
+    type Apply<X>;
+}
+
+#[coinduction(Weigh)]
+mod fixpoint {
+    use super::{Functor, Weigh};
+
+    pub struct Fix<F: Functor>(pub Box<F::Apply<Fix<F>>>);
+    pub struct Cons<X>(pub u8, pub Option<X>);
+
+    impl<F: Functor> Weigh for Fix<F>
+    where
+        F::Apply<Fix<F>>: Weigh,
+    {
+        fn weigh(&self) -> usize {
+            self.0.weigh()
+        }
+    }
+
+    impl<X> Weigh for Cons<X>
+    where
+        X: Weigh,
+    {
+        fn weigh(&self) -> usize {
+            self.0 as usize + self.1.as_ref().map_or(0, |x| x.weigh())
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,5 @@
+coinduction::__next_step! {
+    "0.2.0", None, [], {::coinduction}, [], [], [], [{[u8: Clone, u8: Clone], [], []}], [], None, None, false, false
+}
+
+fn main() {}
@@ -0,0 +1,40 @@
+use coinduction::*;
+
+#[traitdef]
+trait Size {
+    fn size(&self) -> usize;
+}
+
+macro_rules! node {
+    ($name:ident) => {
+        $name
+    };
+}
+
+#[coinduction(Size)]
+mod with_macro_types {
+    use super::Size;
+
+    pub struct Left;
+    pub struct Right;
+
+    impl Size for Left
+    where
+        node!(Right): Size,
+    {
+        fn size(&self) -> usize {
+            1
+        }
+    }
+
+    impl Size for Right
+    where
+        Left: Size,
+    {
+        fn size(&self) -> usize {
+            2
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,45 @@
+use coinduction::*;
+
+pub mod a {
+    pub trait Show {
+        fn show(&self) -> String;
+    }
+}
+
+pub mod b {
+    use coinduction::traitdef;
+
+    #[traitdef]
+    pub trait Show {
+        fn show(&self) -> String;
+    }
+}
+
+// The module implements `b::Show`, which only shares its name with the trait named here
+#[coinduction(super::a::Show)]
+mod mixed {
+    use super::b;
+
+    pub struct Expr;
+    pub struct Term;
+
+    impl b::Show for Expr
+    where
+        Term: b::Show,
+    {
+        fn show(&self) -> String {
+            Term.show()
+        }
+    }
+
+    impl b::Show for Term
+    where
+        Expr: b::Show,
+    {
+        fn show(&self) -> String {
+            "term".to_string()
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,34 @@
+use coinduction::*;
+
+#[traitdef]
+trait Evaluate {
+    fn evaluate(&self) -> i32;
+}
+
+#[coinduction(super::Evaluat)]
+mod misspelled {
+    use super::Evaluate;
+
+    pub struct Expr;
+    pub struct Term;
+
+    impl Evaluate for Expr
+    where
+        Term: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            Term.evaluate()
+        }
+    }
+
+    impl Evaluate for Term
+    where
+        Expr: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            1
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,5 @@
+coinduction::__next_step! {
+    "0.2.0", None, [Vec<u8>], {::coinduction}, [], [], [], [], [], None, None, false, false
+}
+
+fn main() {}
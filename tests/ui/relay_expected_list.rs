@@ -0,0 +1,5 @@
+coinduction::__next_step! {
+    "0.2.0", None, [], {::coinduction}, Evaluate, [], [], [], [], None, None, false, false
+}
+
+fn main() {}
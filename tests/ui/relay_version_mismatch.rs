@@ -0,0 +1,5 @@
+coinduction::__next_step! {
+    "0.0.0", None, [], {::coinduction}, [], [], [], [], [], None, None, false, false
+}
+
+fn main() {}
@@ -0,0 +1,4 @@
+// What the `Check` arm of a trait macro generated by another release expands to
+coinduction::__next_step! { "0.0.0", Check }
+
+fn main() {}
@@ -0,0 +1,8 @@
+// A relay from a `#[traitdef]` macro built by a newer release of the crate
+coinduction::__next_step! {
+    "0.3.0",
+    Traitdef { appending_constraints: [] },
+    [Node: Walk], {::coinduction}, [Walk], [], [], [], [], None, None, false, false
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use coinduction::{coinduction, traitdef};
+
+#[traitdef]
+trait Show {}
+
+#[coinduction(Show)]
+mod runaway {
+    use super::Show;
+
+    pub struct Node;
+    pub struct Wrap<T>(T);
+
+    impl Show for Node where Wrap<Node>: Show {}
+
+    // Every expansion asks for a deeper `Wrap`, so the graph never stops growing
+    impl<T> Show for Wrap<T>
+    where
+        T: Show,
+        Wrap<Wrap<T>>: Show,
+    {
+    }
+}
+
+fn main() {}
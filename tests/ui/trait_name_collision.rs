@@ -0,0 +1,20 @@
+use coinduction::coinduction;
+
+mod a {
+    pub trait Show {}
+}
+
+mod b {
+    pub trait Show {}
+}
+
+#[coinduction]
+mod shown {
+    pub struct Node;
+
+    impl super::a::Show for Node {}
+
+    impl super::b::Show for Node {}
+}
+
+fn main() {}
@@ -0,0 +1,46 @@
+use coinduction::*;
+
+#[traitdef]
+trait Evaluate {
+    fn evaluate(&self) -> i32;
+}
+
+// No impl lies on a cycle, so the attribute changes nothing and warns on nightly
+#[coinduction(Evaluate)]
+mod acyclic {
+    use super::Evaluate;
+
+    pub struct Expr;
+    pub struct Term;
+
+    impl Evaluate for Expr
+    where
+        Term: Evaluate,
+    {
+        fn evaluate(&self) -> i32 {
+            Term.evaluate()
+        }
+    }
+
+    impl Evaluate for Term {
+        fn evaluate(&self) -> i32 {
+            1
+        }
+    }
+}
+
+// Without cycles on purpose
+#[coinduction(allow_trivial, Evaluate)]
+mod intended {
+    use super::Evaluate;
+
+    pub struct Leaf;
+
+    impl Evaluate for Leaf {
+        fn evaluate(&self) -> i32 {
+            1
+        }
+    }
+}
+
+fn main() {}
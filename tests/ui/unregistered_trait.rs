@@ -0,0 +1,11 @@
+use coinduction::{check_traits, traitdef};
+
+#[traitdef]
+trait Registered {}
+
+trait Unregistered {}
+
+#[check_traits(Registered, Unregistered)]
+struct Checked;
+
+fn main() {}
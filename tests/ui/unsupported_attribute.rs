@@ -0,0 +1,18 @@
+use coinduction::{coinduction, traitdef};
+
+#[traitdef]
+trait Show {}
+
+#[coinduction(Show)]
+mod gated {
+    use super::Show;
+
+    pub struct Node;
+    pub struct Arr<const N: usize>;
+
+    impl Show for Node where Arr<{ #[cfg(unix)] 4 }>: Show {}
+
+    impl<const N: usize> Show for Arr<N> where Node: Show {}
+}
+
+fn main() {}
@@ -0,0 +1,124 @@
+use coinduction::*;
+
+#[traitdef((Vec<_>) => { })]
+trait Count {
+    fn count(&self) -> usize;
+}
+
+impl<T> Count for Vec<T> {
+    fn count(&self) -> usize {
+        self.len()
+    }
+}
+
+#[traitdef(((_, $T:ty)) => { $T: Tagged })]
+trait Tagged {
+    fn tag(&self) -> &'static str;
+}
+
+impl<U, T: Tagged> Tagged for (U, T) {
+    fn tag(&self) -> &'static str {
+        self.1.tag()
+    }
+}
+
+// `'_` is an anonymous lifetime, not a wildcard
+#[traitdef((&'_ $T:ty) => { $T: Named })]
+trait Named {
+    fn name(&self) -> String;
+}
+
+impl<T: Named> Named for &'_ T {
+    fn name(&self) -> String {
+        format!("&{}", (**self).name())
+    }
+}
+
+#[coinduction(Count)]
+mod counted {
+    use super::Count;
+
+    pub struct Node;
+    pub struct Leaf;
+
+    impl Count for Node
+    where
+        Vec<Leaf>: Count,
+        Leaf: Count,
+    {
+        fn count(&self) -> usize {
+            vec![Leaf, Leaf].count()
+        }
+    }
+
+    impl Count for Leaf
+    where
+        Vec<Vec<Node>>: Count,
+        Node: Count,
+    {
+        fn count(&self) -> usize {
+            1
+        }
+    }
+}
+
+#[coinduction(Tagged)]
+mod tagged {
+    use super::Tagged;
+
+    pub struct Node;
+    pub struct Leaf;
+
+    impl Tagged for Node
+    where
+        (u8, Leaf): Tagged,
+    {
+        fn tag(&self) -> &'static str {
+            "node"
+        }
+    }
+
+    impl Tagged for Leaf
+    where
+        Node: Tagged,
+    {
+        fn tag(&self) -> &'static str {
+            "leaf"
+        }
+    }
+}
+
+#[coinduction(Named)]
+mod named {
+    use super::Named;
+
+    pub struct Node;
+    pub struct Leaf;
+
+    impl Named for Node
+    where
+        Leaf: Named,
+    {
+        fn name(&self) -> String {
+            "node".to_string()
+        }
+    }
+
+    impl Named for Leaf
+    where
+        Node: Named,
+    {
+        fn name(&self) -> String {
+            (&&Node).name()
+        }
+    }
+}
+
+#[test]
+fn test_wildcard_rule_patterns() {
+    assert_eq!(counted::Node.count(), 2);
+    assert_eq!(counted::Leaf.count(), 1);
+    assert_eq!(tagged::Node.tag(), "node");
+    assert_eq!((String::new(), tagged::Leaf).tag(), "leaf");
+    assert_eq!(named::Leaf.name(), "&node");
+}